@@ -0,0 +1,30 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#pragma once
+
+#if defined(BUILD_EL2) && defined(BUILD_HYP_STUB)
+  #error "Staying at EL2 and leaving a hyp-stub are mutually exclusive"
+#endif
+
+/// @defgroup BootEl Registers of the exception level lunar runs at
+///
+/// By default lunar drops to EL1. When built with the `el2` feature, it stays
+/// at EL2 instead, so that the payload can be entered at EL2 as well.
+/// @{
+#if defined(BUILD_EL2)
+  #define BOOT_EL   2        ///< Exception level lunar runs at.
+  #define BOOT_VBAR vbar_el2 ///< Vector base address register.
+  #define BOOT_ELR  elr_el2  ///< Exception link register.
+  #define BOOT_SPSR spsr_el2 ///< Saved program status register.
+  #define BOOT_ESR  esr_el2  ///< Exception syndrome register.
+  #define BOOT_FAR  far_el2  ///< Fault address register.
+#else
+  #define BOOT_EL   1        ///< Exception level lunar runs at.
+  #define BOOT_VBAR vbar_el1 ///< Vector base address register.
+  #define BOOT_ELR  elr_el1  ///< Exception link register.
+  #define BOOT_SPSR spsr_el1 ///< Saved program status register.
+  #define BOOT_ESR  esr_el1  ///< Exception syndrome register.
+  #define BOOT_FAR  far_el1  ///< Fault address register.
+#endif
+/// @}
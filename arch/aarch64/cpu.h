@@ -0,0 +1,19 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#pragma once
+
+/// Mask of the affinity fields (Aff3, Aff2, Aff1 and Aff0) of MPIDR_EL1.
+#define MPIDR_AFFINITY_MASK (0xFF00FFFFFF)
+
+/// Offset of the `boot_cpuid_phys` field within the FDT header.
+#define FDT_BOOT_CPUID_PHYS (28)
+
+/// Value of the CurrentEL register when executing at EL3.
+#define CURRENTEL_EL3 (0xC)
+
+/// Size of a single entry of `smp_boot_args`.
+#define BOOT_ARGS_SIZE (24)
+
+/// Value of the hardware identifier of an unused `smp_boot_args` entry.
+#define BOOT_ARGS_UNUSED (-1)
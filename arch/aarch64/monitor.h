@@ -0,0 +1,34 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#pragma once
+
+/// Maximum number of cores managed by the EL3 monitor.
+///
+/// Must match the length of `monitor_cores`, see: `src/arch/aarch64/monitor.rs`.
+#define MONITOR_MAX_CPUS (8)
+
+/// Size of the EL3 stack of each core.
+#define MONITOR_STACK_SIZE (0x1000)
+
+/// Size of a single entry of `monitor_cores`.
+#define MONITOR_SLOT_SIZE (32)
+
+/// Value of the hardware identifier of an unclaimed `monitor_cores` entry.
+#define MONITOR_SLOT_FREE (-1)
+
+/// Offset of the power state within a `monitor_cores` entry.
+#define MONITOR_SLOT_STATE (8)
+
+/// Offset of the entry point within a `monitor_cores` entry.
+#define MONITOR_SLOT_ENTRY (16)
+
+/// Offset of the context identifier within a `monitor_cores` entry.
+#define MONITOR_SLOT_CONTEXT (24)
+
+/// @defgroup MonitorStates Power states of cores managed by the monitor
+/// @{
+#define MONITOR_STATE_OFF        (0) ///< Core waits in `monitor_pen`.
+#define MONITOR_STATE_ON_PENDING (1) ///< Core was given an entry point.
+#define MONITOR_STATE_ON         (2) ///< Core runs below EL3.
+/// @}
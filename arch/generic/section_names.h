@@ -11,3 +11,12 @@
 
 /// Name of the section containing early initialization arena.
 #define SNAME_START_ARENA .start.arena
+
+/// Name of the section containing code placed at the very start of the image.
+#define SNAME_HEAD_TEXT .head.text
+
+/// Name of the section containing descriptors of devicetree drivers.
+#define SNAME_DRIVERS .drivers
+
+/// Name of the section containing the function symbol table.
+#define SNAME_SYMBOLS .symbols.rodata
@@ -21,6 +21,16 @@
   		__estart = .;             \
   	}
 
+  /// Declare head text section.
+  ///
+  /// This is a section with a minimal amount of code, placed at the very start
+  /// of the image, for platforms where firmware jumps to the load address
+  /// instead of the ELF entry point.
+  #define SECTION_HEAD_TEXT                \
+  	SNAME_HEAD_TEXT : {               \
+  		KEEP(*(SNAME_HEAD_TEXT))  \
+  	}
+
   /// Section for early init allocations.
   ///
   /// This section will be reclaimed once the setup is complete.
@@ -43,6 +53,30 @@
   		__etext = .; \
   	}
 
+  /// Declare a section with descriptors of devicetree drivers.
+  ///
+  /// Drivers register themselves by placing a descriptor in this section, see
+  /// `register_driver!` in `src/drivers.rs`. The descriptors form an array
+  /// walked when probing devices.
+  #define SECTION_DRIVERS              \
+  	SNAME_DRIVERS : ALIGN(8) {     \
+  		__drivers = .;         \
+  		KEEP(*(SNAME_DRIVERS)) \
+  		__edrivers = .;        \
+  	}
+
+  /// Declare a section with the function symbol table.
+  ///
+  /// The table is generated by the build script, see `build/symtab.rs`. It
+  /// lies outside of the start text so that early code remains symbolized
+  /// once that is reclaimed.
+  #define SECTION_SYMBOLS              \
+  	SNAME_SYMBOLS : ALIGN(8) {     \
+  		__symbols = .;         \
+  		KEEP(*(SNAME_SYMBOLS)) \
+  		__esymbols = .;        \
+  	}
+
   /// Declare a section with an embedded Devicetree blob.
   #define SECTION_DTB              \
   	SNAME_DTB : ALIGN(8) {     \
@@ -56,6 +90,7 @@
   	.data : {            \
   		__data = .;  \
   		*(.data*)    \
+  		*(.sdata*)   \
   		__edata = .; \
   	}
 
@@ -65,6 +100,7 @@
   	.bss (NOLOAD) : ALIGN(align) { \
   		__bss = .;             \
   		*(.bss*)               \
+  		*(.sbss*)              \
   		*(COMMON)              \
 		. = ALIGN(align);      \
 		__ebss = .;            \
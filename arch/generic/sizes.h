@@ -6,3 +6,4 @@
 #define _1_KiB (0x400)
 #define _2_KiB (0x800)
 #define _4_KiB (0x1000)
+#define _32_KiB (0x8000)
@@ -0,0 +1,34 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#pragma once
+
+/// Value of the `sstatus.FS` field marking the FP unit as enabled and clean.
+#define SSTATUS_FS_INITIAL (0x2000)
+
+/// Size of a single entry of the `smp_spin_table`.
+#define SPIN_SLOT_SIZE (16)
+
+/// Offset of the entry point within an entry of the `smp_spin_table`.
+#define SPIN_SLOT_ENTRY (8)
+
+/// Value of the hart identifier of the last `smp_spin_table` entry.
+#define SPIN_SLOT_END (-2)
+
+/// Size of a single entry of `smp_boot_args`.
+#define BOOT_ARGS_SIZE (24)
+
+/// Value of the hart identifier of an unused `smp_boot_args` entry.
+#define BOOT_ARGS_UNUSED (-1)
+
+/// Value of the `mstatus.MPP` field selecting S-mode as the previous mode.
+#define MSTATUS_MPP_S (0x800)
+
+/// Mask of the `mstatus.MPP` field.
+#define MSTATUS_MPP_MASK (0x1800)
+
+/// `mstatus.MPIE` bit, enabling M-mode interrupts upon `MRET`.
+#define MSTATUS_MPIE (0x80)
+
+/// `mie.MSIE` bit, enabling M-mode software interrupts.
+#define MIE_MSIE (0x8)
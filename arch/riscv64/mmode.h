@@ -0,0 +1,46 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#pragma once
+
+/// Maximum number of harts managed by the M-mode firmware.
+///
+/// Harts are indexed by their identifiers, which must thus be lower than this.
+/// Must match the length of `mmode_harts`, see: `src/arch/riscv64/mmode.rs`.
+#define MMODE_MAX_CPUS (8)
+
+/// Size of the M-mode stack of each hart.
+#define MMODE_STACK_SIZE (0x1000)
+
+/// Size of `mmode_stacks`, a power of two so that a single PMP entry covers it.
+#define MMODE_STACKS_SIZE (MMODE_MAX_CPUS * MMODE_STACK_SIZE)
+
+/// Binary logarithm of the size of a single entry of `mmode_harts`.
+#define MMODE_SLOT_SHIFT (5)
+
+/// Offset of the HSM state within a `mmode_harts` entry.
+#define MMODE_SLOT_STATE (0)
+
+/// Offset of the entry point within a `mmode_harts` entry.
+#define MMODE_SLOT_ENTRY (8)
+
+/// Offset of the opaque argument within a `mmode_harts` entry.
+#define MMODE_SLOT_OPAQUE (16)
+
+/// @defgroup MmodeStates HSM states of harts managed by the firmware
+///
+/// Values match the ones returned by SBI `HART_GET_STATUS`.
+/// @{
+#define MMODE_STATE_STARTED       (0) ///< Hart runs below M-mode.
+#define MMODE_STATE_STOPPED       (1) ///< Hart waits in `mmode_pen`.
+#define MMODE_STATE_START_PENDING (2) ///< Hart was given an entry point.
+/// @}
+
+/// Size of a trap frame: `mstatus`, `x1-x31`, `f0-f31` and `fcsr`, rounded up.
+#define MMODE_FRAME_SIZE (66 * 8)
+
+/// Offset of `f0` within a trap frame.
+#define MMODE_FRAME_FREGS (32 * 8)
+
+/// Offset of `fcsr` within a trap frame.
+#define MMODE_FRAME_FCSR (64 * 8)
@@ -0,0 +1,10 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#if defined(BUILD_M_MODE)
+  // QEMU enters `-bios` firmware at the beginning of RAM.
+  IMAGE_ADDRESS = 0x80000000;
+#else
+  // OpenSBI `fw_jump` enters its payload past its own reserved memory.
+  IMAGE_ADDRESS = 0x80200000;
+#endif
@@ -4,6 +4,7 @@
 mod cargo;
 mod commands;
 mod sources;
+mod symtab;
 
 use std::env;
 use std::path::PathBuf;
@@ -11,6 +12,14 @@ use std::process::Command;
 
 use cc;
 
+/// Cargo features selecting the privilege level setup and their defines.
+const BOOT_EL_FEATURES: [(&str, &str); 4] = [
+    ("CARGO_FEATURE_EL2", "BUILD_EL2"),
+    ("CARGO_FEATURE_HYP_STUB", "BUILD_HYP_STUB"),
+    ("CARGO_FEATURE_EL3_MONITOR", "BUILD_EL3_MONITOR"),
+    ("CARGO_FEATURE_M_MODE", "BUILD_M_MODE"),
+];
+
 fn archdir(arch: &str) -> PathBuf {
     PathBuf::from("arch").join(arch)
 }
@@ -38,6 +47,17 @@ fn build_asm(arch: &str, dtbo: &PathBuf) -> Result<(), ()> {
         cc.flag("-mabi=lp64d");
     }
 
+    for (feature, define) in BOOT_EL_FEATURES {
+        if env::var_os(feature).is_some() {
+            cc.define(define, None);
+        }
+    }
+
+    if let Ok(cpuid) = env::var("BOOT_CPUID") {
+        cc.define("BUILD_BOOT_CPUID", Some(cpuid.as_str()));
+        cargo::info!("BUILD_BOOT_CPUID: {cpuid}");
+    }
+
     cc.flags(["-x", "assembler-with-cpp"])
         .include(&archdir(arch))
         .include(&arch_generic_dir())
@@ -93,12 +113,20 @@ fn cons_lds(out: &PathBuf, arch: &str, board: &str) -> Result<PathBuf, ()> {
         cargo::rerun_if_changed!(file);
     }
 
-    commands::Cpp::new(&out.join("lunar.lds"), &input)
+    let mut cpp = commands::Cpp::new(&lds, &input)
         .define("__LINKER_SCRIPT__", None)
         .include(&archdir(arch))
         .include(&arch_generic_dir())
-        .include(&boarddir(board))
-        .run()?;
+        .include(&boarddir(board));
+
+    // Boards may place the image differently depending on the boot mode.
+    for (feature, define) in BOOT_EL_FEATURES {
+        if env::var_os(feature).is_some() {
+            cpp = cpp.define(define, None);
+        }
+    }
+
+    cpp.run()?;
 
     Ok(lds)
 }
@@ -151,11 +179,15 @@ fn do_main(out: &PathBuf, arch: &str, board: &str) -> Result<(), ()> {
 
     export_sections(out)?;
 
+    let symbols = symtab::generate(out)?;
+    cargo::rustc_env!("BUILD_SYMBOLS", symbols.display().to_string());
+
     Ok(())
 }
 
 fn main() {
     cargo::rerun_if_env_changed!("BOARD");
+    cargo::rerun_if_env_changed!("BOOT_CPUID");
     cargo::rerun_if_changed!(file!());
 
     let out = PathBuf::from(env::var("OUT_DIR").unwrap());
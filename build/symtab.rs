@@ -0,0 +1,242 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::bprintln;
+use crate::cargo;
+
+/// Space reserved for the symbol table, see `SYMBOLS_SIZE` in
+/// `src/symbols.rs`, which must be the same.
+const SYMBOLS_SIZE: usize = 0x60000;
+
+/// Magic number opening a filled-in symbol table.
+const MAGIC: &[u8; 4] = b"LSYM";
+
+/// Environment variable marking the first link pass.
+const PASS_ENV: &str = "LUNAR_SYMTAB_PASS";
+
+/// ELF identification and header fields, for 64-bit little endian files.
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const E_SHOFF: usize = 0x28;
+const E_SHENTSIZE: usize = 0x3A;
+const E_SHNUM: usize = 0x3C;
+
+/// ELF section header fields.
+const SH_TYPE: usize = 0x04;
+const SH_OFFSET: usize = 0x18;
+const SH_SIZE: usize = 0x20;
+const SH_LINK: usize = 0x28;
+const SHT_SYMTAB: u32 = 2;
+
+/// ELF symbol fields.
+const ST_NAME: usize = 0x00;
+const ST_INFO: usize = 0x04;
+const ST_SHNDX: usize = 0x06;
+const ST_VALUE: usize = 0x08;
+const ST_SIZE: usize = 0x10;
+const SYM_SIZE: usize = 0x18;
+const STT_FUNC: u8 = 2;
+
+fn read_u16(data: &[u8], off: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(off..off + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], off: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(off..off + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], off: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(off..off + 8)?.try_into().ok()?))
+}
+
+/// Collect sized function symbols of an ELF, by address.
+///
+/// Aliases share an address, the first one in the symbol table is kept. Names
+/// are demangled, without the hashes of legacy Rust mangling.
+fn symbols(elf: &[u8]) -> Option<BTreeMap<u64, (u64, String)>> {
+    if !elf.starts_with(ELF_MAGIC)
+        || elf.get(4..6) != Some(&[ELFCLASS64, ELFDATA2LSB])
+    {
+        return None;
+    }
+
+    let shoff = read_u64(elf, E_SHOFF)? as usize;
+    let shentsize = read_u16(elf, E_SHENTSIZE)? as usize;
+    let shnum = read_u16(elf, E_SHNUM)? as usize;
+    let section = |idx: usize| {
+        let sh = elf.get(shoff + idx * shentsize..)?;
+        let off = read_u64(sh, SH_OFFSET)? as usize;
+        let size = read_u64(sh, SH_SIZE)? as usize;
+
+        Some((sh, elf.get(off..off + size)?))
+    };
+
+    let (sh, symtab) = (0..shnum)
+        .filter_map(section)
+        .find(|(sh, _)| read_u32(sh, SH_TYPE) == Some(SHT_SYMTAB))?;
+    let (_, strtab) = section(read_u32(sh, SH_LINK)? as usize)?;
+
+    let mut syms = BTreeMap::new();
+
+    for sym in symtab.chunks_exact(SYM_SIZE) {
+        let addr = read_u64(sym, ST_VALUE)?;
+        let size = read_u64(sym, ST_SIZE)?;
+
+        if sym[ST_INFO] & 0xF != STT_FUNC
+            || read_u16(sym, ST_SHNDX)? == 0
+            || size == 0
+            || syms.contains_key(&addr)
+        {
+            continue;
+        }
+
+        let name = strtab.get(read_u32(sym, ST_NAME)? as usize..)?;
+        let len = name.iter().position(|byte| *byte == 0)?;
+        let name = std::str::from_utf8(&name[..len]).ok()?;
+
+        if name.starts_with(['.', '$']) {
+            continue;
+        }
+
+        let name = format!("{:#}", rustc_demangle::demangle(name));
+        syms.insert(addr, (size, name));
+    }
+
+    Some(syms)
+}
+
+/// Lay a symbol table out, see `src/symbols.rs` for the reading side.
+///
+/// The table consists of a header, entries sorted by address and names. All
+/// values are little endian.
+///
+/// ```text
+/// header: magic "LSYM" (u32), symbol count (u32), base address (u64)
+/// entry:  offset from base (u32), size (u32), offset of name (u32)
+/// names:  NUL-terminated, following the last entry
+/// ```
+fn build_table(syms: &BTreeMap<u64, (u64, String)>) -> Vec<u8> {
+    let base = syms.keys().next().copied().unwrap_or(0);
+    let mut entries = Vec::new();
+    let mut names = Vec::new();
+
+    for (addr, (size, name)) in syms {
+        entries.extend(((addr - base) as u32).to_le_bytes());
+        entries.extend((*size as u32).to_le_bytes());
+        entries.extend((names.len() as u32).to_le_bytes());
+
+        names.extend(name.as_bytes());
+        names.push(0);
+    }
+
+    let mut table = MAGIC.to_vec();
+    table.extend((syms.len() as u32).to_le_bytes());
+    table.extend(base.to_le_bytes());
+    table.extend(entries);
+    table.extend(names);
+
+    table
+}
+
+/// Link the image once with an empty symbol table and return the path of the
+/// resulting ELF.
+///
+/// The first pass is a nested build of the same package, target, profile and
+/// features, in a separate target directory. Its build script sees
+/// [`PASS_ENV`] and leaves the table empty instead of recursing.
+fn first_pass(out: &PathBuf) -> Result<PathBuf, ()> {
+    let cargo = env::var("CARGO").unwrap();
+    let manifest = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("Cargo.toml");
+    let target = env::var("TARGET").unwrap();
+    let profile = env::var("PROFILE").unwrap();
+    let name = env::var("CARGO_PKG_NAME").unwrap();
+    let dir = out.join("symtab");
+
+    let features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            let feature = key.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+
+    let mut cmd = Command::new(cargo);
+    cmd.args(["build", "--bin", &name, "--no-default-features"])
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .args(["--target", &target])
+        .arg("--target-dir")
+        .arg(&dir)
+        .args(["--features", &features.join(",")])
+        .env(PASS_ENV, "1")
+        // Linting wrappers such as the one of `cargo clippy` skip code
+        // generation, so there would be nothing to read symbols from.
+        .env_remove("RUSTC_WORKSPACE_WRAPPER");
+
+    if profile == "release" {
+        cmd.arg("--release");
+    }
+
+    if !cmd.status().is_ok_and(|result| result.success()) {
+        cargo::error!("Failed to link the image for its symbol table");
+        return Err(());
+    }
+
+    Ok(dir.join(target).join(profile).join(name))
+}
+
+/// Generate the function symbol table embedded in the image.
+///
+/// The table depends on where functions end up, which is only known after
+/// linking. The image is therefore linked twice: first with an empty table,
+/// which the symbols are read from, then with the table filled in. The table
+/// has a fixed size, so filling it in moves nothing.
+///
+/// Returns the path of the table, in the format of `src/symbols.rs`.
+pub fn generate(out: &PathBuf) -> Result<PathBuf, ()> {
+    let path = out.join("symbols.bin");
+    let mut table = Vec::new();
+
+    // The table is regenerated whenever the code changes.
+    cargo::rerun_if_changed!(PathBuf::from("src"));
+    cargo::rerun_if_changed!(PathBuf::from("Cargo.toml"));
+    cargo::rerun_if_env_changed!("{PASS_ENV}");
+
+    if env::var_os(PASS_ENV).is_none() {
+        let elf = first_pass(out)?;
+
+        let Some(syms) = std::fs::read(&elf).ok().and_then(|e| symbols(&e))
+        else {
+            cargo::error!("Failed to read symbols of {elf:?}");
+            return Err(());
+        };
+
+        table = build_table(&syms);
+
+        if table.len() > SYMBOLS_SIZE {
+            cargo::error!(
+                "Symbol table of {} bytes exceeds {SYMBOLS_SIZE} reserved, \
+                 raise SYMBOLS_SIZE",
+                table.len()
+            );
+            return Err(());
+        }
+
+        cargo::info!("BUILD_SYMBOLS: {} of {SYMBOLS_SIZE} bytes", table.len());
+    }
+
+    table.resize(SYMBOLS_SIZE, 0);
+
+    if std::fs::write(&path, &table).is_err() {
+        cargo::error!("Failed to generate {path:?}");
+        return Err(());
+    }
+
+    Ok(path)
+}
@@ -0,0 +1,229 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+pub mod exception;
+#[cfg(feature = "el3-monitor")]
+pub mod monitor;
+pub mod psci;
+pub mod smccc;
+pub mod smp;
+pub mod timer;
+
+use core::arch::asm;
+
+use crate::fdt::{self, patch};
+
+/// Mask of the affinity fields (Aff3, Aff2, Aff1 and Aff0) of MPIDR_EL1.
+const MPIDR_AFFINITY_MASK: u64 = 0xFF_00FF_FFFF;
+
+/// Initialize firmware interfaces of the architecture.
+///
+/// Must be called after [`crate::fdt::init`].
+pub fn init() {
+    #[cfg(feature = "el3-monitor")]
+    monitor::init();
+    psci::init();
+}
+
+/// Obtain hardware identifier of the current core.
+///
+/// On AArch64 this is the affinity part of the MPIDR_EL1 register, which is
+/// also the value used in `reg` properties of `/cpus` devicetree nodes.
+pub fn cpu_hwid() -> u64 {
+    let mpidr: u64;
+
+    unsafe {
+        asm!("mrs {}, mpidr_el1", out(reg) mpidr, options(nomem, nostack));
+    }
+
+    mpidr & MPIDR_AFFINITY_MASK
+}
+
+/// Obtain the exception level the current core executes at.
+pub fn current_el() -> u8 {
+    let el: u64;
+
+    unsafe {
+        asm!("mrs {}, currentel", out(reg) el, options(nomem, nostack));
+    }
+
+    ((el >> 2) & 0x3) as u8
+}
+
+/// Obtain name of the privilege level the current core executes at.
+pub fn privilege_level() -> &'static str {
+    ["EL0", "EL1", "EL2", "EL3"][current_el() as usize]
+}
+
+/// Offset of the frame pointer of the caller within a frame record.
+pub const FRAME_FP_OFFSET: isize = 0;
+
+/// Offset of the return address within a frame record.
+pub const FRAME_RA_OFFSET: isize = 8;
+
+/// Obtain value of the frame pointer, which points to the frame record of the
+/// calling function.
+#[inline(always)]
+pub fn frame_pointer() -> usize {
+    let fp: usize;
+
+    unsafe {
+        asm!("mov {}, x29", out(reg) fp, options(nomem, nostack));
+    }
+
+    fp
+}
+
+/// Write the devicetree handed off to the payload into a buffer.
+///
+/// A resident monitor amends it to advertise itself, otherwise the embedded
+/// devicetree is copied as it is. Returns size of the resulting blob.
+pub fn patch_fdt(dst: &mut [u8]) -> Result<usize, patch::Error> {
+    #[cfg(feature = "el3-monitor")]
+    if monitor::is_active() {
+        return monitor::patch_fdt(dst);
+    }
+
+    let patch = patch::Patch {
+        reserve: &[],
+        nodes: &[],
+    };
+
+    patch.apply(fdt::get(), dst)
+}
+
+/// Enter a payload on the current core, as the arm64 Linux boot protocol
+/// mandates.
+///
+/// The payload is entered with interrupts masked, `x0` holding address of its
+/// devicetree and `x1` to `x3` zeroed. That happens at the current exception
+/// level, except when a hyp-stub was left behind, which lets the payload have
+/// EL2 instead.
+///
+/// # Arguments
+///
+/// - `entry`: Address of the entry point of the payload.
+/// - `fdt`: Address of the devicetree handed off to the payload.
+pub fn enter_payload(entry: usize, fdt: usize) -> ! {
+    #[cfg(feature = "hyp-stub")]
+    if current_el() == 1 {
+        hyp_soft_restart(entry, [fdt as u64, 0, 0]);
+    }
+
+    unsafe {
+        asm!(
+            "msr daifset, #0xf",
+            "br {entry}",
+            entry = in(reg) entry,
+            in("x0") fdt,
+            in("x1") 0,
+            in("x2") 0,
+            in("x3") 0,
+            options(noreturn, nostack),
+        );
+    }
+}
+
+/// Enter a payload at EL2, through the hyp-stub left behind by lunar.
+///
+/// This issues the `HVC_SOFT_RESTART` call of the hyp-stub, which resumes
+/// execution at a given address at EL2, with interrupts masked.
+///
+/// # Arguments
+///
+/// - `entry`: Address at which execution is to be resumed.
+/// - `args`: Values of `x0` to `x2` upon entry.
+#[cfg(feature = "hyp-stub")]
+pub fn hyp_soft_restart(entry: usize, args: [u64; 3]) -> ! {
+    unsafe {
+        asm!(
+            "hvc #0",
+            in("x0") 1u64,
+            in("x1") entry,
+            in("x2") args[0],
+            in("x3") args[1],
+            in("x4") args[2],
+            options(noreturn, nostack),
+        );
+    }
+}
+
+/// Issue a semihosting call to the debugger or emulator.
+///
+/// # Arguments
+///
+/// - `op`: Operation number.
+/// - `arg`: Argument of the operation, usually address of a parameter block.
+#[cfg(feature = "semihosting")]
+pub fn semihosting_call(op: usize, arg: usize) -> isize {
+    let ret: isize;
+
+    unsafe {
+        asm!(
+            "hlt #0xf000",
+            inlateout("x0") op => ret,
+            in("x1") arg,
+            options(nostack),
+        );
+    }
+
+    ret
+}
+
+/// Put the current core into a low-power state until an event is signalled.
+pub fn wait_for_event() {
+    unsafe {
+        asm!("wfe", options(nomem, nostack));
+    }
+}
+
+/// Signal an event to all cores in the system.
+pub fn send_event() {
+    unsafe {
+        asm!("dsb sy", "sev", options(nostack));
+    }
+}
+
+/// Order memory accesses before the barrier against device accesses after it.
+///
+/// Meant for handing memory over to devices doing DMA, and back.
+pub fn io_barrier() {
+    unsafe {
+        asm!("dsb sy", options(nostack));
+    }
+}
+
+/// Put the current core into a low-power state until an interrupt is pending.
+pub fn wait_for_interrupt() {
+    unsafe {
+        asm!("dsb sy", "wfi", options(nomem, nostack));
+    }
+}
+
+/// Reset the whole system through firmware.
+///
+/// Returns only if the firmware is unable to perform the reset.
+pub fn system_reset() {
+    let _ = psci::system_reset();
+}
+
+/// Power off the whole system through firmware.
+///
+/// Returns only if the firmware is unable to power the system off.
+pub fn system_off() {
+    let _ = psci::system_off();
+}
+
+/// Unmask IRQs and FIQs on the current core.
+pub fn enable_interrupts() {
+    unsafe {
+        asm!("msr daifclr, #3", options(nomem, nostack));
+    }
+}
+
+/// Mask IRQs and FIQs on the current core.
+pub fn disable_interrupts() {
+    unsafe {
+        asm!("msr daifset, #3", options(nomem, nostack));
+    }
+}
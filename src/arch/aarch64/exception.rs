@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::fmt;
+
+use crate::backtrace::Backtrace;
+use crate::console::println;
+use crate::irq;
+use crate::symbols::Symbolized;
+
+/// Registers saved upon taking an exception.
+///
+/// Layout of this structure is shared with `asm/vectors.S`.
+#[repr(C)]
+pub struct TrapFrame {
+    /// General purpose registers `x0` to `x30`.
+    pub x: [u64; 31],
+    /// Stack pointer from before the exception.
+    pub sp: u64,
+    /// Exception link register, the address execution resumes at.
+    pub elr: u64,
+    /// Saved program status register.
+    pub spsr: u64,
+    /// Exception syndrome register.
+    pub esr: u64,
+    /// Fault address register.
+    pub far: u64,
+}
+
+impl TrapFrame {
+    /// Obtain the exception class field of the syndrome register.
+    pub fn exception_class(&self) -> u8 {
+        ((self.esr >> 26) & 0x3F) as u8
+    }
+}
+
+impl fmt::Display for TrapFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ec = self.exception_class();
+
+        writeln!(
+            f,
+            "ESR:  {:#018x} (EC {:#04x}: {})",
+            self.esr,
+            ec,
+            ec_name(ec)
+        )?;
+        writeln!(f, "FAR:  {:#018x}", self.far)?;
+        writeln!(f, "ELR:  {}", Symbolized(self.elr as usize))?;
+        writeln!(f, "SPSR: {:#018x}", self.spsr)?;
+
+        for (idx, pair) in self.x.chunks(2).enumerate() {
+            write!(f, "x{:<2}: {:#018x}", idx * 2, pair[0])?;
+
+            match pair.get(1) {
+                Some(reg) => {
+                    writeln!(f, "  x{:<2}: {:#018x}", idx * 2 + 1, reg)?
+                }
+                None => writeln!(f, "  sp : {:#018x}", self.sp)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Origin of an exception, as implied by its vector table entry group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Source {
+    CurrentSpEl0,
+    CurrentSpElx,
+    LowerAArch64,
+    LowerAArch32,
+}
+
+/// Type of an exception, as implied by its position within an entry group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Sync,
+    Irq,
+    Fiq,
+    SError,
+}
+
+/// Obtain a human-readable description of an exception class.
+fn ec_name(ec: u8) -> &'static str {
+    match ec {
+        0x00 => "unknown reason",
+        0x01 => "trapped WFI or WFE",
+        0x07 => "trapped SIMD or floating point access",
+        0x0E => "illegal execution state",
+        0x15 => "SVC from AArch64",
+        0x16 => "HVC from AArch64",
+        0x17 => "SMC from AArch64",
+        0x18 => "trapped MSR, MRS or system instruction",
+        0x20 => "instruction abort from lower EL",
+        0x21 => "instruction abort from current EL",
+        0x22 => "PC alignment fault",
+        0x24 => "data abort from lower EL",
+        0x25 => "data abort from current EL",
+        0x26 => "SP alignment fault",
+        0x2F => "SError interrupt",
+        0x30 | 0x31 => "breakpoint",
+        0x32 | 0x33 => "software step",
+        0x34 | 0x35 => "watchpoint",
+        0x3C => "BRK instruction",
+        _ => "reserved",
+    }
+}
+
+/// Exception handler called from the exception vector table.
+///
+/// # Arguments
+///
+/// - `frame`: Registers of the interrupted context.
+/// - `entry`: Index of the vector table entry that was taken.
+#[unsafe(no_mangle)]
+extern "C" fn exception_dispatch(frame: &mut TrapFrame, entry: u64) {
+    let source = match entry >> 2 {
+        0 => Source::CurrentSpEl0,
+        1 => Source::CurrentSpElx,
+        2 => Source::LowerAArch64,
+        _ => Source::LowerAArch32,
+    };
+
+    let kind = match entry & 0x3 {
+        0 => Kind::Sync,
+        1 => Kind::Irq,
+        2 => Kind::Fiq,
+        _ => Kind::SError,
+    };
+
+    match kind {
+        Kind::Irq | Kind::Fiq => irq::dispatch(),
+        Kind::Sync | Kind::SError => {
+            println!("Unhandled {kind:?} exception from {source:?}");
+            println!("{frame}");
+            Backtrace::from_frame(frame.x[29] as usize).print();
+            panic!("Unhandled exception");
+        }
+    }
+}
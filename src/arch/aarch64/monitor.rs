@@ -0,0 +1,332 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::ops::Range;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::MPIDR_AFFINITY_MASK;
+use super::psci::{self, Error};
+use crate::cpu::{self, MAX_CPUS};
+use crate::drivers::gic;
+use crate::drivers::syscon::Syscon;
+use crate::fdt;
+use crate::fdt::patch::{self, Node, Patch, Prop, Value};
+
+unsafe extern "C" {
+    // See: asm/monitor.S
+    fn monitor_pen() -> !;
+
+    // See: arch/generic/sections.lds.h
+    static __dtb: u8;
+    static __estack: u8;
+}
+
+/// Size of the EL3 stack of each core. Must match `monitor.h`.
+const STACK_SIZE: usize = 0x1000;
+
+/// Hardware identifier of an unclaimed [`CORES`] entry.
+const SLOT_FREE: u64 = u64::MAX;
+
+/// Power states of cores, as stored in [`CORES`]. Must match `monitor.h`.
+const STATE_OFF: u64 = 0;
+const STATE_ON_PENDING: u64 = 1;
+const STATE_ON: u64 = 2;
+
+/// Transient state of a core for which a `CPU_ON` call is being processed.
+const STATE_CLAIMED: u64 = 3;
+
+/// Value returned by `PSCI_VERSION`, that is 1.0.
+const PSCI_VERSION: u64 = 0x1_0000;
+
+/// Value returned by `MIGRATE_INFO_TYPE`, stating there is no Trusted OS.
+const MIGRATE_INFO_TYPE_NONE: u64 = 2;
+
+/// The `/psci` node added to the devicetree handed off to the payload.
+const PSCI_NODE: Node = Node {
+    name: "psci",
+    props: &[
+        Prop {
+            name: "compatible",
+            value: Value::Strs(&["arm,psci-1.0", "arm,psci-0.2"]),
+        },
+        Prop {
+            name: "method",
+            value: Value::Str("smc"),
+        },
+    ],
+};
+
+/// Cores known to the monitor, with their power states and entry points.
+///
+/// Entries are claimed by cores themselves in `monitor_setup`, or by `CPU_ON`
+/// calls naming cores that have not reached the monitor yet. See:
+/// `asm/monitor.S`.
+#[unsafe(export_name = "monitor_cores")]
+static CORES: [CoreSlot; MAX_CPUS] = [const { CoreSlot::new() }; MAX_CPUS];
+
+/// EL3 stacks of cores, indexed like [`CORES`].
+#[unsafe(export_name = "monitor_stacks")]
+static STACKS: StacksCell = StacksCell(UnsafeCell::new(
+    [const { Stack([0; STACK_SIZE]) }; MAX_CPUS],
+));
+
+/// Set by `monitor_setup` once the monitor is installed.
+///
+/// This happens before `.bss` is cleared, hence the explicit placement.
+#[unsafe(export_name = "monitor_active")]
+#[unsafe(link_section = ".data")]
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set by [`monitor_gic_setup`] once the distributor is set up.
+///
+/// This happens before `.bss` is cleared, hence the explicit placement.
+#[unsafe(link_section = ".data")]
+static GIC_READY: AtomicBool = AtomicBool::new(false);
+
+/// Registers used to reset and power off the system.
+///
+/// Resolved from the devicetree by [`init`], as the monitor cannot rely on it
+/// once the payload is running.
+static POWER: PowerCell = PowerCell(UnsafeCell::new(Power {
+    reset: None,
+    off: None,
+}));
+
+/// See: [`CORES`].
+#[repr(C)]
+struct CoreSlot {
+    hwid: AtomicU64,
+    state: AtomicU64,
+    entry: AtomicU64,
+    context: AtomicU64,
+}
+
+impl CoreSlot {
+    const fn new() -> Self {
+        CoreSlot {
+            hwid: AtomicU64::new(SLOT_FREE),
+            state: AtomicU64::new(STATE_OFF),
+            entry: AtomicU64::new(0),
+            context: AtomicU64::new(0),
+        }
+    }
+}
+
+#[repr(C, align(16))]
+struct Stack([u8; STACK_SIZE]);
+
+/// See: [`POWER`].
+struct Power {
+    reset: Option<Syscon>,
+    off: Option<Syscon>,
+}
+
+/// Check whether lunar was entered at EL3 and installed the monitor.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+/// Prepare the monitor for serving the payload.
+///
+/// Must be called before [`psci::init`], so that lunar itself uses the monitor
+/// instead of the interface described in its devicetree.
+pub fn init() {
+    if !is_active() {
+        return;
+    }
+
+    unsafe {
+        *POWER.0.get() = Power {
+            reset: Syscon::from_compatible("syscon-reboot"),
+            off: Syscon::from_compatible("syscon-poweroff"),
+        };
+    }
+}
+
+/// Hand the interrupt controller over to the non-secure side.
+///
+/// Called at EL3 by every core on its way to the boot EL, see:
+/// `asm/start.S`. The first one sets up the distributor, all of them set up
+/// their own interfaces. This runs before `.bss` is cleared on the primary
+/// core, so the embedded devicetree is parsed anew.
+#[unsafe(no_mangle)]
+extern "C" fn monitor_gic_setup() {
+    let Some(fdt) = fdt::embedded() else {
+        return;
+    };
+
+    let dist = GIC_READY
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_ok();
+
+    gic::secure_init(&fdt, dist);
+}
+
+/// Memory the monitor occupies, to be kept away from the payload.
+///
+/// This covers the embedded devicetree, code and data of lunar, all of which
+/// the monitor may reach while handling calls.
+pub fn resident() -> Range<u64> {
+    let start = &raw const __dtb as u64;
+    let end = &raw const __estack as u64;
+
+    start..end
+}
+
+/// Write the devicetree to be handed off to the payload into a buffer.
+///
+/// The monitor advertises itself with a `/psci` node and reserves its memory.
+/// Returns size of the resulting blob.
+pub fn patch_fdt(dst: &mut [u8]) -> Result<usize, patch::Error> {
+    let patch = Patch {
+        reserve: &[resident()],
+        nodes: &[PSCI_NODE],
+    };
+
+    patch.apply(fdt::get(), dst)
+}
+
+fn power() -> &'static Power {
+    unsafe { &*POWER.0.get() }
+}
+
+/// Find the [`CORES`] entry of a core, claiming a free one if necessary.
+fn slot(hwid: u64) -> Option<&'static CoreSlot> {
+    if let Some(slot) = CORES
+        .iter()
+        .find(|slot| slot.hwid.load(Ordering::Acquire) == hwid)
+    {
+        return Some(slot);
+    }
+
+    CORES.iter().find(|slot| {
+        match slot.hwid.compare_exchange(
+            SLOT_FREE,
+            hwid,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => true,
+            Err(current) => current == hwid,
+        }
+    })
+}
+
+fn cpu_on(hwid: u64, entry: u64, context: u64) -> Result<u64, Error> {
+    if hwid & !MPIDR_AFFINITY_MASK != 0 || cpu::index_of(hwid).is_none() {
+        return Err(Error::InvalidParameters);
+    }
+
+    let slot = slot(hwid).ok_or(Error::InternalFailure)?;
+
+    slot.state
+        .compare_exchange(
+            STATE_OFF,
+            STATE_CLAIMED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        )
+        .map_err(|state| match state {
+            STATE_ON => Error::AlreadyOn,
+            _ => Error::OnPending,
+        })?;
+
+    slot.entry.store(entry, Ordering::Relaxed);
+    slot.context.store(context, Ordering::Relaxed);
+    slot.state.store(STATE_ON_PENDING, Ordering::Release);
+    super::send_event();
+
+    Ok(0)
+}
+
+fn cpu_off() -> Result<u64, Error> {
+    let slot = slot(super::cpu_hwid()).ok_or(Error::Denied)?;
+
+    slot.state.store(STATE_OFF, Ordering::Release);
+
+    unsafe { monitor_pen() }
+}
+
+fn affinity_info(hwid: u64, level: u64) -> Result<u64, Error> {
+    if level != 0 || cpu::index_of(hwid).is_none() {
+        return Err(Error::InvalidParameters);
+    }
+
+    let state = CORES
+        .iter()
+        .find(|slot| slot.hwid.load(Ordering::Acquire) == hwid)
+        .map_or(STATE_OFF, |slot| slot.state.load(Ordering::Acquire));
+
+    match state {
+        STATE_ON => Ok(0),
+        STATE_OFF => Ok(1),
+        _ => Ok(2),
+    }
+}
+
+fn system_power(syscon: Option<Syscon>) -> Result<u64, Error> {
+    syscon.ok_or(Error::NotSupported)?.write();
+
+    loop {
+        super::wait_for_interrupt();
+    }
+}
+
+fn features(fid: u32) -> Result<u64, Error> {
+    match fid {
+        psci::FN_PSCI_VERSION
+        | psci::FN_PSCI_FEATURES
+        | psci::FN_CPU_ON
+        | psci::FN64_CPU_ON
+        | psci::FN_CPU_OFF
+        | psci::FN_AFFINITY_INFO
+        | psci::FN64_AFFINITY_INFO
+        | psci::FN_MIGRATE_INFO_TYPE => Ok(0),
+        psci::FN_SYSTEM_RESET if power().reset.is_some() => Ok(0),
+        psci::FN_SYSTEM_OFF if power().off.is_some() => Ok(0),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// Handle an SMC issued by a lower EL.
+///
+/// Called from `monitor_smc` in `asm/monitor.S` with the caller's `x0-x3`,
+/// which are replaced with the results of the call.
+#[unsafe(no_mangle)]
+extern "C" fn monitor_dispatch(regs: &mut [u64; 4]) {
+    let fid = regs[0] as u32;
+    let mut args = [regs[1], regs[2], regs[3]];
+
+    // Arguments of SMC32 calls are only 32 bits wide.
+    if fid & (1 << 30) == 0 {
+        args.iter_mut().for_each(|arg| *arg &= u32::MAX as u64);
+    }
+
+    let ret = match fid {
+        psci::FN_PSCI_VERSION => Ok(PSCI_VERSION),
+        psci::FN_PSCI_FEATURES => features(args[0] as u32),
+        psci::FN_CPU_ON | psci::FN64_CPU_ON => {
+            cpu_on(args[0], args[1], args[2])
+        }
+        psci::FN_CPU_OFF => cpu_off(),
+        psci::FN_AFFINITY_INFO | psci::FN64_AFFINITY_INFO => {
+            affinity_info(args[0], args[1])
+        }
+        psci::FN_MIGRATE_INFO_TYPE => Ok(MIGRATE_INFO_TYPE_NONE),
+        psci::FN_SYSTEM_OFF => system_power(power().off),
+        psci::FN_SYSTEM_RESET => system_power(power().reset),
+        _ => Err(Error::NotSupported),
+    };
+
+    regs[0] = ret.unwrap_or_else(|err| err.code() as i64 as u64);
+}
+
+/// See: [`STACKS`].
+#[allow(dead_code)] // Only accessed from assembly.
+struct StacksCell(UnsafeCell<[Stack; MAX_CPUS]>);
+unsafe impl Sync for StacksCell {}
+
+/// See: [`POWER`].
+struct PowerCell(UnsafeCell<Power>);
+unsafe impl Sync for PowerCell {}
@@ -0,0 +1,330 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use super::smccc::Conduit;
+use crate::fdt::{self, FdtStreamable};
+
+/// Standard function identifier of `PSCI_VERSION`, as of PSCI 0.2.
+pub(super) const FN_PSCI_VERSION: u32 = 0x8400_0000;
+
+/// Standard function identifier of `CPU_OFF`, as of PSCI 0.2.
+pub(super) const FN_CPU_OFF: u32 = 0x8400_0002;
+
+/// Standard function identifier of 32-bit `CPU_ON`, as of PSCI 0.2.
+#[cfg(feature = "el3-monitor")]
+pub(super) const FN_CPU_ON: u32 = 0x8400_0003;
+
+/// Standard function identifier of 64-bit `CPU_ON`, as of PSCI 0.2.
+pub(super) const FN64_CPU_ON: u32 = 0xC400_0003;
+
+/// Standard function identifier of 32-bit `AFFINITY_INFO`, as of PSCI 0.2.
+#[cfg(feature = "el3-monitor")]
+pub(super) const FN_AFFINITY_INFO: u32 = 0x8400_0004;
+
+/// Standard function identifier of 64-bit `AFFINITY_INFO`, as of PSCI 0.2.
+pub(super) const FN64_AFFINITY_INFO: u32 = 0xC400_0004;
+
+/// Standard function identifier of `MIGRATE_INFO_TYPE`, as of PSCI 0.2.
+#[cfg(feature = "el3-monitor")]
+pub(super) const FN_MIGRATE_INFO_TYPE: u32 = 0x8400_0006;
+
+/// Standard function identifier of `SYSTEM_OFF`, as of PSCI 0.2.
+pub(super) const FN_SYSTEM_OFF: u32 = 0x8400_0008;
+
+/// Standard function identifier of `SYSTEM_RESET`, as of PSCI 0.2.
+pub(super) const FN_SYSTEM_RESET: u32 = 0x8400_0009;
+
+/// Standard function identifier of `PSCI_FEATURES`, as of PSCI 1.0.
+#[cfg(feature = "el3-monitor")]
+pub(super) const FN_PSCI_FEATURES: u32 = 0x8400_000A;
+
+/// Firmware interface discovered from the devicetree.
+///
+/// Just like the system FDT view, this is written only once during early
+/// initialization, before any secondary cores are started.
+static PSCI: PsciCell = PsciCell(UnsafeCell::new(None));
+
+/// Errors reported by PSCI functions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    NotSupported,
+    InvalidParameters,
+    Denied,
+    AlreadyOn,
+    OnPending,
+    InternalFailure,
+    NotPresent,
+    Disabled,
+    InvalidAddress,
+    /// PSCI node is missing from the devicetree or is malformed.
+    Unavailable,
+    /// Firmware returned a value not defined by the specification.
+    Unknown(i32),
+}
+
+impl Error {
+    /// Decode a return value of a PSCI function that returns no data.
+    fn check(ret: u64) -> Result<(), Error> {
+        Error::decode(ret).map(|_| ())
+    }
+
+    /// Decode a return value of a PSCI function.
+    ///
+    /// As per SMCCC, negative values denote errors, while the rest is data.
+    fn decode(ret: u64) -> Result<u32, Error> {
+        use Error::*;
+
+        match ret as i32 {
+            data @ 0.. => Ok(data as u32),
+            -1 => Err(NotSupported),
+            -2 => Err(InvalidParameters),
+            -3 => Err(Denied),
+            -4 => Err(AlreadyOn),
+            -5 => Err(OnPending),
+            -6 => Err(InternalFailure),
+            -7 => Err(NotPresent),
+            -8 => Err(Disabled),
+            -9 => Err(InvalidAddress),
+            code => Err(Unknown(code)),
+        }
+    }
+}
+
+impl Error {
+    /// Encode an error as a return value of a PSCI function.
+    #[cfg(feature = "el3-monitor")]
+    pub(super) fn code(self) -> i32 {
+        use Error::*;
+
+        match self {
+            NotSupported | Unavailable => -1,
+            InvalidParameters => -2,
+            Denied => -3,
+            AlreadyOn => -4,
+            OnPending => -5,
+            InternalFailure => -6,
+            NotPresent => -7,
+            Disabled => -8,
+            InvalidAddress => -9,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+
+        match self {
+            NotSupported => write!(f, "not supported"),
+            InvalidParameters => write!(f, "invalid parameters"),
+            Denied => write!(f, "denied"),
+            AlreadyOn => write!(f, "already on"),
+            OnPending => write!(f, "on pending"),
+            InternalFailure => write!(f, "internal failure"),
+            NotPresent => write!(f, "not present"),
+            Disabled => write!(f, "disabled"),
+            InvalidAddress => write!(f, "invalid address"),
+            Unavailable => write!(f, "unavailable"),
+            Unknown(code) => write!(f, "unknown error {code}"),
+        }
+    }
+}
+
+/// Version of the PSCI implementation.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Power state of a core, as reported by `AFFINITY_INFO`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AffinityState {
+    On,
+    Off,
+    OnPending,
+}
+
+/// Function identifiers and the conduit used to invoke them.
+struct Psci {
+    conduit: Conduit,
+    /// Identifier of `CPU_ON`, unless the firmware does not provide it.
+    cpu_on: Option<u32>,
+    /// Identifier of `CPU_OFF`, unless the firmware does not provide it.
+    cpu_off: Option<u32>,
+    /// Whether functions standardized in PSCI 0.2 are available.
+    standard: bool,
+}
+
+impl Psci {
+    /// Obtain a standard function identifier, if supported by the firmware.
+    fn standard(&self, fid: u32) -> Result<u32, Error> {
+        if !self.standard {
+            return Err(Error::NotSupported);
+        }
+
+        Ok(fid)
+    }
+}
+
+/// Discover the PSCI interface from the `/psci` devicetree node.
+///
+/// Nodes compatible with `arm,psci-0.2` or newer use standard function
+/// identifiers. Older `arm,psci` nodes carry them as properties instead, and
+/// only cover functions defined by PSCI 0.1.
+pub fn init() {
+    // A resident monitor takes precedence over whatever the devicetree says.
+    #[cfg(feature = "el3-monitor")]
+    if super::monitor::is_active() {
+        unsafe {
+            *PSCI.0.get() = Some(Psci {
+                conduit: Conduit::Smc,
+                cpu_on: Some(FN64_CPU_ON),
+                cpu_off: Some(FN_CPU_OFF),
+                standard: true,
+            });
+        }
+
+        return;
+    }
+
+    let Some(node) = fdt::get().node_by_path("/psci") else {
+        return;
+    };
+
+    let Some(conduit) = node
+        .prop_strs("method")
+        .and_then(|mut method| method.next())
+        .and_then(Conduit::from_method)
+    else {
+        return;
+    };
+
+    // When running at EL2, HVC would be taken by lunar itself.
+    if conduit == Conduit::Hvc && super::current_el() >= 2 {
+        return;
+    }
+
+    let psci = if node.is_compatible("arm,psci-0.2") {
+        Psci {
+            conduit,
+            cpu_on: Some(FN64_CPU_ON),
+            cpu_off: Some(FN_CPU_OFF),
+            standard: true,
+        }
+    } else if node.is_compatible("arm,psci") {
+        // Identifiers are implementation defined, so there is nothing to fall
+        // back on if one is missing.
+        Psci {
+            conduit,
+            cpu_on: node.prop_u32("cpu_on"),
+            cpu_off: node.prop_u32("cpu_off"),
+            standard: false,
+        }
+    } else {
+        return;
+    };
+
+    unsafe {
+        *PSCI.0.get() = Some(psci);
+    }
+}
+
+fn get() -> Result<&'static Psci, Error> {
+    unsafe { (*PSCI.0.get()).as_ref().ok_or(Error::Unavailable) }
+}
+
+/// Power up a core and have it start execution at a given address.
+///
+/// # Arguments
+///
+/// - `hwid`: MPIDR affinity value of the target core.
+/// - `entry`: Physical address at which the core begins execution.
+/// - `context`: Value passed to the core in its `x0` register.
+pub fn cpu_on(hwid: u64, entry: usize, context: u64) -> Result<(), Error> {
+    let psci = get()?;
+    let fid = psci.cpu_on.ok_or(Error::NotSupported)?;
+
+    Error::check(psci.conduit.call(fid, hwid, entry as u64, context))
+}
+
+/// Power down the calling core.
+///
+/// On success this function does not return. Subsequent `CPU_ON` call will
+/// restart the core at the requested entry point.
+pub fn cpu_off() -> Result<(), Error> {
+    let psci = get()?;
+    let fid = psci.cpu_off.ok_or(Error::NotSupported)?;
+
+    Error::check(psci.conduit.call(fid, 0, 0, 0))
+}
+
+/// Obtain version of the PSCI implementation.
+///
+/// Firmware implementing only PSCI 0.1 has no means of reporting its version,
+/// so it is assumed based on the devicetree.
+pub fn version() -> Result<Version, Error> {
+    let psci = get()?;
+
+    if !psci.standard {
+        return Ok(Version { major: 0, minor: 1 });
+    }
+
+    let ver = Error::decode(psci.conduit.call(FN_PSCI_VERSION, 0, 0, 0))?;
+
+    Ok(Version {
+        major: (ver >> 16) as u16,
+        minor: ver as u16,
+    })
+}
+
+/// Query power state of a core.
+///
+/// # Arguments
+///
+/// - `hwid`: MPIDR affinity value of the target core.
+pub fn affinity_info(hwid: u64) -> Result<AffinityState, Error> {
+    let psci = get()?;
+    let fid = psci.standard(FN64_AFFINITY_INFO)?;
+
+    match Error::decode(psci.conduit.call(fid, hwid, 0, 0))? {
+        0 => Ok(AffinityState::On),
+        1 => Ok(AffinityState::Off),
+        2 => Ok(AffinityState::OnPending),
+        state => Err(Error::Unknown(state as i32)),
+    }
+}
+
+/// Shut down the whole system.
+///
+/// On success this function does not return.
+pub fn system_off() -> Result<(), Error> {
+    let psci = get()?;
+    let fid = psci.standard(FN_SYSTEM_OFF)?;
+
+    Error::check(psci.conduit.call(fid, 0, 0, 0))
+}
+
+/// Perform a cold reset of the whole system.
+///
+/// On success this function does not return.
+pub fn system_reset() -> Result<(), Error> {
+    let psci = get()?;
+    let fid = psci.standard(FN_SYSTEM_RESET)?;
+
+    Error::check(psci.conduit.call(fid, 0, 0, 0))
+}
+
+/// See: [`PSCI`].
+struct PsciCell(UnsafeCell<Option<Psci>>);
+unsafe impl Sync for PsciCell {}
@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+
+/// An instruction used to call into firmware, as per the SMC Calling Convention.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Conduit {
+    /// Hypervisor call, handled at EL2.
+    Hvc,
+    /// Secure monitor call, handled at EL3.
+    Smc,
+}
+
+impl Conduit {
+    /// Parse a conduit from a value of a devicetree `method` property.
+    pub fn from_method(method: &str) -> Option<Self> {
+        match method {
+            "hvc" => Some(Conduit::Hvc),
+            "smc" => Some(Conduit::Smc),
+            _ => None,
+        }
+    }
+
+    /// Issue a call with up to three arguments and return value of `x0`.
+    ///
+    /// Registers `x4` to `x17` are treated as clobbered, since SMCCC prior to
+    /// version 1.1 did not require the callee to preserve them.
+    pub fn call(self, fid: u32, a1: u64, a2: u64, a3: u64) -> u64 {
+        let ret: u64;
+
+        macro_rules! call {
+            ($insn:literal) => {
+                asm!(
+                    $insn,
+                    inlateout("x0") fid as u64 => ret,
+                    inlateout("x1") a1 => _,
+                    inlateout("x2") a2 => _,
+                    inlateout("x3") a3 => _,
+                    lateout("x4") _, lateout("x5") _, lateout("x6") _,
+                    lateout("x7") _, lateout("x8") _, lateout("x9") _,
+                    lateout("x10") _, lateout("x11") _, lateout("x12") _,
+                    lateout("x13") _, lateout("x14") _, lateout("x15") _,
+                    lateout("x16") _, lateout("x17") _,
+                    options(nostack),
+                )
+            };
+        }
+
+        unsafe {
+            match self {
+                Conduit::Hvc => call!("hvc #0"),
+                Conduit::Smc => call!("smc #0"),
+            }
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::ptr;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use core::time::Duration;
+
+use super::psci::{self, AffinityState};
+use crate::cpu::MAX_CPUS;
+use crate::fdt::{FdtNode, FdtStreamable};
+use crate::time;
+
+unsafe extern "C" {
+    // See: asm/smp.S
+    fn secondary_start();
+    fn smp_park(release: usize) -> !;
+}
+
+/// Time given to the firmware to power off a core parked with PSCI.
+const PARK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Value marking an unused [`SPIN_TABLE`] slot.
+const SLOT_UNUSED: u64 = u64::MAX;
+
+/// Value marking the last [`SPIN_TABLE`] slot.
+const SLOT_END: u64 = u64::MAX - 1;
+
+/// Release addresses of cores parked by lunar itself.
+///
+/// Cores entering lunar at reset, other than the primary one, wait in the
+/// `smp_pen` until a slot with their hardware identifier appears here. They
+/// then proceed to spin on the release address from that slot, as if it was
+/// an ordinary spin-table. Slots are indexed by logical core index, so used
+/// and unused ones may interleave. The last slot is never used and serves as a
+/// sentinel. See: `asm/smp.S`.
+#[unsafe(export_name = "smp_spin_table")]
+static SPIN_TABLE: [SpinSlot; MAX_CPUS + 1] = {
+    let mut table = [const { SpinSlot::new(SLOT_UNUSED) }; MAX_CPUS + 1];
+    table[MAX_CPUS] = SpinSlot::new(SLOT_END);
+    table
+};
+
+/// See: [`SPIN_TABLE`].
+#[repr(C)]
+struct SpinSlot {
+    hwid: AtomicU64,
+    release: AtomicU64,
+}
+
+impl SpinSlot {
+    const fn new(hwid: u64) -> Self {
+        SpinSlot {
+            hwid: AtomicU64::new(hwid),
+            release: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A method of bringing a secondary core online.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnableMethod {
+    /// Core is powered on through PSCI `CPU_ON` call.
+    Psci,
+    /// Core spins on a given address, waiting for an entry point to appear.
+    SpinTable(usize),
+}
+
+impl EnableMethod {
+    /// Read the enable method of a given `/cpus` child node.
+    pub fn from_node(node: &FdtNode) -> Option<Self> {
+        match node.prop_strs("enable-method")?.next()? {
+            "psci" => Some(EnableMethod::Psci),
+            "spin-table" => node
+                .prop_u64("cpu-release-addr")
+                .map(|addr| EnableMethod::SpinTable(addr as usize)),
+            _ => None,
+        }
+    }
+
+    /// Prepare a core for being started with [`EnableMethod::start`].
+    ///
+    /// Spin-table cores that were parked by lunar are told where their release
+    /// address is. This is a no-op for cores parked elsewhere.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hardware identifier of the core.
+    /// - `cpu`: Logical index of the core.
+    pub fn prepare(self, hwid: u64, cpu: usize) {
+        if let EnableMethod::SpinTable(release) = self {
+            let slot = &SPIN_TABLE[cpu];
+
+            slot.release.store(release as u64, Ordering::Relaxed);
+            slot.hwid.store(hwid, Ordering::Release);
+            super::send_event();
+        }
+    }
+
+    /// Start a core, directing it to the secondary entry point.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hardware identifier of the core.
+    /// - `cpu`: Logical index of the core.
+    pub fn start(self, hwid: u64, cpu: usize) -> Result<(), psci::Error> {
+        let entry = secondary_start as *const () as usize;
+
+        match self {
+            EnableMethod::Psci => psci::cpu_on(hwid, entry, cpu as u64),
+            EnableMethod::SpinTable(release) => {
+                unsafe {
+                    ptr::write_volatile(release as *mut u64, entry as u64);
+                }
+
+                super::send_event();
+                Ok(())
+            }
+        }
+    }
+
+    /// Finalize a start of a core, once it reported itself online.
+    ///
+    /// For spin-table this clears the release address, so that the core can
+    /// later be returned to spinning on it.
+    pub fn started(self) {
+        if let EnableMethod::SpinTable(release) = self {
+            unsafe {
+                ptr::write_volatile(release as *mut u64, 0);
+            }
+        }
+    }
+
+    /// Leave the calling core in a state expected by the payload.
+    ///
+    /// Cores started with PSCI are powered off, so that the payload can power
+    /// them on again. Cores started from a spin-table return to spinning on
+    /// their release address.
+    pub fn park(self) -> ! {
+        if let EnableMethod::SpinTable(release) = self {
+            unsafe { smp_park(release) }
+        }
+
+        let _ = psci::cpu_off();
+
+        loop {
+            super::wait_for_event();
+        }
+    }
+
+    /// Wait for a core that left lunar with [`EnableMethod::park`] to settle.
+    ///
+    /// For PSCI this waits for the firmware to report the core as powered off,
+    /// as a `CPU_ON` issued by the payload in the meantime would be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hardware identifier of the core.
+    pub fn wait_parked(self, hwid: u64) {
+        if let EnableMethod::Psci = self {
+            time::wait_until(PARK_TIMEOUT, || {
+                !matches!(
+                    psci::affinity_info(hwid),
+                    Ok(AffinityState::On | AffinityState::OnPending)
+                )
+            });
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::irq;
+
+/// Devicetree compatible strings of the ARM generic timer.
+const COMPATIBLE: [&str; 2] = ["arm,armv8-timer", "arm,armv7-timer"];
+
+/// Index of the non-secure EL1 physical timer in the `interrupts` property.
+const IRQ_INDEX: usize = 1;
+
+/// CNTP_CTL_EL0.ENABLE bit.
+const CNTP_CTL_ENABLE: u64 = 1 << 0;
+
+/// Locate the devicetree node describing the generic timer.
+fn node() -> Option<FdtNode<'static>> {
+    COMPATIBLE
+        .iter()
+        .find_map(|compat| fdt::get().node_by_compatible(compat))
+}
+
+/// Read the system counter.
+pub fn counter() -> u64 {
+    let cnt: u64;
+
+    unsafe {
+        asm!("isb", "mrs {}, cntpct_el0", out(reg) cnt, options(nostack));
+    }
+
+    cnt
+}
+
+/// Obtain frequency of the system counter in Hz.
+///
+/// The `clock-frequency` property of the timer node takes precedence over
+/// CNTFRQ_EL0, as the latter is left unprogrammed by some firmware.
+pub fn frequency() -> u64 {
+    if let Some(freq) = node().and_then(|node| node.prop_u32("clock-frequency"))
+    {
+        return freq as u64;
+    }
+
+    let freq: u64;
+
+    unsafe {
+        asm!("mrs {}, cntfrq_el0", out(reg) freq, options(nomem, nostack));
+    }
+
+    freq
+}
+
+/// Make the timer fire once the counter reaches a given value.
+pub fn set_alarm(ticks: u64) {
+    unsafe {
+        asm!(
+            "msr cntp_cval_el0, {}",
+            "msr cntp_ctl_el0, {}",
+            "isb",
+            in(reg) ticks,
+            in(reg) CNTP_CTL_ENABLE,
+            options(nostack),
+        );
+    }
+}
+
+/// Stop the timer from firing.
+pub fn cancel_alarm() {
+    unsafe {
+        asm!("msr cntp_ctl_el0, xzr", "isb", options(nostack));
+    }
+}
+
+/// Register a handler of the timer interrupt with the interrupt controller.
+pub fn request_irq(handler: irq::Handler) -> Result<u32, irq::Error> {
+    let node = node().ok_or(irq::Error::BadSpecifier)?;
+
+    irq::request(&node, IRQ_INDEX, handler)
+}
@@ -0,0 +1,291 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#[cfg(feature = "m-mode")]
+pub mod mmode;
+pub mod sbi;
+pub mod smp;
+pub mod timer;
+pub mod trap;
+
+use core::arch::asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::fdt::{self, FdtNode, FdtStreamable, patch};
+use crate::inttypes::BEu32;
+
+/// `sstatus.SIE` bit, enabling interrupts in S-mode.
+const SSTATUS_SIE: usize = 1 << 1;
+
+/// Arguments passed to lunar by the firmware on the primary hart.
+///
+/// These are saved by `start` in `asm/start.S` before `.bss` is cleared, hence
+/// the explicit placement.
+#[unsafe(export_name = "boot_args")]
+#[unsafe(link_section = ".data")]
+static BOOT_ARGS: BootArgs = BootArgs {
+    hartid: AtomicU64::new(0),
+    fdt: AtomicU64::new(0),
+};
+
+/// See: [`BOOT_ARGS`].
+#[repr(C)]
+struct BootArgs {
+    hartid: AtomicU64,
+    fdt: AtomicU64,
+}
+
+/// An entry of an `interrupts-extended` property pointing at a hart.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HartIrq {
+    /// Identifier of the hart owning the referenced interrupt controller.
+    pub hartid: u64,
+    /// Hart-local interrupt number, such as 9 for S-mode external interrupts.
+    pub irq: u32,
+}
+
+/// Iterate over entries of the `interrupts-extended` property of a node.
+///
+/// Entries are expected to reference `riscv,cpu-intc` nodes, which have a
+/// single interrupt cell. Entries referencing anything else yield [`None`], so
+/// that positions of the remaining ones, often serving as context indices of
+/// the device, are preserved.
+pub fn hart_irqs(node: &FdtNode) -> impl Iterator<Item = Option<HartIrq>> {
+    let mut cells =
+        node.prop_cells("interrupts-extended").into_iter().flatten();
+
+    core::iter::from_fn(move || {
+        let phandle = cells.next()?;
+        let irq = cells.next()?;
+
+        Some(hart_of_intc(phandle).map(|hartid| HartIrq { hartid, irq }))
+    })
+}
+
+/// Find the hart owning a hart-local interrupt controller with a phandle.
+fn hart_of_intc(phandle: u32) -> Option<u64> {
+    let fdt = fdt::get();
+    let intc = fdt
+        .root()
+        .node_by_phandle(BEu32::new(phandle.to_be_bytes()))?;
+
+    if !intc.is_compatible("riscv,cpu-intc") {
+        return None;
+    }
+
+    fdt.parent_of(&intc)?.reg_addr_u64()
+}
+
+/// Initialize firmware interfaces of the architecture.
+///
+/// Must be called after [`crate::fdt::init`].
+pub fn init() {
+    #[cfg(feature = "m-mode")]
+    mmode::init();
+    sbi::init();
+}
+
+/// Obtain hardware identifier of the current core.
+///
+/// On RISC-V this is the hart identifier, which is also the value used in `reg`
+/// properties of `/cpus` devicetree nodes. It is handed over by the firmware
+/// upon entry and kept in the `tp` register afterwards.
+pub fn cpu_hwid() -> u64 {
+    let hartid: u64;
+
+    unsafe {
+        asm!("mv {}, tp", out(reg) hartid, options(nomem, nostack));
+    }
+
+    hartid
+}
+
+/// Obtain name of the privilege mode the current hart executes in.
+///
+/// The mode cannot be read, but lunar only leaves S-mode to run the M-mode
+/// firmware, which has stacks of its own.
+pub fn privilege_level() -> &'static str {
+    #[cfg(feature = "m-mode")]
+    if mmode::in_firmware() {
+        return "M-mode";
+    }
+
+    "S-mode"
+}
+
+/// Offset of the frame pointer of the caller from a frame pointer.
+///
+/// A frame pointer points right past the frame record, which holds the frame
+/// pointer of the caller followed by the return address.
+pub const FRAME_FP_OFFSET: isize = -16;
+
+/// Offset of the return address from a frame pointer.
+pub const FRAME_RA_OFFSET: isize = -8;
+
+/// Obtain value of the frame pointer of the calling function.
+#[inline(always)]
+pub fn frame_pointer() -> usize {
+    let fp: usize;
+
+    unsafe {
+        asm!("mv {}, s0", out(reg) fp, options(nomem, nostack));
+    }
+
+    fp
+}
+
+/// Obtain hart identifier of the primary hart, as passed by the firmware.
+pub fn boot_hartid() -> u64 {
+    BOOT_ARGS.hartid.load(Ordering::Relaxed)
+}
+
+/// Obtain address of the devicetree passed by the firmware, if any.
+///
+/// lunar itself uses its embedded devicetree. This one describes the platform
+/// as seen by the firmware, which may have amended it at runtime.
+pub fn boot_fdt() -> Option<usize> {
+    match BOOT_ARGS.fdt.load(Ordering::Relaxed) {
+        0 => None,
+        addr => Some(addr as usize),
+    }
+}
+
+/// Write the devicetree handed off to the payload into a buffer.
+///
+/// The M-mode firmware amends it to reserve its memory, otherwise the embedded
+/// devicetree is copied as it is. Returns size of the resulting blob.
+pub fn patch_fdt(dst: &mut [u8]) -> Result<usize, patch::Error> {
+    #[cfg(feature = "m-mode")]
+    {
+        mmode::patch_fdt(dst)
+    }
+
+    #[cfg(not(feature = "m-mode"))]
+    {
+        let patch = patch::Patch {
+            reserve: &[],
+            nodes: &[],
+        };
+
+        patch.apply(fdt::get(), dst)
+    }
+}
+
+/// Enter a payload on the current hart, as the RISC-V Linux boot protocol
+/// mandates.
+///
+/// The payload is entered in S-mode with interrupts masked, `a0` holding the
+/// hart identifier and `a1` address of its devicetree.
+///
+/// # Arguments
+///
+/// - `entry`: Address of the entry point of the payload.
+/// - `fdt`: Address of the devicetree handed off to the payload.
+pub fn enter_payload(entry: usize, fdt: usize) -> ! {
+    unsafe {
+        asm!(
+            "csrw sie, zero",
+            "csrc sstatus, {sie}",
+            "jr {entry}",
+            sie = in(reg) SSTATUS_SIE,
+            entry = in(reg) entry,
+            in("a0") cpu_hwid(),
+            in("a1") fdt,
+            options(noreturn, nostack),
+        );
+    }
+}
+
+/// Issue a semihosting call to the debugger or emulator.
+///
+/// The call is recognized by the `ebreak` being surrounded by a pair of
+/// marker instructions, all of them uncompressed and within a single page.
+///
+/// # Arguments
+///
+/// - `op`: Operation number.
+/// - `arg`: Argument of the operation, usually address of a parameter block.
+#[cfg(feature = "semihosting")]
+pub fn semihosting_call(op: usize, arg: usize) -> isize {
+    let ret: isize;
+
+    unsafe {
+        asm!(
+            ".balign 16",
+            ".option push",
+            ".option norvc",
+            "slli zero, zero, 0x1f",
+            "ebreak",
+            "srai zero, zero, 0x7",
+            ".option pop",
+            inlateout("a0") op => ret,
+            in("a1") arg,
+            options(nostack),
+        );
+    }
+
+    ret
+}
+
+/// Wait for a change of state made by another core.
+///
+/// RISC-V has no counterpart of the AArch64 event register, so this is merely
+/// a hint that the core is spinning.
+pub fn wait_for_event() {
+    core::hint::spin_loop();
+}
+
+/// Make prior memory accesses visible to all cores in the system.
+pub fn send_event() {
+    unsafe {
+        asm!("fence rw, rw", options(nostack));
+    }
+}
+
+/// Order memory accesses before the barrier against device accesses after it.
+///
+/// Meant for handing memory over to devices doing DMA, and back.
+pub fn io_barrier() {
+    unsafe {
+        asm!("fence iorw, iorw", options(nostack));
+    }
+}
+
+/// Put the current core into a low-power state until an interrupt is pending.
+pub fn wait_for_interrupt() {
+    unsafe {
+        asm!("wfi", options(nomem, nostack));
+    }
+}
+
+/// Reset the whole system through firmware.
+///
+/// Returns only if the firmware is unable to perform the reset.
+pub fn system_reset() {
+    let _ = sbi::system_reset(
+        sbi::ResetType::ColdReboot,
+        sbi::ResetReason::NoReason,
+    );
+}
+
+/// Power off the whole system through firmware.
+///
+/// Returns only if the firmware is unable to power the system off.
+pub fn system_off() {
+    let _ =
+        sbi::system_reset(sbi::ResetType::Shutdown, sbi::ResetReason::NoReason);
+}
+
+/// Unmask S-mode interrupts on the current core.
+pub fn enable_interrupts() {
+    unsafe {
+        asm!("csrs sstatus, {}", in(reg) SSTATUS_SIE, options(nomem, nostack));
+    }
+}
+
+/// Mask S-mode interrupts on the current core.
+pub fn disable_interrupts() {
+    unsafe {
+        asm!("csrc sstatus, {}", in(reg) SSTATUS_SIE, options(nomem, nostack));
+    }
+}
@@ -0,0 +1,506 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+use core::cell::UnsafeCell;
+use core::ops::Range;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::sbi::{self, Error};
+use crate::cpu::MAX_CPUS;
+use crate::drivers::aclint::Clint;
+use crate::drivers::ns16550::Ns16550;
+use crate::drivers::serial::{self, Serial};
+use crate::drivers::syscon::Syscon;
+use crate::fdt;
+use crate::fdt::patch::{self, Patch};
+
+unsafe extern "C" {
+    // See: asm/mmode.S
+    fn mmode_pen() -> !;
+
+    // See: arch/generic/sections.lds.h
+    static __dtb: u8;
+    static __estack: u8;
+}
+
+/// Size of the M-mode stack of each hart. Must match `mmode.h`.
+const STACK_SIZE: usize = 0x1000;
+
+/// HSM states of harts, as stored in [`HARTS`]. Must match `mmode.h`.
+const STATE_STARTED: u64 = 0;
+const STATE_STOPPED: u64 = 1;
+const STATE_START_PENDING: u64 = 2;
+
+/// Transient state of a hart for which a `HART_START` call is being processed.
+const STATE_CLAIMED: u64 = u64::MAX;
+
+/// Value returned by `GET_SPEC_VERSION`, that is 2.0.
+const SPEC_VERSION: usize = 2 << 24;
+
+/// Value returned by `GET_IMPL_ID`.
+///
+/// lunar has no implementation identifier assigned, so this is "luna" in
+/// ASCII, well clear of the registered ones.
+const IMPL_ID: usize = 0x6C75_6E61;
+
+/// Value of `hart_mask_base` addressing all harts in `SEND_IPI`.
+const HART_MASK_ALL: usize = usize::MAX;
+
+/// `mcause` bit set for interrupts.
+const MCAUSE_INTERRUPT: usize = 1 << 63;
+
+/// Interrupt and exception codes found in `mcause`.
+const IRQ_M_SOFT: usize = 3;
+const IRQ_M_TIMER: usize = 7;
+const EXC_ECALL_S: usize = 9;
+
+/// Bits of the `mip` and `mie` registers.
+const MIP_SSIP: usize = 1 << 1;
+const MIP_STIP: usize = 1 << 5;
+const MIE_MTIE: usize = 1 << 7;
+
+/// Harts managed by the firmware, indexed by hart identifier.
+///
+/// Harts other than the primary one start off stopped, waiting in `mmode_pen`.
+/// See: `asm/mmode.S`.
+#[unsafe(export_name = "mmode_harts")]
+static HARTS: [HartSlot; MAX_CPUS] = [const { HartSlot::new() }; MAX_CPUS];
+
+/// M-mode stacks of harts, indexed like [`HARTS`].
+///
+/// S-mode is denied access to these with a single PMP entry, hence alignment.
+#[unsafe(export_name = "mmode_stacks")]
+static STACKS: StacksCell = StacksCell(UnsafeCell::new(Stacks(
+    [const { Stack([0; STACK_SIZE]) }; MAX_CPUS],
+)));
+
+/// Supervisor software interrupts requested with `SEND_IPI`, indexed like
+/// [`HARTS`].
+static IPI_PENDING: [AtomicBool; MAX_CPUS] =
+    [const { AtomicBool::new(false) }; MAX_CPUS];
+
+/// Devices driven by the firmware on behalf of S-mode.
+///
+/// Resolved from the devicetree by [`init`], as the firmware cannot rely on it
+/// once the payload is running.
+static PLATFORM: PlatformCell = PlatformCell(UnsafeCell::new(Platform {
+    clint: None,
+    uart: None,
+    reset: None,
+    off: None,
+}));
+
+/// See: [`HARTS`].
+#[repr(C, align(32))]
+struct HartSlot {
+    state: AtomicU64,
+    entry: AtomicU64,
+    opaque: AtomicU64,
+}
+
+impl HartSlot {
+    const fn new() -> Self {
+        HartSlot {
+            state: AtomicU64::new(STATE_STOPPED),
+            entry: AtomicU64::new(0),
+            opaque: AtomicU64::new(0),
+        }
+    }
+}
+
+#[repr(C, align(16))]
+struct Stack([u8; STACK_SIZE]);
+
+/// See: [`STACKS`].
+#[repr(C, align(0x8000))]
+struct Stacks([Stack; MAX_CPUS]);
+
+/// Registers saved by `mmode_trap_vector`, indexed by register number.
+///
+/// Floating point registers follow, but are of no interest here.
+#[repr(C)]
+struct TrapFrame {
+    regs: [usize; 32],
+}
+
+impl TrapFrame {
+    const A0: usize = 10;
+    const A1: usize = 11;
+    const A6: usize = 16;
+    const A7: usize = 17;
+}
+
+/// See: [`PLATFORM`].
+struct Platform {
+    clint: Option<Clint>,
+    uart: Option<Ns16550>,
+    reset: Option<Syscon>,
+    off: Option<Syscon>,
+}
+
+/// Prepare the firmware for serving S-mode.
+///
+/// Must be called before [`sbi::init`], so that the extensions provided by the
+/// firmware are discovered correctly.
+pub fn init() {
+    unsafe {
+        *PLATFORM.0.get() = Platform {
+            clint: Clint::probe(),
+            uart: serial::stdout()
+                .and_then(|(node, _)| Ns16550::probe(&node).ok()),
+            reset: Syscon::from_compatible("syscon-reboot"),
+            off: Syscon::from_compatible("syscon-poweroff"),
+        };
+    }
+}
+
+/// Memory the firmware occupies, to be kept away from the payload.
+///
+/// This covers the embedded devicetree, code and data of lunar, all of which
+/// the firmware may reach while handling calls.
+pub fn resident() -> Range<u64> {
+    let start = &raw const __dtb as u64;
+    let end = &raw const __estack as u64;
+
+    start..end
+}
+
+/// Write the devicetree to be handed off to the payload into a buffer.
+///
+/// The firmware reserves its memory. Returns size of the resulting blob.
+pub fn patch_fdt(dst: &mut [u8]) -> Result<usize, patch::Error> {
+    let patch = Patch {
+        reserve: &[resident()],
+        nodes: &[],
+    };
+
+    patch.apply(fdt::get(), dst)
+}
+
+/// Check whether the current hart executes the firmware, that is whether it
+/// runs on its M-mode stack.
+pub fn in_firmware() -> bool {
+    let sp: usize;
+
+    unsafe {
+        asm!("mv {}, sp", out(reg) sp, options(nomem, nostack));
+    }
+
+    let start = STACKS.0.get() as usize;
+
+    (start..start + size_of::<Stacks>()).contains(&sp)
+}
+
+fn platform() -> &'static Platform {
+    unsafe { &*PLATFORM.0.get() }
+}
+
+fn hartid() -> usize {
+    let hartid: usize;
+
+    unsafe {
+        asm!("csrr {}, mhartid", out(reg) hartid, options(nomem, nostack));
+    }
+
+    hartid
+}
+
+macro_rules! read_csr {
+    ($csr:literal) => {{
+        let val: usize;
+
+        unsafe {
+            asm!(concat!("csrr {}, ", $csr), out(reg) val, options(nostack));
+        }
+
+        val
+    }};
+}
+
+macro_rules! set_csr {
+    ($csr:literal, $bits:expr) => {
+        unsafe {
+            asm!(concat!("csrs ", $csr, ", {}"), in(reg) $bits, options(nostack));
+        }
+    };
+}
+
+macro_rules! clear_csr {
+    ($csr:literal, $bits:expr) => {
+        unsafe {
+            asm!(concat!("csrc ", $csr, ", {}"), in(reg) $bits, options(nostack));
+        }
+    };
+}
+
+fn probe_extension(ext: usize) -> usize {
+    let plat = platform();
+
+    let present = match ext {
+        sbi::EXT_BASE | sbi::EXT_HSM => true,
+        sbi::EXT_TIME | sbi::EXT_IPI => plat.clint.is_some(),
+        sbi::EXT_SRST => plat.reset.is_some() || plat.off.is_some(),
+        sbi::EXT_DBCN | sbi::EXT_LEGACY_PUTCHAR | sbi::EXT_LEGACY_GETCHAR => {
+            plat.uart.is_some()
+        }
+        _ => false,
+    };
+
+    present as usize
+}
+
+fn base(fid: usize, args: &[usize]) -> Result<usize, Error> {
+    match fid {
+        sbi::FN_GET_SPEC_VERSION => Ok(SPEC_VERSION),
+        sbi::FN_GET_IMPL_ID => Ok(IMPL_ID),
+        sbi::FN_GET_IMPL_VERSION => Ok(0),
+        sbi::FN_PROBE_EXTENSION => Ok(probe_extension(args[0])),
+        sbi::FN_GET_MVENDORID => Ok(read_csr!("mvendorid")),
+        sbi::FN_GET_MARCHID => Ok(read_csr!("marchid")),
+        sbi::FN_GET_MIMPID => Ok(read_csr!("mimpid")),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn set_timer(ticks: u64) -> Result<usize, Error> {
+    let clint = platform().clint.ok_or(Error::NotSupported)?;
+
+    clint
+        .mtimer
+        .set_compare(hartid() as u64, ticks)
+        .ok_or(Error::Failed)?;
+
+    clear_csr!("mip", MIP_STIP);
+    set_csr!("mie", MIE_MTIE);
+
+    Ok(0)
+}
+
+fn send_ipi(mask: usize, base: usize) -> Result<usize, Error> {
+    let clint = platform().clint.ok_or(Error::NotSupported)?;
+
+    let targets = (0..MAX_CPUS).filter(|hartid| match base {
+        HART_MASK_ALL => true,
+        _ => hartid.checked_sub(base).is_some_and(|bit| {
+            bit < usize::BITS as usize && mask >> bit & 1 != 0
+        }),
+    });
+
+    for hartid in targets {
+        if !clint.mswi.has_hart(hartid as u64) {
+            continue;
+        }
+
+        IPI_PENDING[hartid].store(true, Ordering::Release);
+        clint.mswi.set_pending(hartid as u64, true);
+    }
+
+    Ok(0)
+}
+
+fn hart_start(
+    hartid: usize,
+    entry: usize,
+    opaque: usize,
+) -> Result<usize, Error> {
+    let clint = platform().clint.ok_or(Error::NotSupported)?;
+    let slot = HARTS.get(hartid).ok_or(Error::InvalidParam)?;
+
+    if !clint.mswi.has_hart(hartid as u64) {
+        return Err(Error::InvalidParam);
+    }
+
+    slot.state
+        .compare_exchange(
+            STATE_STOPPED,
+            STATE_CLAIMED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        )
+        .map_err(|_| Error::AlreadyAvailable)?;
+
+    slot.entry.store(entry as u64, Ordering::Relaxed);
+    slot.opaque.store(opaque as u64, Ordering::Relaxed);
+    slot.state.store(STATE_START_PENDING, Ordering::Release);
+    clint.mswi.set_pending(hartid as u64, true);
+
+    Ok(0)
+}
+
+fn hart_stop() -> Result<usize, Error> {
+    let slot = HARTS.get(hartid()).ok_or(Error::Failed)?;
+
+    clear_csr!("mie", MIE_MTIE);
+    slot.state.store(STATE_STOPPED, Ordering::Release);
+
+    unsafe { mmode_pen() }
+}
+
+fn hart_status(hartid: usize) -> Result<usize, Error> {
+    let slot = HARTS.get(hartid).ok_or(Error::InvalidParam)?;
+
+    let state = match slot.state.load(Ordering::Acquire) {
+        STATE_STARTED => STATE_STARTED,
+        STATE_STOPPED => STATE_STOPPED,
+        _ => STATE_START_PENDING,
+    };
+
+    Ok(state as usize)
+}
+
+fn hsm(fid: usize, args: &[usize]) -> Result<usize, Error> {
+    match fid {
+        sbi::FN_HART_START => hart_start(args[0], args[1], args[2]),
+        sbi::FN_HART_STOP => hart_stop(),
+        sbi::FN_HART_GET_STATUS => hart_status(args[0]),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn system_reset(kind: usize) -> Result<usize, Error> {
+    let plat = platform();
+
+    let syscon = match kind {
+        0 => plat.off,
+        1 | 2 => plat.reset,
+        _ => return Err(Error::InvalidParam),
+    };
+
+    syscon.ok_or(Error::NotSupported)?.write();
+
+    loop {
+        super::wait_for_interrupt();
+    }
+}
+
+fn console(fid: usize, args: &[usize]) -> Result<usize, Error> {
+    let uart = platform().uart.ok_or(Error::NotSupported)?;
+
+    match fid {
+        sbi::FN_CONSOLE_WRITE => {
+            let bytes = unsafe {
+                core::slice::from_raw_parts(args[1] as *const u8, args[0])
+            };
+
+            bytes.iter().for_each(|byte| uart.putc(*byte));
+            Ok(bytes.len())
+        }
+        sbi::FN_CONSOLE_READ => {
+            let buf = unsafe {
+                core::slice::from_raw_parts_mut(args[1] as *mut u8, args[0])
+            };
+
+            let mut count = 0;
+            for byte in buf.iter_mut() {
+                let Some(ch) = uart.getc() else {
+                    break;
+                };
+
+                *byte = ch;
+                count += 1;
+            }
+
+            Ok(count)
+        }
+        sbi::FN_CONSOLE_WRITE_BYTE => {
+            uart.putc(args[0] as u8);
+            Ok(0)
+        }
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// Handle a legacy SBI call, which returns a bare value in `a0`.
+fn legacy(ext: usize, arg: usize) -> isize {
+    let Some(uart) = platform().uart else {
+        return Error::NotSupported.code();
+    };
+
+    match ext {
+        sbi::EXT_LEGACY_PUTCHAR => {
+            uart.putc(arg as u8);
+            0
+        }
+        _ => uart.getc().map_or(-1, |byte| byte as isize),
+    }
+}
+
+/// Handle an environment call issued from S-mode.
+fn ecall(frame: &mut TrapFrame) {
+    let ext = frame.regs[TrapFrame::A7];
+    let fid = frame.regs[TrapFrame::A6];
+    let args = &frame.regs[TrapFrame::A0..TrapFrame::A0 + 6];
+
+    if let sbi::EXT_LEGACY_PUTCHAR | sbi::EXT_LEGACY_GETCHAR = ext {
+        frame.regs[TrapFrame::A0] = legacy(ext, args[0]) as usize;
+        return;
+    }
+
+    let ret = match ext {
+        sbi::EXT_BASE => base(fid, args),
+        sbi::EXT_TIME if fid == sbi::FN_SET_TIMER => set_timer(args[0] as u64),
+        sbi::EXT_IPI if fid == sbi::FN_SEND_IPI => send_ipi(args[0], args[1]),
+        sbi::EXT_HSM => hsm(fid, args),
+        sbi::EXT_SRST if fid == sbi::FN_SYSTEM_RESET => system_reset(args[0]),
+        sbi::EXT_DBCN => console(fid, args),
+        _ => Err(Error::NotSupported),
+    };
+
+    let (error, value) = match ret {
+        Ok(value) => (0, value),
+        Err(err) => (err.code(), 0),
+    };
+
+    frame.regs[TrapFrame::A0] = error as usize;
+    frame.regs[TrapFrame::A1] = value;
+}
+
+/// Handle a trap taken to M-mode.
+///
+/// Called from `mmode_trap_vector` in `asm/mmode.S`. Environment calls are
+/// served as SBI calls, while M-mode timer and software interrupts are passed
+/// on to S-mode. Anything else is not expected to reach M-mode and halts the
+/// hart.
+#[unsafe(no_mangle)]
+extern "C" fn mmode_trap(frame: &mut TrapFrame) {
+    let cause = read_csr!("mcause");
+
+    match cause {
+        _ if cause == MCAUSE_INTERRUPT | IRQ_M_TIMER => {
+            clear_csr!("mie", MIE_MTIE);
+            set_csr!("mip", MIP_STIP);
+        }
+        _ if cause == MCAUSE_INTERRUPT | IRQ_M_SOFT => {
+            let hartid = hartid();
+
+            if let Some(clint) = platform().clint {
+                clint.mswi.set_pending(hartid as u64, false);
+            }
+
+            if IPI_PENDING[hartid].swap(false, Ordering::AcqRel) {
+                set_csr!("mip", MIP_SSIP);
+            }
+        }
+        EXC_ECALL_S => {
+            ecall(frame);
+
+            let epc = read_csr!("mepc");
+            unsafe {
+                asm!("csrw mepc, {}", in(reg) epc + 4, options(nostack));
+            }
+        }
+        _ => loop {
+            super::wait_for_interrupt();
+        },
+    }
+}
+
+/// See: [`STACKS`].
+#[allow(dead_code)] // Only accessed from assembly.
+struct StacksCell(UnsafeCell<Stacks>);
+unsafe impl Sync for StacksCell {}
+
+/// See: [`PLATFORM`].
+struct PlatformCell(UnsafeCell<Platform>);
+unsafe impl Sync for PlatformCell {}
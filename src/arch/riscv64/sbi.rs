@@ -0,0 +1,529 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::console;
+
+/// Identifier of the Base extension.
+pub(super) const EXT_BASE: usize = 0x10;
+
+/// Identifier of the Timer extension.
+pub(super) const EXT_TIME: usize = 0x5449_4D45;
+
+/// Identifier of the IPI extension.
+pub(super) const EXT_IPI: usize = 0x73_5049;
+
+/// Identifier of the Hart State Management extension.
+pub(super) const EXT_HSM: usize = 0x48_534D;
+
+/// Identifier of the System Reset extension.
+pub(super) const EXT_SRST: usize = 0x5352_5354;
+
+/// Identifier of the Debug Console extension.
+pub(super) const EXT_DBCN: usize = 0x4442_434E;
+
+/// Identifier of the legacy `sbi_console_putchar` extension.
+pub(super) const EXT_LEGACY_PUTCHAR: usize = 0x01;
+
+/// Identifier of the legacy `sbi_console_getchar` extension.
+pub(super) const EXT_LEGACY_GETCHAR: usize = 0x02;
+
+/// Functions of the Base extension.
+pub(super) const FN_GET_SPEC_VERSION: usize = 0;
+pub(super) const FN_GET_IMPL_ID: usize = 1;
+pub(super) const FN_GET_IMPL_VERSION: usize = 2;
+pub(super) const FN_PROBE_EXTENSION: usize = 3;
+#[cfg(feature = "m-mode")]
+pub(super) const FN_GET_MVENDORID: usize = 4;
+#[cfg(feature = "m-mode")]
+pub(super) const FN_GET_MARCHID: usize = 5;
+#[cfg(feature = "m-mode")]
+pub(super) const FN_GET_MIMPID: usize = 6;
+
+/// Function of the Timer extension.
+pub(super) const FN_SET_TIMER: usize = 0;
+
+/// Function of the IPI extension.
+pub(super) const FN_SEND_IPI: usize = 0;
+
+/// Functions of the Hart State Management extension.
+pub(super) const FN_HART_START: usize = 0;
+pub(super) const FN_HART_STOP: usize = 1;
+pub(super) const FN_HART_GET_STATUS: usize = 2;
+
+/// Function of the System Reset extension.
+pub(super) const FN_SYSTEM_RESET: usize = 0;
+
+/// Functions of the Debug Console extension.
+pub(super) const FN_CONSOLE_WRITE: usize = 0;
+pub(super) const FN_CONSOLE_READ: usize = 1;
+pub(super) const FN_CONSOLE_WRITE_BYTE: usize = 2;
+
+/// Firmware interface discovered by [`init`].
+///
+/// Written only once during early initialization, before any secondary harts
+/// are started.
+static SBI: SbiCell = SbiCell(UnsafeCell::new(None));
+
+/// Errors reported by SBI functions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    Failed,
+    NotSupported,
+    InvalidParam,
+    Denied,
+    InvalidAddress,
+    AlreadyAvailable,
+    AlreadyStarted,
+    AlreadyStopped,
+    NoShmem,
+    InvalidState,
+    BadRange,
+    Timeout,
+    Io,
+    /// Firmware lacks the SBI extension providing the function.
+    Unavailable,
+    /// Firmware returned a value not defined by the specification.
+    Unknown(isize),
+}
+
+impl Error {
+    /// Decode the `error` and `value` pair returned by an SBI function.
+    fn decode(error: isize, value: usize) -> Result<usize, Error> {
+        use Error::*;
+
+        match error {
+            0 => Ok(value),
+            -1 => Err(Failed),
+            -2 => Err(NotSupported),
+            -3 => Err(InvalidParam),
+            -4 => Err(Denied),
+            -5 => Err(InvalidAddress),
+            -6 => Err(AlreadyAvailable),
+            -7 => Err(AlreadyStarted),
+            -8 => Err(AlreadyStopped),
+            -9 => Err(NoShmem),
+            -10 => Err(InvalidState),
+            -11 => Err(BadRange),
+            -12 => Err(Timeout),
+            -13 => Err(Io),
+            code => Err(Unknown(code)),
+        }
+    }
+}
+
+impl Error {
+    /// Encode an error as the `error` value returned by an SBI function.
+    #[cfg(feature = "m-mode")]
+    pub(super) fn code(self) -> isize {
+        use Error::*;
+
+        match self {
+            Failed => -1,
+            NotSupported | Unavailable => -2,
+            InvalidParam => -3,
+            Denied => -4,
+            InvalidAddress => -5,
+            AlreadyAvailable => -6,
+            AlreadyStarted => -7,
+            AlreadyStopped => -8,
+            NoShmem => -9,
+            InvalidState => -10,
+            BadRange => -11,
+            Timeout => -12,
+            Io => -13,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+
+        match self {
+            Failed => write!(f, "failed"),
+            NotSupported => write!(f, "not supported"),
+            InvalidParam => write!(f, "invalid parameter"),
+            Denied => write!(f, "denied"),
+            InvalidAddress => write!(f, "invalid address"),
+            AlreadyAvailable => write!(f, "already available"),
+            AlreadyStarted => write!(f, "already started"),
+            AlreadyStopped => write!(f, "already stopped"),
+            NoShmem => write!(f, "shared memory not available"),
+            InvalidState => write!(f, "invalid state"),
+            BadRange => write!(f, "bad range"),
+            Timeout => write!(f, "timeout"),
+            Io => write!(f, "input/output error"),
+            Unavailable => write!(f, "unavailable"),
+            Unknown(code) => write!(f, "unknown error {code}"),
+        }
+    }
+}
+
+/// Version of the SBI specification implemented by the firmware.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u32,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// State of a hart, as reported by `HART_GET_STATUS`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HartState {
+    Started,
+    Stopped,
+    StartPending,
+    StopPending,
+    Suspended,
+    SuspendPending,
+    ResumePending,
+}
+
+/// Kind of a system-wide reset requested with [`system_reset`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResetType {
+    Shutdown = 0,
+    ColdReboot = 1,
+    WarmReboot = 2,
+}
+
+/// Reason of a system-wide reset requested with [`system_reset`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResetReason {
+    NoReason = 0,
+    SystemFailure = 1,
+}
+
+/// Extensions found to be implemented by the firmware.
+#[derive(Clone, Copy, Default)]
+struct Extensions {
+    time: bool,
+    ipi: bool,
+    hsm: bool,
+    srst: bool,
+    dbcn: bool,
+    legacy_putchar: bool,
+    legacy_getchar: bool,
+}
+
+/// See: [`SBI`].
+struct Sbi {
+    version: Version,
+    impl_id: usize,
+    impl_version: usize,
+    ext: Extensions,
+}
+
+/// Console provided by the firmware.
+pub struct Console;
+
+impl console::Sink for Console {
+    fn write_str(&self, s: &str) {
+        let _ = console_write(s.as_bytes());
+    }
+
+    fn getc(&self) -> Option<u8> {
+        let mut byte = 0;
+
+        match console_read(core::slice::from_mut(&mut byte)) {
+            Ok(1) => Some(byte),
+            _ => None,
+        }
+    }
+}
+
+/// Call an SBI function with up to three arguments.
+fn ecall(ext: usize, fid: usize, args: [usize; 3]) -> Result<usize, Error> {
+    let error: isize;
+    let value: usize;
+
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => error,
+            inlateout("a1") args[1] => value,
+            in("a2") args[2],
+            in("a6") fid,
+            in("a7") ext,
+            options(nostack),
+        );
+    }
+
+    Error::decode(error, value)
+}
+
+/// Call a legacy SBI extension with a single argument.
+///
+/// These predate the Base extension and return a bare value in `a0`.
+fn legacy_ecall(ext: usize, arg: usize) -> isize {
+    let ret: isize;
+
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") arg => ret,
+            lateout("a1") _,
+            in("a7") ext,
+            options(nostack),
+        );
+    }
+
+    ret
+}
+
+/// Check whether the firmware implements an extension with a given identifier.
+fn probe(ext: usize) -> bool {
+    ecall(EXT_BASE, FN_PROBE_EXTENSION, [ext, 0, 0]).is_ok_and(|val| val != 0)
+}
+
+/// Discover the SBI implementation and extensions it provides.
+///
+/// Firmware implementing SBI 0.1 lacks the Base extension. Legacy console
+/// functions are assumed present in that case, as there is no way of probing
+/// for them. If the firmware provides any console and no other console is
+/// registered, the firmware console is registered.
+pub fn init() {
+    let sbi = match ecall(EXT_BASE, FN_GET_SPEC_VERSION, [0; 3]) {
+        Ok(ver) => Sbi {
+            version: Version {
+                major: ((ver >> 24) & 0x7F) as u8,
+                minor: (ver & 0xFF_FFFF) as u32,
+            },
+            impl_id: ecall(EXT_BASE, FN_GET_IMPL_ID, [0; 3]).unwrap_or(0),
+            impl_version: ecall(EXT_BASE, FN_GET_IMPL_VERSION, [0; 3])
+                .unwrap_or(0),
+            ext: Extensions {
+                time: probe(EXT_TIME),
+                ipi: probe(EXT_IPI),
+                hsm: probe(EXT_HSM),
+                srst: probe(EXT_SRST),
+                dbcn: probe(EXT_DBCN),
+                legacy_putchar: probe(EXT_LEGACY_PUTCHAR),
+                legacy_getchar: probe(EXT_LEGACY_GETCHAR),
+            },
+        },
+        Err(_) => Sbi {
+            version: Version { major: 0, minor: 1 },
+            impl_id: 0,
+            impl_version: 0,
+            ext: Extensions {
+                legacy_putchar: true,
+                legacy_getchar: true,
+                ..Extensions::default()
+            },
+        },
+    };
+
+    let has_console = sbi.ext.dbcn || sbi.ext.legacy_putchar;
+
+    unsafe {
+        *SBI.0.get() = Some(sbi);
+    }
+
+    if has_console && !console::has_sink() {
+        let _ = console::register("sbi", &Console);
+    }
+}
+
+fn get() -> Result<&'static Sbi, Error> {
+    unsafe { (*SBI.0.get()).as_ref().ok_or(Error::Unavailable) }
+}
+
+/// Obtain an SBI implementation, provided that it has a given extension.
+fn with(pred: fn(&Extensions) -> bool) -> Result<&'static Sbi, Error> {
+    let sbi = get()?;
+
+    if !pred(&sbi.ext) {
+        return Err(Error::Unavailable);
+    }
+
+    Ok(sbi)
+}
+
+/// Obtain version of the SBI specification implemented by the firmware.
+pub fn version() -> Result<Version, Error> {
+    Ok(get()?.version)
+}
+
+/// Obtain identifier and version of the SBI implementation.
+///
+/// Both are zero for firmware implementing only SBI 0.1.
+pub fn implementation() -> Result<(usize, usize), Error> {
+    let sbi = get()?;
+
+    Ok((sbi.impl_id, sbi.impl_version))
+}
+
+/// Write bytes to the firmware console.
+///
+/// Returns once all bytes are written. The legacy console is used if the
+/// firmware lacks the Debug Console extension.
+pub fn console_write(bytes: &[u8]) -> Result<(), Error> {
+    let sbi = get()?;
+
+    if sbi.ext.dbcn {
+        let mut rest = bytes;
+
+        while !rest.is_empty() {
+            let addr = rest.as_ptr() as usize;
+            let n = ecall(EXT_DBCN, FN_CONSOLE_WRITE, [rest.len(), addr, 0])?;
+            rest = &rest[n.min(rest.len())..];
+        }
+
+        return Ok(());
+    }
+
+    if sbi.ext.legacy_putchar {
+        for byte in bytes {
+            legacy_ecall(EXT_LEGACY_PUTCHAR, *byte as usize);
+        }
+
+        return Ok(());
+    }
+
+    Err(Error::Unavailable)
+}
+
+/// Write a single byte to the firmware console.
+pub fn console_write_byte(byte: u8) -> Result<(), Error> {
+    let sbi = get()?;
+
+    if sbi.ext.dbcn {
+        return ecall(EXT_DBCN, FN_CONSOLE_WRITE_BYTE, [byte as usize, 0, 0])
+            .map(|_| ());
+    }
+
+    console_write(&[byte])
+}
+
+/// Read bytes available on the firmware console without blocking.
+///
+/// Returns number of bytes read, which is zero if none are pending.
+pub fn console_read(buf: &mut [u8]) -> Result<usize, Error> {
+    let sbi = get()?;
+
+    if sbi.ext.dbcn {
+        let addr = buf.as_mut_ptr() as usize;
+
+        return ecall(EXT_DBCN, FN_CONSOLE_READ, [buf.len(), addr, 0]);
+    }
+
+    if sbi.ext.legacy_getchar {
+        let mut count = 0;
+
+        for byte in buf.iter_mut() {
+            match legacy_ecall(EXT_LEGACY_GETCHAR, 0) {
+                ch @ 0..=0xFF => *byte = ch as u8,
+                _ => break,
+            }
+
+            count += 1;
+        }
+
+        return Ok(count);
+    }
+
+    Err(Error::Unavailable)
+}
+
+/// Program the S-mode timer of the calling hart.
+///
+/// The timer interrupt becomes pending once the `time` CSR reaches `ticks`.
+/// Setting it far in the future effectively cancels it.
+pub fn set_timer(ticks: u64) -> Result<(), Error> {
+    with(|ext| ext.time)?;
+
+    ecall(EXT_TIME, FN_SET_TIMER, [ticks as usize, 0, 0]).map(|_| ())
+}
+
+/// Send a supervisor software interrupt to a set of harts.
+///
+/// # Arguments
+///
+/// - `hart_mask`: Bitmap of target harts, relative to `hart_mask_base`.
+/// - `hart_mask_base`: Hart identifier of the first bit of `hart_mask`.
+pub fn send_ipi(hart_mask: usize, hart_mask_base: usize) -> Result<(), Error> {
+    with(|ext| ext.ipi)?;
+
+    ecall(EXT_IPI, FN_SEND_IPI, [hart_mask, hart_mask_base, 0]).map(|_| ())
+}
+
+/// Start a stopped hart at a given address, in S-mode.
+///
+/// # Arguments
+///
+/// - `hartid`: Identifier of the target hart.
+/// - `entry`: Physical address at which the hart begins execution.
+/// - `opaque`: Value passed to the hart in its `a1` register.
+pub fn hart_start(
+    hartid: u64,
+    entry: usize,
+    opaque: usize,
+) -> Result<(), Error> {
+    with(|ext| ext.hsm)?;
+
+    ecall(EXT_HSM, FN_HART_START, [hartid as usize, entry, opaque]).map(|_| ())
+}
+
+/// Stop the calling hart and return it to the firmware.
+///
+/// On success this function does not return. Subsequent `HART_START` call will
+/// restart the hart at the requested address.
+pub fn hart_stop() -> Result<(), Error> {
+    with(|ext| ext.hsm)?;
+
+    ecall(EXT_HSM, FN_HART_STOP, [0; 3]).map(|_| ())
+}
+
+/// Query state of a hart.
+///
+/// # Arguments
+///
+/// - `hartid`: Identifier of the target hart.
+pub fn hart_status(hartid: u64) -> Result<HartState, Error> {
+    with(|ext| ext.hsm)?;
+
+    match ecall(EXT_HSM, FN_HART_GET_STATUS, [hartid as usize, 0, 0])? {
+        0 => Ok(HartState::Started),
+        1 => Ok(HartState::Stopped),
+        2 => Ok(HartState::StartPending),
+        3 => Ok(HartState::StopPending),
+        4 => Ok(HartState::Suspended),
+        5 => Ok(HartState::SuspendPending),
+        6 => Ok(HartState::ResumePending),
+        state => Err(Error::Unknown(state as isize)),
+    }
+}
+
+/// Check whether harts can be started and stopped through the firmware.
+pub fn has_hsm() -> bool {
+    with(|ext| ext.hsm).is_ok()
+}
+
+/// Reset or shut down the whole system.
+///
+/// On success this function does not return.
+pub fn system_reset(kind: ResetType, reason: ResetReason) -> Result<(), Error> {
+    with(|ext| ext.srst)?;
+
+    ecall(
+        EXT_SRST,
+        FN_SYSTEM_RESET,
+        [kind as usize, reason as usize, 0],
+    )
+    .map(|_| ())
+}
+
+/// See: [`SBI`].
+struct SbiCell(UnsafeCell<Option<Sbi>>);
+unsafe impl Sync for SbiCell {}
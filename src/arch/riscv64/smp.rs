@@ -0,0 +1,162 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use super::sbi::{self, HartState};
+use crate::cpu::MAX_CPUS;
+use crate::fdt::{FdtNode, FdtStreamable};
+use crate::time;
+
+unsafe extern "C" {
+    // See: asm/start.S
+    fn secondary_start();
+}
+
+/// Time given to the firmware to stop a hart parked with SBI HSM.
+const PARK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Value marking an unused [`SPIN_TABLE`] slot.
+const SLOT_UNUSED: u64 = u64::MAX;
+
+/// Value marking the last [`SPIN_TABLE`] slot.
+const SLOT_END: u64 = u64::MAX - 1;
+
+/// Entry points of harts parked by lunar itself.
+///
+/// Harts entering lunar at reset, other than the primary one, wait in the
+/// `smp_pen` until a slot with their hart identifier appears here. They then
+/// proceed to spin on the entry point from that slot. Slots are indexed by
+/// logical hart index, so used and unused ones may interleave. The last slot
+/// is never used and serves as a sentinel. See: `asm/smp.S`.
+#[unsafe(export_name = "smp_spin_table")]
+static SPIN_TABLE: [SpinSlot; MAX_CPUS + 1] = {
+    let mut table = [const { SpinSlot::new(SLOT_UNUSED) }; MAX_CPUS + 1];
+    table[MAX_CPUS] = SpinSlot::new(SLOT_END);
+    table
+};
+
+/// See: [`SPIN_TABLE`].
+#[repr(C)]
+struct SpinSlot {
+    hwid: AtomicU64,
+    entry: AtomicU64,
+}
+
+impl SpinSlot {
+    const fn new(hwid: u64) -> Self {
+        SpinSlot {
+            hwid: AtomicU64::new(hwid),
+            entry: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A method of bringing a secondary hart online.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnableMethod {
+    /// Hart is started through SBI HSM `HART_START` call.
+    Hsm,
+    /// Hart entered lunar at reset and waits in `smp_pen`.
+    Pen,
+}
+
+impl EnableMethod {
+    /// Read the enable method of a given `/cpus` child node.
+    ///
+    /// RISC-V harts carry no enable method in the devicetree. Firmware with
+    /// the HSM extension manages all harts but the primary one itself, while
+    /// otherwise all harts enter lunar at reset.
+    pub fn from_node(node: &FdtNode) -> Option<Self> {
+        if !node
+            .prop_strs("compatible")?
+            .any(|compat| compat == "riscv")
+        {
+            return None;
+        }
+
+        if sbi::has_hsm() {
+            Some(EnableMethod::Hsm)
+        } else {
+            Some(EnableMethod::Pen)
+        }
+    }
+
+    /// Prepare a hart for being started with [`EnableMethod::start`].
+    ///
+    /// The hart is given a slot in [`SPIN_TABLE`]. Harts that never entered
+    /// lunar simply never look at it.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hart identifier.
+    /// - `cpu`: Logical index of the hart.
+    pub fn prepare(self, hwid: u64, cpu: usize) {
+        if let EnableMethod::Pen = self {
+            let slot = &SPIN_TABLE[cpu];
+
+            slot.entry.store(0, Ordering::Relaxed);
+            slot.hwid.store(hwid, Ordering::Release);
+            super::send_event();
+        }
+    }
+
+    /// Start a hart, directing it to the secondary entry point.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hart identifier.
+    /// - `cpu`: Logical index of the hart.
+    pub fn start(self, hwid: u64, cpu: usize) -> Result<(), sbi::Error> {
+        let entry = secondary_start as *const () as usize;
+
+        match self {
+            EnableMethod::Hsm => sbi::hart_start(hwid, entry, cpu),
+            EnableMethod::Pen => {
+                SPIN_TABLE[cpu].entry.store(entry as u64, Ordering::Release);
+                super::send_event();
+                Ok(())
+            }
+        }
+    }
+
+    /// Finalize a start of a hart, once it reported itself online.
+    pub fn started(self) {}
+
+    /// Leave the calling hart in a state expected by the payload.
+    ///
+    /// Harts started with HSM are stopped, so that the payload can start them
+    /// again. There is no protocol for handing harts held in the pen over to
+    /// the payload, so these are halted for good.
+    pub fn park(self) -> ! {
+        super::disable_interrupts();
+
+        if let EnableMethod::Hsm = self {
+            let _ = sbi::hart_stop();
+        }
+
+        loop {
+            super::wait_for_interrupt();
+        }
+    }
+
+    /// Wait for a hart that left lunar with [`EnableMethod::park`] to settle.
+    ///
+    /// For HSM this waits for the firmware to report the hart as stopped, as
+    /// a `HART_START` issued by the payload in the meantime would be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// - `hwid`: Hart identifier.
+    pub fn wait_parked(self, hwid: u64) {
+        if let EnableMethod::Hsm = self {
+            time::wait_until(PARK_TIMEOUT, || {
+                !matches!(
+                    sbi::hart_status(hwid),
+                    Ok(HartState::Started | HartState::StopPending)
+                )
+            });
+        }
+    }
+}
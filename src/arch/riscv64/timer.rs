@@ -0,0 +1,54 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+
+use super::sbi;
+use super::trap::{self, IRQ_S_TIMER};
+use crate::fdt::{self, FdtStreamable};
+use crate::irq;
+
+/// Read the `time` CSR, which mirrors the platform real-time counter.
+pub fn counter() -> u64 {
+    let cnt: u64;
+
+    unsafe {
+        asm!("rdtime {}", out(reg) cnt, options(nomem, nostack));
+    }
+
+    cnt
+}
+
+/// Obtain frequency of the real-time counter in Hz.
+///
+/// This is the `timebase-frequency` property of the `/cpus` node, which all
+/// harts share on supported platforms.
+pub fn frequency() -> u64 {
+    fdt::get()
+        .node_by_path("/cpus")
+        .and_then(|cpus| cpus.prop_u32("timebase-frequency"))
+        .unwrap_or(0) as u64
+}
+
+/// Make the timer fire once the counter reaches a given value.
+///
+/// S-mode has no timer of its own, so this goes through the SBI.
+pub fn set_alarm(ticks: u64) {
+    let _ = sbi::set_timer(ticks);
+}
+
+/// Stop the timer from firing.
+pub fn cancel_alarm() {
+    let _ = sbi::set_timer(u64::MAX);
+}
+
+/// Register a handler of the timer interrupt and unmask it.
+///
+/// The S-mode timer interrupt is local to each hart and bypasses the platform
+/// interrupt controller, so it is only unmasked on the calling hart.
+pub fn request_irq(handler: irq::Handler) -> Result<u32, irq::Error> {
+    irq::register(IRQ_S_TIMER, handler)?;
+    trap::enable_local(IRQ_S_TIMER);
+
+    Ok(IRQ_S_TIMER)
+}
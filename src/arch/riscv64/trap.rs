@@ -0,0 +1,179 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+use core::fmt;
+
+use crate::backtrace::Backtrace;
+use crate::console::println;
+use crate::irq;
+use crate::symbols::Symbolized;
+
+/// Hart-local S-mode interrupts, numbered like their `scause` codes.
+///
+/// These are also the first interrupt numbers of the [`irq`] subsystem, with
+/// the platform interrupt controller numbering its lines past them.
+pub const IRQ_S_SOFT: u32 = 1;
+pub const IRQ_S_TIMER: u32 = 5;
+pub const IRQ_S_EXT: u32 = 9;
+
+/// Number of interrupt numbers reserved for hart-local interrupts.
+pub const LOCAL_IRQS: u32 = 16;
+
+/// `scause` bit set for interrupts.
+const SCAUSE_INTERRUPT: u64 = 1 << 63;
+
+/// `sip` bit of a pending S-mode software interrupt.
+const SIP_SSIP: usize = 1 << IRQ_S_SOFT;
+
+/// ABI names of general purpose registers.
+const REG_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1",
+    "a2", "a3", "a4", "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7",
+    "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
+/// Registers saved upon taking a trap.
+///
+/// Layout of this structure is shared with `asm/trap.S`.
+#[repr(C)]
+pub struct TrapFrame {
+    /// General purpose registers `x0` to `x31`, `x2` being the stack pointer
+    /// from before the trap. The slot of `x0` always reads as zero.
+    pub x: [u64; 32],
+    /// Exception program counter, the address execution resumes at.
+    pub sepc: u64,
+    /// Saved status register.
+    pub sstatus: u64,
+    /// Trap cause register.
+    pub scause: u64,
+    /// Trap value register, such as the faulting address.
+    pub stval: u64,
+}
+
+impl TrapFrame {
+    /// Check whether the trap was caused by an interrupt.
+    pub fn is_interrupt(&self) -> bool {
+        self.scause & SCAUSE_INTERRUPT != 0
+    }
+
+    /// Obtain the interrupt or exception code of the cause register.
+    pub fn code(&self) -> u64 {
+        self.scause & !SCAUSE_INTERRUPT
+    }
+}
+
+impl fmt::Display for TrapFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.is_interrupt() {
+            irq_name(self.code())
+        } else {
+            exception_name(self.code())
+        };
+
+        writeln!(f, "SCAUSE:  {:#018x} ({})", self.scause, name)?;
+        writeln!(f, "STVAL:   {:#018x}", self.stval)?;
+        writeln!(f, "SEPC:    {}", Symbolized(self.sepc as usize))?;
+        writeln!(f, "SSTATUS: {:#018x}", self.sstatus)?;
+
+        for (idx, pair) in self.x.chunks(2).enumerate() {
+            writeln!(
+                f,
+                "{:<4}: {:#018x}  {:<4}: {:#018x}",
+                REG_NAMES[idx * 2],
+                pair[0],
+                REG_NAMES[idx * 2 + 1],
+                pair[1]
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Obtain a human-readable description of an interrupt code.
+fn irq_name(code: u64) -> &'static str {
+    match code {
+        1 => "supervisor software interrupt",
+        5 => "supervisor timer interrupt",
+        9 => "supervisor external interrupt",
+        13 => "counter overflow interrupt",
+        _ => "reserved interrupt",
+    }
+}
+
+/// Obtain a human-readable description of an exception code.
+fn exception_name(code: u64) -> &'static str {
+    match code {
+        0 => "instruction address misaligned",
+        1 => "instruction access fault",
+        2 => "illegal instruction",
+        3 => "breakpoint",
+        4 => "load address misaligned",
+        5 => "load access fault",
+        6 => "store/AMO address misaligned",
+        7 => "store/AMO access fault",
+        8 => "environment call from U-mode",
+        9 => "environment call from S-mode",
+        12 => "instruction page fault",
+        13 => "load page fault",
+        15 => "store/AMO page fault",
+        18 => "software check",
+        19 => "hardware error",
+        _ => "reserved exception",
+    }
+}
+
+/// Unmask a hart-local interrupt on the calling hart.
+pub fn enable_local(irq: u32) {
+    if irq < LOCAL_IRQS {
+        unsafe {
+            asm!("csrs sie, {}", in(reg) 1usize << irq, options(nomem, nostack));
+        }
+    }
+}
+
+/// Mask a hart-local interrupt on the calling hart.
+pub fn disable_local(irq: u32) {
+    if irq < LOCAL_IRQS {
+        unsafe {
+            asm!("csrc sie, {}", in(reg) 1usize << irq, options(nomem, nostack));
+        }
+    }
+}
+
+/// Trap handler called from the trap vector.
+///
+/// External interrupts are passed to the platform interrupt controller, while
+/// the remaining hart-local ones go straight to their handlers. Local
+/// interrupts without a handler are masked.
+///
+/// # Arguments
+///
+/// - `frame`: Registers of the interrupted context.
+#[unsafe(no_mangle)]
+extern "C" fn trap_dispatch(frame: &mut TrapFrame) {
+    if !frame.is_interrupt() {
+        println!("Unhandled exception");
+        println!("{frame}");
+        Backtrace::from_frame(frame.x[8] as usize).print();
+        panic!("Unhandled exception");
+    }
+
+    let irq = frame.code() as u32;
+
+    if irq == IRQ_S_EXT {
+        irq::dispatch();
+        return;
+    }
+
+    if irq == IRQ_S_SOFT {
+        unsafe {
+            asm!("csrc sip, {}", in(reg) SIP_SSIP, options(nomem, nostack));
+        }
+    }
+
+    if !irq::handle(irq) {
+        disable_local(irq);
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::arch;
+use crate::console::eprintln;
+use crate::symbols::Symbolized;
+
+/// Maximum number of frames walked.
+const MAX_FRAMES: usize = 32;
+
+/// Maximum distance between the first and any other frame walked.
+///
+/// Stacks of lunar are much smaller than this, so a frame pointer further away
+/// is considered corrupted.
+const MAX_STACK_DEPTH: usize = 0x10000;
+
+/// Required alignment of frame pointers.
+const FRAME_ALIGN: usize = 16;
+
+/// An iterator over return addresses of active frames, innermost first.
+///
+/// Frames are found by following the chain of frame pointers, which lunar is
+/// built to maintain. The walk stops at the first frame pointer which is null
+/// or does not look sane.
+#[derive(Clone)]
+pub struct Backtrace {
+    fp: usize,
+    /// Frame pointer of the first frame, bounding the walk.
+    start: usize,
+    depth: usize,
+}
+
+impl Backtrace {
+    /// Start a backtrace at the frame of the calling function.
+    #[inline(always)]
+    pub fn capture() -> Self {
+        Self::from_frame(arch::frame_pointer())
+    }
+
+    /// Start a backtrace at a given frame.
+    ///
+    /// # Arguments
+    ///
+    /// - `fp`: Frame pointer of the innermost frame.
+    pub fn from_frame(fp: usize) -> Self {
+        Backtrace {
+            fp,
+            start: fp,
+            depth: 0,
+        }
+    }
+
+    fn is_sane(&self, fp: usize) -> bool {
+        fp != 0
+            && fp.is_multiple_of(FRAME_ALIGN)
+            && fp >= self.start
+            && fp - self.start < MAX_STACK_DEPTH
+    }
+
+    /// Print the backtrace over the console.
+    ///
+    /// Return addresses are shown along with the functions they belong to.
+    pub fn print(self) {
+        eprintln!("backtrace:");
+
+        for (idx, addr) in self.enumerate() {
+            eprintln!("  #{idx:<2} {}", Symbolized(addr));
+        }
+    }
+}
+
+impl Iterator for Backtrace {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.depth >= MAX_FRAMES || !self.is_sane(self.fp) {
+            return None;
+        }
+
+        let (next, ra) = unsafe {
+            let fp = self.fp as *const usize;
+
+            (
+                *fp.byte_offset(arch::FRAME_FP_OFFSET),
+                *fp.byte_offset(arch::FRAME_RA_OFFSET),
+            )
+        };
+
+        // Frames of callers lie above those of their callees.
+        self.fp = if next > self.fp { next } else { 0 };
+        self.depth += 1;
+
+        (ra != 0).then_some(ra)
+    }
+}
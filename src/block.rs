@@ -0,0 +1,339 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+pub mod part;
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+/// Maximum number of block devices registered at the same time, partitions
+/// included.
+pub const MAX_DEVICES: usize = 32;
+
+/// Largest block size supported by [`Reader`].
+pub const MAX_BLOCK_SIZE: usize = 4096;
+
+/// Registered block devices.
+///
+/// Only written by drivers during probing, before secondary cores are started.
+static DEVICES: DevicesCell = DevicesCell(UnsafeCell::new([None; MAX_DEVICES]));
+
+/// Errors reported by block devices.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The range accessed extends past the end of the device.
+    OutOfRange,
+    /// The buffer length is not a multiple of the block size.
+    Unaligned,
+    /// The device cannot be written to.
+    ReadOnly,
+    /// The device does not support the operation.
+    Unsupported,
+    /// The device failed the operation.
+    Io,
+    /// All block device slots are taken.
+    Full,
+    /// A block device with the same name is already registered.
+    Exists,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange => write!(f, "access out of range"),
+            Error::Unaligned => write!(f, "unaligned access"),
+            Error::ReadOnly => write!(f, "read-only device"),
+            Error::Unsupported => write!(f, "operation not supported"),
+            Error::Io => write!(f, "I/O error"),
+            Error::Full => write!(f, "too many block devices"),
+            Error::Exists => write!(f, "name already in use"),
+        }
+    }
+}
+
+/// A device storing data in blocks of a fixed size.
+///
+/// Blocks are addressed by their index, starting at zero. Buffers passed to
+/// reads and writes span a whole number of blocks.
+pub trait BlockDevice: Sync {
+    /// Obtain size of a block in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Obtain number of blocks of the device.
+    fn num_blocks(&self) -> u64;
+
+    /// Obtain capacity of the device in bytes.
+    fn capacity(&self) -> u64 {
+        self.num_blocks() * self.block_size() as u64
+    }
+
+    /// Check whether the device rejects writes.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Read consecutive blocks.
+    ///
+    /// # Arguments
+    ///
+    /// - `lba`: Index of the first block.
+    /// - `buf`: Memory to read into, its length determining number of blocks.
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), Error>;
+
+    /// Write consecutive blocks.
+    ///
+    /// # Arguments
+    ///
+    /// - `lba`: Index of the first block.
+    /// - `buf`: Data to write, its length determining number of blocks.
+    fn write_blocks(&self, lba: u64, buf: &[u8]) -> Result<(), Error>;
+
+    /// Make sure written blocks reach persistent storage.
+    fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Check an access against the geometry of a device.
+///
+/// Returns the number of blocks accessed.
+///
+/// # Arguments
+///
+/// - `dev`: Device being accessed.
+/// - `lba`: Index of the first block accessed.
+/// - `len`: Length of the access in bytes.
+pub fn check_access(
+    dev: &(impl BlockDevice + ?Sized),
+    lba: u64,
+    len: usize,
+) -> Result<u64, Error> {
+    if !len.is_multiple_of(dev.block_size()) {
+        return Err(Error::Unaligned);
+    }
+
+    let count = (len / dev.block_size()) as u64;
+
+    match lba.checked_add(count) {
+        Some(end) if end <= dev.num_blocks() => Ok(count),
+        _ => Err(Error::OutOfRange),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Device {
+    name: &'static str,
+    dev: &'static dyn BlockDevice,
+}
+
+/// Register a block device under a given name, such as `vda`.
+pub fn register(
+    name: &'static str,
+    dev: &'static dyn BlockDevice,
+) -> Result<(), Error> {
+    let devices = unsafe { &mut *DEVICES.0.get() };
+
+    if find(name).is_some() {
+        return Err(Error::Exists);
+    }
+
+    let slot = devices
+        .iter_mut()
+        .find(|slot| slot.is_none())
+        .ok_or(Error::Full)?;
+
+    *slot = Some(Device { name, dev });
+
+    Ok(())
+}
+
+/// Find a registered block device by name.
+pub fn find(name: &str) -> Option<&'static dyn BlockDevice> {
+    devices()
+        .find(|(dev_name, _)| *dev_name == name)
+        .map(|(_, dev)| dev)
+}
+
+/// Obtain an iterator over registered block devices and their names.
+pub fn devices()
+-> impl Iterator<Item = (&'static str, &'static dyn BlockDevice)> {
+    let devices = unsafe { &*DEVICES.0.get() };
+
+    devices.iter().flatten().map(|dev| (dev.name, dev.dev))
+}
+
+/// A reader of arbitrary byte ranges of a block device.
+///
+/// The last block read partially is kept, so that small consecutive reads,
+/// such as those of on-disk structures, do not hit the device every time.
+/// Whole blocks are read directly into the destination.
+pub struct Reader<'a> {
+    dev: &'a dyn BlockDevice,
+    buf: [u8; MAX_BLOCK_SIZE],
+    /// Index of the block held in the buffer.
+    cached: Option<u64>,
+}
+
+impl<'a> Reader<'a> {
+    /// Construct a reader of a device.
+    ///
+    /// Fails with [`Error::Unsupported`] if blocks of the device are larger
+    /// than [`MAX_BLOCK_SIZE`].
+    pub fn new(dev: &'a dyn BlockDevice) -> Result<Self, Error> {
+        if dev.block_size() > MAX_BLOCK_SIZE {
+            return Err(Error::Unsupported);
+        }
+
+        Ok(Reader {
+            dev,
+            buf: [0; MAX_BLOCK_SIZE],
+            cached: None,
+        })
+    }
+
+    /// Obtain the device read from.
+    pub fn device(&self) -> &'a dyn BlockDevice {
+        self.dev
+    }
+
+    /// Obtain contents of a block, reading it in unless it is cached.
+    fn block(&mut self, lba: u64) -> Result<&[u8], Error> {
+        let size = self.dev.block_size();
+
+        if self.cached != Some(lba) {
+            self.cached = None;
+            self.dev.read_blocks(lba, &mut self.buf[..size])?;
+            self.cached = Some(lba);
+        }
+
+        Ok(&self.buf[..size])
+    }
+
+    /// Read bytes starting at a given offset from the start of the device.
+    ///
+    /// # Arguments
+    ///
+    /// - `offset`: Offset of the first byte to read.
+    /// - `dst`: Memory to read into, its length determining number of bytes.
+    pub fn read_at(
+        &mut self,
+        offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Error> {
+        let size = self.dev.block_size();
+        let end = offset
+            .checked_add(dst.len() as u64)
+            .ok_or(Error::OutOfRange)?;
+
+        if end > self.dev.capacity() {
+            return Err(Error::OutOfRange);
+        }
+
+        let mut lba = offset / size as u64;
+        let mut skip = (offset % size as u64) as usize;
+        let mut dst = dst;
+
+        while !dst.is_empty() {
+            let whole = (dst.len() / size) * size;
+
+            if skip == 0 && whole != 0 {
+                let (head, tail) = dst.split_at_mut(whole);
+
+                self.dev.read_blocks(lba, head)?;
+                lba += (whole / size) as u64;
+                dst = tail;
+                continue;
+            }
+
+            let len = dst.len().min(size - skip);
+            let (head, tail) = dst.split_at_mut(len);
+
+            head.copy_from_slice(&self.block(lba)?[skip..skip + len]);
+            lba += 1;
+            skip = 0;
+            dst = tail;
+        }
+
+        Ok(())
+    }
+}
+
+/// See: [`DEVICES`].
+struct DevicesCell(UnsafeCell<[Option<Device>; MAX_DEVICES]>);
+unsafe impl Sync for DevicesCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Size of blocks of [`Pattern`].
+    const BLOCK: usize = 512;
+
+    /// A read-only device of 8 blocks, each byte holding the low bits of its
+    /// offset.
+    struct Pattern;
+
+    impl BlockDevice for Pattern {
+        fn block_size(&self) -> usize {
+            BLOCK
+        }
+
+        fn num_blocks(&self) -> u64 {
+            8
+        }
+
+        fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+            check_access(self, lba, buf.len())?;
+
+            for (idx, byte) in buf.iter_mut().enumerate() {
+                *byte = (lba as usize * BLOCK + idx) as u8;
+            }
+
+            Ok(())
+        }
+
+        fn write_blocks(&self, _lba: u64, _buf: &[u8]) -> Result<(), Error> {
+            Err(Error::ReadOnly)
+        }
+    }
+
+    #[test_case]
+    fn check_access_bounds() {
+        assert_eq!(check_access(&Pattern, 0, 8 * BLOCK), Ok(8));
+        assert_eq!(check_access(&Pattern, 7, BLOCK), Ok(1));
+        assert_eq!(
+            check_access(&Pattern, 7, 2 * BLOCK),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(check_access(&Pattern, 0, 100), Err(Error::Unaligned));
+        assert_eq!(check_access(&Pattern, u64::MAX, 0), Ok(0));
+    }
+
+    #[test_case]
+    fn reader_reads_across_blocks() {
+        let mut reader = Reader::new(&Pattern).unwrap();
+        let mut buf = [0u8; 3 * BLOCK];
+
+        for offset in [0, 1, 511, 512, 700] {
+            let len = buf.len() - offset as usize % 7;
+
+            reader.read_at(offset, &mut buf[..len]).unwrap();
+
+            for (idx, byte) in buf[..len].iter().enumerate() {
+                assert_eq!(*byte, (offset as usize + idx) as u8);
+            }
+        }
+    }
+
+    #[test_case]
+    fn reader_stops_at_capacity() {
+        let mut reader = Reader::new(&Pattern).unwrap();
+        let mut buf = [0u8; 16];
+
+        assert!(reader.read_at(8 * BLOCK as u64 - 16, &mut buf).is_ok());
+        assert_eq!(
+            reader.read_at(8 * BLOCK as u64 - 15, &mut buf),
+            Err(Error::OutOfRange)
+        );
+    }
+}
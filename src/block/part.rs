@@ -0,0 +1,1038 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::block::{self, BlockDevice, MAX_BLOCK_SIZE, Reader};
+use crate::crc32::Crc32;
+use crate::log::{debug, info, warn};
+
+/// Maximum number of partitions, over all disks.
+const MAX_PARTITIONS: usize = 32;
+
+/// Maximum length of a partition name, such as `vda1`.
+const MAX_NAME: usize = 16;
+
+/// Maximum length of a GPT partition label, in UTF-8.
+const MAX_LABEL: usize = 108;
+
+/// Offsets of MBR fields.
+const MBR_SIGNATURE: usize = 440;
+const MBR_ENTRIES: usize = 446;
+const MBR_MAGIC: usize = 510;
+
+/// Size of the MBR and of its partition entries.
+const MBR_SIZE: usize = 512;
+const MBR_ENTRY_SIZE: usize = 16;
+
+/// Boot signature closing the MBR.
+const MBR_MAGIC_VALUE: [u8; 2] = [0x55, 0xAA];
+
+/// MBR partition types of special meaning.
+const MBR_TYPE_EMPTY: u8 = 0x00;
+const MBR_TYPE_EXTENDED: [u8; 3] = [0x05, 0x0F, 0x85];
+const MBR_TYPE_PROTECTIVE: u8 = 0xEE;
+
+/// Signature opening a GPT header.
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+
+/// Offsets of GPT header fields.
+const GPT_HEADER_SIZE: usize = 12;
+const GPT_HEADER_CRC: usize = 16;
+const GPT_MY_LBA: usize = 24;
+const GPT_FIRST_USABLE_LBA: usize = 40;
+const GPT_LAST_USABLE_LBA: usize = 48;
+const GPT_ENTRIES_LBA: usize = 72;
+const GPT_NUM_ENTRIES: usize = 80;
+const GPT_ENTRY_SIZE: usize = 84;
+const GPT_ENTRIES_CRC: usize = 88;
+
+/// Size of the GPT header as of revision 1.0.
+const GPT_MIN_HEADER_SIZE: usize = 92;
+
+/// Size of a GPT partition entry as of revision 1.0.
+const GPT_MIN_ENTRY_SIZE: usize = 128;
+
+/// Number of GPT partition entries considered at most.
+const GPT_MAX_ENTRIES: usize = 1024;
+
+/// Offsets of GPT partition entry fields.
+const GPT_ENTRY_GUID: usize = 16;
+const GPT_ENTRY_FIRST_LBA: usize = 32;
+const GPT_ENTRY_LAST_LBA: usize = 40;
+const GPT_ENTRY_NAME: usize = 56;
+
+/// Size of the label of a GPT partition entry, in UTF-16 code units.
+const GPT_NAME_UNITS: usize = 36;
+
+/// Partitions of all disks scanned, in order of discovery.
+///
+/// Only written by [`scan`], before secondary cores are started.
+static PARTITIONS: PartitionsCell =
+    PartitionsCell(UnsafeCell::new([const { None }; MAX_PARTITIONS]));
+
+/// Errors reported by partition table parsing.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The disk could not be read.
+    Block(block::Error),
+    /// The disk has no partition table.
+    NoTable,
+    /// Both GPT headers, or their partition entries, are corrupted.
+    Corrupt,
+    /// The partition table has more partitions than can be kept.
+    TooMany,
+}
+
+impl From<block::Error> for Error {
+    fn from(err: block::Error) -> Self {
+        Error::Block(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Block(err) => write!(f, "{err}"),
+            Error::NoTable => write!(f, "no partition table"),
+            Error::Corrupt => write!(f, "corrupted partition table"),
+            Error::TooMany => write!(f, "too many partitions"),
+        }
+    }
+}
+
+/// A GUID, as stored on disk.
+///
+/// The first three fields are little endian, the rest is a byte string.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    /// Length of the textual form, such as
+    /// `c12a7328-f81f-11d2-ba4b-00a0c93ec93b`.
+    const TEXT_LEN: usize = 36;
+
+    /// Positions of hyphens in the textual form.
+    const HYPHENS: [usize; 4] = [8, 13, 18, 23];
+
+    /// Check whether this is the all-zero GUID, marking unused entries.
+    pub fn is_nil(&self) -> bool {
+        self.0 == [0; 16]
+    }
+
+    /// Parse a GUID from its textual form, in either case.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.as_bytes();
+
+        if s.len() != Self::TEXT_LEN {
+            return None;
+        }
+
+        let mut text = [0u8; 16];
+        let mut digits = s
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !Self::HYPHENS.contains(idx))
+            .map(|(_, ch)| (*ch as char).to_digit(16));
+
+        for &hyphen in &Self::HYPHENS {
+            if s[hyphen] != b'-' {
+                return None;
+            }
+        }
+
+        for byte in text.iter_mut() {
+            *byte = (digits.next()?? << 4 | digits.next()??) as u8;
+        }
+
+        // Fields stored in little endian are written most significant first.
+        text[0..4].reverse();
+        text[4..6].reverse();
+        text[6..8].reverse();
+
+        Some(Guid(text))
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.0;
+
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-",
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            u16::from_le_bytes([b[4], b[5]]),
+            u16::from_le_bytes([b[6], b[7]]),
+            b[8],
+            b[9]
+        )?;
+
+        b[10..].iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// Partitioning scheme specific details of a partition.
+#[derive(Clone, Copy, Debug)]
+pub enum Scheme {
+    Mbr {
+        /// Signature of the disk.
+        signature: u32,
+        /// Partition type.
+        kind: u8,
+    },
+    Gpt {
+        /// Partition type.
+        type_guid: Guid,
+        /// Unique partition GUID.
+        guid: Guid,
+    },
+}
+
+/// Identifier of a partition as used by Linux, `root=PARTUUID=<this>`.
+///
+/// It is the unique partition GUID on GPT disks, and the disk signature
+/// followed by the partition index on MBR ones, as in `12345678-01`.
+#[derive(Clone, Copy)]
+pub struct PartUuid(Scheme, usize);
+
+impl PartUuid {
+    /// Check whether the identifier matches a given textual form, in either
+    /// case.
+    pub fn matches(&self, s: &str) -> bool {
+        match self.0 {
+            Scheme::Gpt { guid, .. } => Guid::parse(s) == Some(guid),
+            Scheme::Mbr { signature, .. } => {
+                s.split_once('-').is_some_and(|(sig, idx)| {
+                    u32::from_str_radix(sig, 16) == Ok(signature)
+                        && u8::from_str_radix(idx, 16).ok()
+                            == Some(self.1 as u8)
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for PartUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Scheme::Gpt { guid, .. } => write!(f, "{guid}"),
+            Scheme::Mbr { signature, .. } => {
+                write!(f, "{signature:08x}-{:02x}", self.1)
+            }
+        }
+    }
+}
+
+/// A partition of a disk, usable as a block device of its own.
+pub struct Partition {
+    disk: &'static dyn BlockDevice,
+    disk_name: &'static str,
+    /// Index of the partition, starting at one.
+    index: usize,
+    /// First block of the partition on the disk.
+    start: u64,
+    /// Number of blocks of the partition.
+    blocks: u64,
+    scheme: Scheme,
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    label: [u8; MAX_LABEL],
+    label_len: usize,
+}
+
+impl Partition {
+    /// Obtain name of the partition, such as `vda1`.
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+
+    /// Obtain name of the disk holding the partition.
+    pub fn disk_name(&self) -> &'static str {
+        self.disk_name
+    }
+
+    /// Obtain index of the partition on its disk, starting at one.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Obtain the first block of the partition on its disk.
+    pub fn start_lba(&self) -> u64 {
+        self.start
+    }
+
+    /// Obtain partitioning scheme specific details of the partition.
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Obtain the unique partition GUID, which only GPT partitions have.
+    pub fn guid(&self) -> Option<Guid> {
+        match self.scheme {
+            Scheme::Gpt { guid, .. } => Some(guid),
+            Scheme::Mbr { .. } => None,
+        }
+    }
+
+    /// Obtain the identifier of the partition used by Linux.
+    pub fn partuuid(&self) -> PartUuid {
+        PartUuid(self.scheme, self.index)
+    }
+
+    /// Obtain label of the partition, empty unless on a GPT disk.
+    pub fn label(&self) -> &str {
+        core::str::from_utf8(&self.label[..self.label_len]).unwrap_or("")
+    }
+}
+
+impl BlockDevice for Partition {
+    fn block_size(&self) -> usize {
+        self.disk.block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.disk.is_read_only()
+    }
+
+    fn read_blocks(
+        &self,
+        lba: u64,
+        buf: &mut [u8],
+    ) -> Result<(), block::Error> {
+        block::check_access(self, lba, buf.len())?;
+        self.disk.read_blocks(self.start + lba, buf)
+    }
+
+    fn write_blocks(&self, lba: u64, buf: &[u8]) -> Result<(), block::Error> {
+        block::check_access(self, lba, buf.len())?;
+        self.disk.write_blocks(self.start + lba, buf)
+    }
+
+    fn flush(&self) -> Result<(), block::Error> {
+        self.disk.flush()
+    }
+}
+
+fn read_u32(bytes: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(bytes[off..off + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap())
+}
+
+/// A partition found in a partition table, before it is kept.
+struct Entry {
+    index: usize,
+    start: u64,
+    blocks: u64,
+    scheme: Scheme,
+    /// Label in UTF-16, GPT only.
+    label: [u16; GPT_NAME_UNITS],
+}
+
+/// Location and layout of GPT partition entries, from a valid header.
+struct GptTable {
+    entries_lba: u64,
+    num_entries: usize,
+    entry_size: usize,
+    first_usable: u64,
+    last_usable: u64,
+}
+
+/// Read and validate a GPT header along with its partition entries.
+///
+/// # Arguments
+///
+/// - `reader`: Reader of the disk.
+/// - `lba`: Block the header is expected at.
+fn gpt_table(reader: &mut Reader, lba: u64) -> Result<GptTable, Error> {
+    let bs = reader.device().block_size();
+    let mut header = [0u8; MAX_BLOCK_SIZE];
+    let header = &mut header[..bs];
+
+    reader.read_at(lba * bs as u64, header)?;
+
+    let size = read_u32(header, GPT_HEADER_SIZE) as usize;
+
+    if &header[..8] != GPT_SIGNATURE
+        || !(GPT_MIN_HEADER_SIZE..=bs).contains(&size)
+        || read_u64(header, GPT_MY_LBA) != lba
+    {
+        return Err(Error::Corrupt);
+    }
+
+    let crc = read_u32(header, GPT_HEADER_CRC);
+    header[GPT_HEADER_CRC..GPT_HEADER_CRC + 4].fill(0);
+
+    let mut check = Crc32::new();
+    check.update(&header[..size]);
+
+    if check.finish() != crc {
+        return Err(Error::Corrupt);
+    }
+
+    let table = GptTable {
+        entries_lba: read_u64(header, GPT_ENTRIES_LBA),
+        num_entries: read_u32(header, GPT_NUM_ENTRIES) as usize,
+        entry_size: read_u32(header, GPT_ENTRY_SIZE) as usize,
+        first_usable: read_u64(header, GPT_FIRST_USABLE_LBA),
+        last_usable: read_u64(header, GPT_LAST_USABLE_LBA),
+    };
+
+    let num_blocks = reader.device().num_blocks();
+    let len = (table.num_entries * table.entry_size) as u64;
+
+    if table.num_entries > GPT_MAX_ENTRIES
+        || !(GPT_MIN_ENTRY_SIZE..=MAX_BLOCK_SIZE).contains(&table.entry_size)
+        || !table.entry_size.is_power_of_two()
+        || table.first_usable > table.last_usable
+        || table.last_usable >= num_blocks
+        || table
+            .entries_lba
+            .checked_add(len.div_ceil(bs as u64))
+            .is_none_or(|end| end > num_blocks)
+    {
+        return Err(Error::Corrupt);
+    }
+
+    // The entries are checked in pieces, as they span many blocks.
+    let mut check = Crc32::new();
+    let mut entry = [0u8; GPT_MIN_ENTRY_SIZE];
+    let start = table.entries_lba * bs as u64;
+
+    for off in (0..len).step_by(entry.len()) {
+        reader.read_at(start + off, &mut entry)?;
+        check.update(&entry);
+    }
+
+    if check.finish() != read_u32(header, GPT_ENTRIES_CRC) {
+        return Err(Error::Corrupt);
+    }
+
+    Ok(table)
+}
+
+/// Read partitions of a GPT disk, through the primary header or the backup
+/// one if the primary is corrupted.
+fn gpt_partitions(
+    reader: &mut Reader,
+    disk_name: &str,
+    f: &mut impl FnMut(Entry) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let bs = reader.device().block_size() as u64;
+    let backup = reader
+        .device()
+        .num_blocks()
+        .checked_sub(1)
+        .ok_or(Error::Corrupt)?;
+
+    let table = match gpt_table(reader, 1) {
+        Ok(table) => table,
+        Err(Error::Corrupt) => {
+            warn!("{disk_name}: primary GPT corrupted, trying the backup");
+            gpt_table(reader, backup)?
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut raw = [0u8; GPT_MIN_ENTRY_SIZE];
+
+    for idx in 0..table.num_entries {
+        let off = table.entries_lba * bs + (idx * table.entry_size) as u64;
+
+        reader.read_at(off, &mut raw)?;
+
+        let type_guid = Guid(raw[..16].try_into().unwrap());
+        let guid =
+            Guid(raw[GPT_ENTRY_GUID..GPT_ENTRY_GUID + 16].try_into().unwrap());
+        let first = read_u64(&raw, GPT_ENTRY_FIRST_LBA);
+        let last = read_u64(&raw, GPT_ENTRY_LAST_LBA);
+
+        if type_guid.is_nil() {
+            continue;
+        }
+
+        if first < table.first_usable
+            || first > last
+            || last > table.last_usable
+        {
+            warn!("{disk_name}: GPT entry {} out of bounds", idx + 1);
+            continue;
+        }
+
+        let mut label = [0u16; GPT_NAME_UNITS];
+        for (unit, bytes) in
+            label.iter_mut().zip(raw[GPT_ENTRY_NAME..].chunks_exact(2))
+        {
+            *unit = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        f(Entry {
+            index: idx + 1,
+            start: first,
+            blocks: last - first + 1,
+            scheme: Scheme::Gpt { type_guid, guid },
+            label,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Read primary partitions of an MBR disk.
+///
+/// Returns [`Error::NoTable`] if the MBR is only there to protect a GPT.
+fn mbr_partitions(
+    mbr: &[u8; MBR_SIZE],
+    disk: &dyn BlockDevice,
+    disk_name: &str,
+    f: &mut impl FnMut(Entry) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let signature = read_u32(mbr, MBR_SIGNATURE);
+    let entries = mbr[MBR_ENTRIES..MBR_MAGIC].chunks_exact(MBR_ENTRY_SIZE);
+
+    if entries.clone().any(|entry| entry[4] == MBR_TYPE_PROTECTIVE) {
+        return Err(Error::NoTable);
+    }
+
+    for (idx, entry) in entries.enumerate() {
+        let kind = entry[4];
+        let start = read_u32(entry, 8) as u64;
+        let blocks = read_u32(entry, 12) as u64;
+
+        if kind == MBR_TYPE_EMPTY || blocks == 0 {
+            continue;
+        }
+
+        // Logical partitions are not supported.
+        if MBR_TYPE_EXTENDED.contains(&kind) {
+            debug!("{disk_name}: skipping extended partition {}", idx + 1);
+            continue;
+        }
+
+        if start + blocks > disk.num_blocks() {
+            warn!("{disk_name}: MBR entry {} out of bounds", idx + 1);
+            continue;
+        }
+
+        f(Entry {
+            index: idx + 1,
+            start,
+            blocks,
+            scheme: Scheme::Mbr { signature, kind },
+            label: [0; GPT_NAME_UNITS],
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Read the partition table of a disk, be it GPT or MBR.
+///
+/// Returns name of the partitioning scheme.
+///
+/// # Arguments
+///
+/// - `reader`: Reader of the disk.
+/// - `disk_name`: Name of the disk, for diagnostics.
+/// - `f`: Function called for every partition found.
+fn read_table(
+    reader: &mut Reader,
+    disk_name: &str,
+    f: &mut impl FnMut(Entry) -> Result<(), Error>,
+) -> Result<&'static str, Error> {
+    let mut mbr = [0u8; MBR_SIZE];
+
+    reader.read_at(0, &mut mbr)?;
+
+    if mbr[MBR_MAGIC..] != MBR_MAGIC_VALUE {
+        return Err(Error::NoTable);
+    }
+
+    match mbr_partitions(&mbr, reader.device(), disk_name, f) {
+        Err(Error::NoTable) => {
+            gpt_partitions(reader, disk_name, f)?;
+            Ok("GPT")
+        }
+        result => result.map(|_| "MBR"),
+    }
+}
+
+/// Turn a partition table entry into a partition.
+fn partition(
+    disk: &'static dyn BlockDevice,
+    disk_name: &'static str,
+    entry: &Entry,
+) -> Partition {
+    let mut part = Partition {
+        disk,
+        disk_name,
+        index: entry.index,
+        start: entry.start,
+        blocks: entry.blocks,
+        scheme: entry.scheme,
+        name: [0; MAX_NAME],
+        name_len: 0,
+        label: [0; MAX_LABEL],
+        label_len: 0,
+    };
+
+    // Names of disks ending in a digit are separated from the index, as in
+    // `mmcblk0p1`.
+    let sep = if disk_name.ends_with(|ch: char| ch.is_ascii_digit()) {
+        "p"
+    } else {
+        ""
+    };
+
+    let mut name = Buffer::new(&mut part.name);
+    let _ = fmt::Write::write_fmt(
+        &mut name,
+        format_args!("{disk_name}{sep}{}", entry.index),
+    );
+    part.name_len = name.len;
+
+    let units = entry.label.iter().copied().take_while(|unit| *unit != 0);
+    let mut label = Buffer::new(&mut part.label);
+
+    for ch in char::decode_utf16(units) {
+        let ch = ch.unwrap_or(char::REPLACEMENT_CHARACTER);
+
+        if fmt::Write::write_char(&mut label, ch).is_err() {
+            break;
+        }
+    }
+    part.label_len = label.len;
+
+    part
+}
+
+/// Scan a disk for partitions and register them as block devices.
+///
+/// Returns the number of partitions found. Partitions of a GPT disk are read
+/// from the backup table if the primary one is corrupted.
+///
+/// # Arguments
+///
+/// - `disk_name`: Name of the disk, which partition names are derived from.
+/// - `disk`: The disk to scan.
+pub fn scan(
+    disk_name: &'static str,
+    disk: &'static dyn BlockDevice,
+) -> Result<usize, Error> {
+    let mut reader = Reader::new(disk)?;
+    let mut found = 0;
+
+    let mut keep = |entry: Entry| {
+        let parts = unsafe { &mut *PARTITIONS.0.get() };
+        let slot = parts
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(Error::TooMany)?;
+        let part: &'static Partition =
+            slot.insert(partition(disk, disk_name, &entry));
+
+        debug!(
+            "{}: {} MiB at block {}, PARTUUID={} '{}'",
+            part.name(),
+            part.capacity() >> 20,
+            part.start,
+            part.partuuid(),
+            part.label()
+        );
+
+        if let Err(err) = block::register(part.name(), part) {
+            warn!("{}: not registered: {}", part.name(), err);
+        }
+
+        found += 1;
+
+        Ok(())
+    };
+
+    let scheme = read_table(&mut reader, disk_name, &mut keep)?;
+
+    info!("{disk_name}: {scheme}, {found} partitions");
+
+    Ok(found)
+}
+
+/// Scan all registered block devices, other than partitions, for partitions.
+///
+/// Disks without a partition table are left as they are.
+pub fn scan_all() {
+    let mut disks = [None; block::MAX_DEVICES];
+
+    for (slot, (name, dev)) in disks.iter_mut().zip(
+        block::devices()
+            .filter(|(name, _)| partitions().all(|part| part.name() != *name)),
+    ) {
+        *slot = Some((name, dev));
+    }
+
+    for (name, dev) in disks.into_iter().flatten() {
+        match scan(name, dev) {
+            Ok(_) | Err(Error::NoTable) => {}
+            Err(err) => warn!("{name}: partition scan failed: {err}"),
+        }
+    }
+}
+
+/// Obtain an iterator over partitions of all disks scanned.
+pub fn partitions() -> impl Iterator<Item = &'static Partition> {
+    let parts = unsafe { &*PARTITIONS.0.get() };
+
+    parts.iter().flatten()
+}
+
+/// Find a partition of a disk by its index, starting at one.
+pub fn by_index(disk_name: &str, index: usize) -> Option<&'static Partition> {
+    partitions().find(|part| part.disk_name == disk_name && part.index == index)
+}
+
+/// Find a GPT partition by its unique partition GUID.
+pub fn by_guid(guid: &Guid) -> Option<&'static Partition> {
+    partitions().find(|part| part.guid() == Some(*guid))
+}
+
+/// Find a partition by its identifier used by Linux, see [`PartUuid`].
+pub fn by_partuuid(partuuid: &str) -> Option<&'static Partition> {
+    partitions().find(|part| part.partuuid().matches(partuuid))
+}
+
+/// Find a GPT partition by its label.
+pub fn by_label(label: &str) -> Option<&'static Partition> {
+    partitions().find(|part| part.label() == label)
+}
+
+/// Find a partition named the way a root device is named on the Linux
+/// command line.
+///
+/// Accepted are `PARTUUID=<partuuid>`, `PARTLABEL=<label>` and partition
+/// names such as `vda1`.
+pub fn lookup(spec: &str) -> Option<&'static Partition> {
+    if let Some(partuuid) = spec.strip_prefix("PARTUUID=") {
+        return by_partuuid(partuuid);
+    }
+
+    if let Some(label) = spec.strip_prefix("PARTLABEL=") {
+        return by_label(label);
+    }
+
+    partitions().find(|part| part.name() == spec)
+}
+
+/// A fixed buffer written to with [`fmt::Write`], failing once full.
+struct Buffer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Buffer<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Buffer { buf, len: 0 }
+    }
+}
+
+impl fmt::Write for Buffer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(fmt::Error)?;
+
+        dst.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+
+        Ok(())
+    }
+}
+
+/// See: [`PARTITIONS`].
+struct PartitionsCell(UnsafeCell<[Option<Partition>; MAX_PARTITIONS]>);
+unsafe impl Sync for PartitionsCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The EFI system partition type, in both forms.
+    const ESP_TEXT: &str = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+    const ESP: Guid = Guid([
+        0x28, 0x73, 0x2a, 0xc1, 0x1f, 0xf8, 0xd2, 0x11, 0xba, 0x4b, 0x00, 0xa0,
+        0xc9, 0x3e, 0xc9, 0x3b,
+    ]);
+
+    /// Size of blocks of [`Disk`].
+    const BLOCK: usize = 512;
+
+    /// Number of blocks of [`Disk`].
+    const BLOCKS: u64 = 16;
+
+    /// Number of entries of the partition tables of [`Disk`], filling a block.
+    const ENTRIES: usize = BLOCK / GPT_MIN_ENTRY_SIZE;
+
+    /// A disk whose blocks are made up on every read, according to a layout.
+    ///
+    /// GPT layouts hold two partitions, `boot_a` in blocks 4 to 7 and `boot_b`
+    /// in blocks 8 to 12, with an unused entry in between.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Disk {
+        /// A protective MBR and both GPT headers intact.
+        Gpt,
+        /// Like [`Disk::Gpt`], but the primary header fails its checksum.
+        BadPrimary,
+        /// Like [`Disk::Gpt`], but both headers fail their checksums.
+        BadBoth,
+        /// A legacy MBR with no GPT.
+        Mbr,
+    }
+
+    impl Disk {
+        /// Fill an MBR entry in.
+        fn mbr_entry(
+            buf: &mut [u8],
+            idx: usize,
+            kind: u8,
+            start: u32,
+            len: u32,
+        ) {
+            let entry = &mut buf[MBR_ENTRIES + idx * MBR_ENTRY_SIZE..];
+
+            entry[4] = kind;
+            entry[8..12].copy_from_slice(&start.to_le_bytes());
+            entry[12..16].copy_from_slice(&len.to_le_bytes());
+        }
+
+        fn mbr(&self, buf: &mut [u8]) {
+            if *self == Disk::Mbr {
+                buf[MBR_SIGNATURE..MBR_SIGNATURE + 4]
+                    .copy_from_slice(&0x1234_abcdu32.to_le_bytes());
+                Self::mbr_entry(buf, 0, 0x83, 4, 4);
+                Self::mbr_entry(buf, 1, MBR_TYPE_EXTENDED[0], 8, 4);
+                Self::mbr_entry(buf, 2, 0x0c, 8, 100);
+                Self::mbr_entry(buf, 3, 0x83, 12, 4);
+            } else {
+                Self::mbr_entry(buf, 0, MBR_TYPE_PROTECTIVE, 1, 15);
+            }
+
+            buf[MBR_MAGIC..].copy_from_slice(&MBR_MAGIC_VALUE);
+        }
+
+        /// Fill a GPT partition entry in.
+        fn gpt_entry(
+            buf: &mut [u8],
+            idx: usize,
+            first: u64,
+            last: u64,
+            label: &str,
+        ) {
+            let entry = &mut buf[idx * GPT_MIN_ENTRY_SIZE..];
+
+            entry[..16].copy_from_slice(&ESP.0);
+            entry[GPT_ENTRY_GUID..GPT_ENTRY_GUID + 16].fill(idx as u8 + 1);
+            entry[GPT_ENTRY_FIRST_LBA..GPT_ENTRY_FIRST_LBA + 8]
+                .copy_from_slice(&first.to_le_bytes());
+            entry[GPT_ENTRY_LAST_LBA..GPT_ENTRY_LAST_LBA + 8]
+                .copy_from_slice(&last.to_le_bytes());
+
+            for (nth, unit) in label.encode_utf16().enumerate() {
+                let off = GPT_ENTRY_NAME + nth * 2;
+                entry[off..off + 2].copy_from_slice(&unit.to_le_bytes());
+            }
+        }
+
+        fn entries(buf: &mut [u8]) {
+            Self::gpt_entry(buf, 0, 4, 7, "boot_a");
+            Self::gpt_entry(buf, 2, 8, 12, "boot_b");
+        }
+
+        /// Fill a GPT header in.
+        ///
+        /// # Arguments
+        ///
+        /// - `buf`: Memory of the block holding the header.
+        /// - `lba`: Block holding the header.
+        /// - `entries`: Block holding the partition entries.
+        /// - `corrupt`: Whether to break the checksum of the header.
+        fn header(buf: &mut [u8], lba: u64, entries: u64, corrupt: bool) {
+            let mut table = [0u8; BLOCK];
+            Self::entries(&mut table);
+
+            buf[..8].copy_from_slice(GPT_SIGNATURE);
+            buf[GPT_HEADER_SIZE..GPT_HEADER_SIZE + 4]
+                .copy_from_slice(&(GPT_MIN_HEADER_SIZE as u32).to_le_bytes());
+            buf[GPT_MY_LBA..GPT_MY_LBA + 8].copy_from_slice(&lba.to_le_bytes());
+            buf[GPT_FIRST_USABLE_LBA..GPT_FIRST_USABLE_LBA + 8]
+                .copy_from_slice(&3u64.to_le_bytes());
+            buf[GPT_LAST_USABLE_LBA..GPT_LAST_USABLE_LBA + 8]
+                .copy_from_slice(&13u64.to_le_bytes());
+            buf[GPT_ENTRIES_LBA..GPT_ENTRIES_LBA + 8]
+                .copy_from_slice(&entries.to_le_bytes());
+            buf[GPT_NUM_ENTRIES..GPT_NUM_ENTRIES + 4]
+                .copy_from_slice(&(ENTRIES as u32).to_le_bytes());
+            buf[GPT_ENTRY_SIZE..GPT_ENTRY_SIZE + 4]
+                .copy_from_slice(&(GPT_MIN_ENTRY_SIZE as u32).to_le_bytes());
+            buf[GPT_ENTRIES_CRC..GPT_ENTRIES_CRC + 4]
+                .copy_from_slice(&crate::crc32::crc32(&table).to_le_bytes());
+
+            let crc = crate::crc32::crc32(&buf[..GPT_MIN_HEADER_SIZE])
+                ^ u32::from(corrupt);
+            buf[GPT_HEADER_CRC..GPT_HEADER_CRC + 4]
+                .copy_from_slice(&crc.to_le_bytes());
+        }
+    }
+
+    impl BlockDevice for Disk {
+        fn block_size(&self) -> usize {
+            BLOCK
+        }
+
+        fn num_blocks(&self) -> u64 {
+            BLOCKS
+        }
+
+        fn read_blocks(
+            &self,
+            lba: u64,
+            buf: &mut [u8],
+        ) -> Result<(), block::Error> {
+            block::check_access(self, lba, buf.len())?;
+
+            for (lba, block) in (lba..).zip(buf.chunks_exact_mut(BLOCK)) {
+                block.fill(0);
+
+                match (self, lba) {
+                    (_, 0) => self.mbr(block),
+                    (Disk::Mbr, _) => {}
+                    (_, 1) => Self::header(block, 1, 2, *self != Disk::Gpt),
+                    (_, 2) | (_, 14) => Self::entries(block),
+                    (_, 15) => {
+                        Self::header(block, 15, 14, *self == Disk::BadBoth)
+                    }
+                    _ => {}
+                }
+            }
+
+            Ok(())
+        }
+
+        fn write_blocks(
+            &self,
+            _lba: u64,
+            _buf: &[u8],
+        ) -> Result<(), block::Error> {
+            Err(block::Error::ReadOnly)
+        }
+    }
+
+    /// Read the partition table of a disk, along with up to four entries.
+    fn read(disk: &Disk) -> Result<(&'static str, [Option<Entry>; 4]), Error> {
+        let mut reader = Reader::new(disk)?;
+        let mut found = [const { None }; 4];
+        let mut count = 0;
+
+        let scheme = read_table(&mut reader, "test", &mut |entry| {
+            found[count] = Some(entry);
+            count += 1;
+            Ok(())
+        })?;
+
+        Ok((scheme, found))
+    }
+
+    /// Check the partitions found on any of the GPT layouts.
+    fn check_gpt(found: &[Option<Entry>; 4]) {
+        let [Some(a), Some(b), None, None] = found else {
+            panic!("expected two partitions");
+        };
+
+        assert_eq!((a.index, a.start, a.blocks), (1, 4, 4));
+        assert_eq!((b.index, b.start, b.blocks), (3, 8, 5));
+
+        let part = partition(&Disk::Gpt, "vda", b);
+
+        assert_eq!(part.name(), "vda3");
+        assert_eq!(part.label(), "boot_b");
+        assert_eq!(part.guid(), Some(Guid([3; 16])));
+    }
+
+    #[test_case]
+    fn gpt_primary() {
+        let (scheme, found) = read(&Disk::Gpt).unwrap();
+
+        assert_eq!(scheme, "GPT");
+        check_gpt(&found);
+    }
+
+    #[test_case]
+    fn gpt_backup_fallback() {
+        let (scheme, found) = read(&Disk::BadPrimary).unwrap();
+
+        assert_eq!(scheme, "GPT");
+        check_gpt(&found);
+
+        assert_eq!(read(&Disk::BadBoth).err(), Some(Error::Corrupt));
+    }
+
+    #[test_case]
+    fn mbr_primary_partitions() {
+        let (scheme, found) = read(&Disk::Mbr).unwrap();
+
+        // The extended partition and the one past the end are skipped.
+        let [Some(a), Some(b), None, None] = &found else {
+            panic!("expected two partitions");
+        };
+
+        assert_eq!(scheme, "MBR");
+        assert_eq!((a.index, a.start, a.blocks), (1, 4, 4));
+        assert_eq!((b.index, b.start, b.blocks), (4, 12, 4));
+
+        let part = partition(&Disk::Mbr, "mmcblk0", b);
+
+        assert_eq!(part.name(), "mmcblk0p4");
+        assert_eq!(part.label(), "");
+        assert!(part.partuuid().matches("1234abcd-04"));
+    }
+
+    #[test_case]
+    fn guid_parse() {
+        assert_eq!(Guid::parse(ESP_TEXT), Some(ESP));
+        assert_eq!(
+            Guid::parse("C12A7328-F81F-11D2-BA4B-00A0C93EC93B"),
+            Some(ESP)
+        );
+        assert_eq!(Guid::parse("c12a7328f81f11d2ba4b00a0c93ec93b"), None);
+        assert_eq!(Guid::parse("c12a7328-f81f-11d2-ba4b-00a0c93ec93x"), None);
+    }
+
+    #[test_case]
+    fn partuuid_matches() {
+        let gpt = PartUuid(
+            Scheme::Gpt {
+                type_guid: ESP,
+                guid: ESP,
+            },
+            1,
+        );
+        let mbr = PartUuid(
+            Scheme::Mbr {
+                signature: 0x1234_abcd,
+                kind: 0x83,
+            },
+            2,
+        );
+
+        assert!(gpt.matches(ESP_TEXT));
+        assert!(mbr.matches("1234ABCD-02"));
+        assert!(!mbr.matches("1234abcd-01"));
+        assert!(!mbr.matches("1234abcd"));
+    }
+}
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::convert::Infallible;
+
+use crate::arch;
+use crate::console;
+use crate::fdt::patch;
+use crate::irq;
+use crate::log::info;
+use crate::smp;
+use crate::time;
+
+/// Size of the buffer receiving the devicetree handed off to the payload.
+const FDT_SIZE: usize = 0x10000;
+
+/// Devicetree handed off to the payload.
+///
+/// Only written by [`handoff`], on the way out of lunar.
+static FDT: FdtCell = FdtCell(UnsafeCell::new(FdtBuffer([0; FDT_SIZE])));
+
+/// See: [`FDT`].
+#[repr(C, align(8))]
+struct FdtBuffer([u8; FDT_SIZE]);
+
+/// Hand the system over to a payload.
+///
+/// The devicetree is patched by the architecture code, secondary cores are
+/// parked, the interrupt controller is left the way the payload expects it and
+/// interrupts are masked. The payload is then entered on the calling core, as
+/// the boot protocol of the architecture mandates.
+///
+/// Returns only if the devicetree cannot be written, which happens before
+/// anything is torn down.
+///
+/// # Arguments
+///
+/// - `entry`: Address of the entry point of the payload.
+pub fn handoff(entry: usize) -> Result<Infallible, patch::Error> {
+    let fdt = unsafe { &mut (*FDT.0.get()).0 };
+    let size = arch::patch_fdt(fdt)?;
+
+    info!(
+        "entering payload at {entry:#x}, devicetree at {:#x} ({size} bytes)",
+        fdt.as_ptr() as usize
+    );
+    console::flush();
+
+    smp::handoff();
+    time::cancel_alarm();
+    arch::disable_interrupts();
+    irq::handoff();
+
+    arch::enter_payload(entry, fdt.as_ptr() as usize)
+}
+
+/// See: [`FDT`].
+struct FdtCell(UnsafeCell<FdtBuffer>);
+unsafe impl Sync for FdtCell {}
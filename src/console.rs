@@ -0,0 +1,323 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+pub mod earlycon;
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::cpu;
+
+/// Maximum number of consoles registered at the same time.
+const MAX_CONSOLES: usize = 4;
+
+/// Value of [`OWNER`] when no core holds the console lock.
+const UNOWNED: u64 = u64::MAX;
+
+/// Number of attempts at taking the console lock after a panic, before the
+/// lock is taken away from its owner.
+const PANIC_SPINS: usize = 1 << 20;
+
+/// Registered consoles.
+///
+/// Protected by the console lock, see [`lock`].
+static CONSOLES: ConsolesCell = ConsolesCell(UnsafeCell::new(Consoles {
+    slots: [None; MAX_CONSOLES],
+    active: None,
+}));
+
+/// Hardware identifier of the core holding the console lock.
+static OWNER: AtomicU64 = AtomicU64::new(UNOWNED);
+
+/// Set once the system has panicked, see [`enter_panic`].
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// An output device backing the print macros.
+pub trait Sink: Sync {
+    /// Write a string to the device.
+    fn write_str(&self, s: &str);
+
+    /// Read a byte from the device, if one is available.
+    fn getc(&self) -> Option<u8> {
+        None
+    }
+
+    /// Wait for all written data to leave the device.
+    fn flush(&self) {}
+}
+
+/// Errors reported by console registration.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// All console slots are taken.
+    Full,
+    /// A console with the same name is already registered.
+    Exists,
+}
+
+#[derive(Clone, Copy)]
+struct Console {
+    name: &'static str,
+    sink: &'static dyn Sink,
+}
+
+/// See: [`CONSOLES`].
+struct Consoles {
+    slots: [Option<Console>; MAX_CONSOLES],
+    /// Slot of the console that input is taken from.
+    active: Option<usize>,
+}
+
+impl Consoles {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_some_and(|con| con.name == name))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Console> {
+        self.slots.iter().flatten()
+    }
+}
+
+/// Ownership of the console lock, released when dropped.
+struct Guard {
+    /// Whether the lock was already held by the current core, in which case
+    /// it is left held on release.
+    nested: bool,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if !self.nested {
+            OWNER.store(UNOWNED, Ordering::Release);
+        }
+    }
+}
+
+/// Take the console lock.
+///
+/// The lock is recursive, so that a core interrupted or faulting while
+/// printing can still print. Once the system has panicked, a lock held for too
+/// long is taken away from its owner, as the owner may never release it.
+fn lock() -> Guard {
+    let me = cpu::current_id();
+
+    if OWNER.load(Ordering::Relaxed) == me {
+        return Guard { nested: true };
+    }
+
+    let mut spins = 0;
+
+    while OWNER
+        .compare_exchange_weak(
+            UNOWNED,
+            me,
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        )
+        .is_err()
+    {
+        if PANICKING.load(Ordering::Relaxed) && spins >= PANIC_SPINS {
+            OWNER.store(me, Ordering::Relaxed);
+            break;
+        }
+
+        spins += 1;
+        core::hint::spin_loop();
+    }
+
+    Guard { nested: false }
+}
+
+/// Obtain the registered consoles, with the console lock held.
+fn consoles(_guard: &mut Guard) -> &mut Consoles {
+    unsafe { &mut *CONSOLES.0.get() }
+}
+
+/// Register a console.
+///
+/// Output of the print macros is written to all registered consoles. The
+/// first console registered becomes the active one, which input is read from.
+///
+/// # Arguments
+///
+/// - `name`: Unique name of the console.
+/// - `sink`: Device backing the console.
+pub fn register(
+    name: &'static str,
+    sink: &'static dyn Sink,
+) -> Result<(), Error> {
+    let mut guard = lock();
+    let consoles = consoles(&mut guard);
+
+    if consoles.find(name).is_some() {
+        return Err(Error::Exists);
+    }
+
+    let idx = consoles
+        .slots
+        .iter()
+        .position(Option::is_none)
+        .ok_or(Error::Full)?;
+
+    consoles.slots[idx] = Some(Console { name, sink });
+    consoles.active.get_or_insert(idx);
+
+    Ok(())
+}
+
+/// Unregister a console, returning whether it was registered.
+///
+/// Pending output of the console is flushed. If it was the active one, the
+/// first remaining console becomes active.
+pub fn unregister(name: &str) -> bool {
+    let mut guard = lock();
+    let consoles = consoles(&mut guard);
+
+    let Some(idx) = consoles.find(name) else {
+        return false;
+    };
+
+    if let Some(con) = consoles.slots[idx].take() {
+        con.sink.flush();
+    }
+
+    if consoles.active == Some(idx) {
+        consoles.active = consoles.slots.iter().position(Option::is_some);
+    }
+
+    true
+}
+
+/// Make a registered console the one input is read from.
+///
+/// Returns `false` if there is no console with a given name.
+pub fn set_active(name: &str) -> bool {
+    let mut guard = lock();
+    let consoles = consoles(&mut guard);
+
+    let Some(idx) = consoles.find(name) else {
+        return false;
+    };
+
+    consoles.active = Some(idx);
+
+    true
+}
+
+/// Check whether output of the print macros is directed to any device.
+pub fn has_sink() -> bool {
+    let mut guard = lock();
+
+    consoles(&mut guard).iter().next().is_some()
+}
+
+/// Read a byte from the active console, if one is available.
+pub fn getc() -> Option<u8> {
+    let mut guard = lock();
+    let consoles = consoles(&mut guard);
+
+    consoles.slots[consoles.active?]?.sink.getc()
+}
+
+/// Wait for output written to all consoles to leave them.
+pub fn flush() {
+    let mut guard = lock();
+
+    consoles(&mut guard).iter().for_each(|con| con.sink.flush());
+}
+
+/// Prepare the consoles for use by the panic handler.
+///
+/// From now on, a console lock held by another core is taken away from it
+/// after a while, so that the panic message is printed even if that core is
+/// stuck or has stopped.
+pub fn enter_panic() {
+    PANICKING.store(true, Ordering::Relaxed);
+}
+
+struct Writer<'a>(&'a Consoles);
+
+impl fmt::Write for Writer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.iter().for_each(|con| con.sink.write_str(s));
+
+        Ok(())
+    }
+}
+
+/// Implementation detail of the print macros.
+///
+/// The console lock is held for the whole message, so that messages printed
+/// by different cores are not interleaved.
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    let mut guard = lock();
+    let _ = fmt::Write::write_fmt(&mut Writer(consoles(&mut guard)), args);
+}
+
+/// Implementation detail of the eprint macros.
+#[doc(hidden)]
+pub fn _eprint(args: fmt::Arguments) {
+    let mut guard = lock();
+    let consoles = consoles(&mut guard);
+
+    let _ = fmt::Write::write_fmt(&mut Writer(consoles), args);
+    consoles.iter().for_each(|con| con.sink.flush());
+}
+
+/// Print formatted text to all consoles.
+#[allow(unused_macros)]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::console::_print(format_args!($($arg)*))
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use print;
+
+/// Print formatted text to all consoles, followed by a newline.
+#[allow(unused_macros)]
+macro_rules! println {
+    () => {
+        $crate::console::print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::console::print!("{}\n", format_args!($($arg)*))
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use println;
+
+/// Print formatted text to all consoles and wait for it to leave them.
+///
+/// Meant for errors, which must not be lost if the system goes down right
+/// after printing them.
+#[allow(unused_macros)]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::console::_eprint(format_args!($($arg)*))
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use eprint;
+
+/// Print formatted text to all consoles followed by a newline, and wait for
+/// it to leave them.
+#[allow(unused_macros)]
+macro_rules! eprintln {
+    () => {
+        $crate::console::eprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::console::eprint!("{}\n", format_args!($($arg)*))
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use eprintln;
+
+/// See: [`CONSOLES`].
+struct ConsolesCell(UnsafeCell<Consoles>);
+unsafe impl Sync for ConsolesCell {}
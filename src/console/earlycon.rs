@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+
+use crate::console::{self, Sink};
+use crate::drivers::ns16550::Ns16550;
+use crate::drivers::pl011::Pl011;
+use crate::drivers::serial::{self, Serial};
+
+/// Name under which the early console is registered.
+pub const NAME: &str = "earlycon";
+
+/// The UART backing the early console.
+///
+/// Written by [`init`], before secondary cores are started.
+static EARLYCON: EarlyconCell = EarlyconCell(UnsafeCell::new(None));
+
+/// UARTs usable as the early console.
+enum Earlycon {
+    Pl011(Pl011),
+    Ns16550(Ns16550),
+}
+
+impl Earlycon {
+    fn serial(&self) -> &dyn Serial {
+        match self {
+            Earlycon::Pl011(uart) => uart,
+            Earlycon::Ns16550(uart) => uart,
+        }
+    }
+}
+
+impl Sink for Earlycon {
+    fn write_str(&self, s: &str) {
+        self.serial().puts(s);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        self.serial().getc()
+    }
+
+    fn flush(&self) {
+        self.serial().flush();
+    }
+}
+
+/// Start the early console on the port named by `/chosen/stdout-path`.
+///
+/// Only the register range of the port is looked at, so the console works
+/// right after the devicetree is set up, before the driver model runs. The
+/// port is used as configured by the firmware. Returns `false` if there is no
+/// stdout-path or its port is not supported.
+pub fn init() -> bool {
+    let Some((node, _)) = serial::stdout() else {
+        return false;
+    };
+
+    let earlycon = match Pl011::early(&node) {
+        Ok(uart) => Earlycon::Pl011(uart),
+        Err(_) => match Ns16550::early(&node) {
+            Ok(uart) => Earlycon::Ns16550(uart),
+            Err(_) => return false,
+        },
+    };
+
+    let earlycon = unsafe {
+        *EARLYCON.0.get() = Some(earlycon);
+        (*EARLYCON.0.get()).as_ref().unwrap()
+    };
+
+    console::register(NAME, earlycon).is_ok()
+}
+
+/// Stop the early console, once its port is taken over by a proper driver.
+pub fn exit() {
+    console::unregister(NAME);
+}
+
+/// See: [`EARLYCON`].
+struct EarlyconCell(UnsafeCell<Option<Earlycon>>);
+unsafe impl Sync for EarlyconCell {}
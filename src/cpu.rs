@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+
+use crate::arch;
+use crate::fdt::{self, FdtNode, FdtStreamable};
+
+/// Maximal number of cores supported by lunar.
+pub const MAX_CPUS: usize = 8;
+
+/// Cores described in the `/cpus` node of the devicetree.
+///
+/// Populated once by [`init`], before any secondary core is started, and only
+/// read afterwards. As such, the [`UnsafeCell`] suffices here.
+static CPUS: CpuMapCell = CpuMapCell(UnsafeCell::new(CpuMap::new()));
+
+/// A core of the system, as described by the devicetree.
+pub struct Cpu {
+    /// Hardware identifier, as found in the `reg` property.
+    pub hwid: u64,
+    /// Node describing the core.
+    pub node: FdtNode<'static>,
+}
+
+/// Mapping of logical core indices to cores.
+struct CpuMap {
+    cpus: [Option<Cpu>; MAX_CPUS],
+    count: usize,
+}
+
+impl CpuMap {
+    const fn new() -> Self {
+        CpuMap {
+            cpus: [const { None }; MAX_CPUS],
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, cpu: Cpu) {
+        if self.count < MAX_CPUS {
+            self.cpus[self.count] = Some(cpu);
+            self.count += 1;
+        }
+    }
+}
+
+/// Enumerate cores of the system from the `/cpus` devicetree node.
+///
+/// Calling core is assigned logical index zero. Remaining cores get subsequent
+/// indices in the order in which they appear in the devicetree. Cores in excess
+/// of [`MAX_CPUS`] are ignored.
+pub fn init() {
+    let Some(cpus) = fdt::get().node_by_path("/cpus") else {
+        return;
+    };
+
+    let map = unsafe { &mut *CPUS.0.get() };
+    let current = current_id();
+
+    let nodes = || {
+        cpus.stream()
+            .filter(|node| {
+                node.prop_strs("device_type")
+                    .is_some_and(|mut types| types.any(|ty| ty == "cpu"))
+            })
+            .filter_map(|node| Some((node.reg_addr_u64()?, node)))
+    };
+
+    for (hwid, node) in nodes().filter(|(hwid, _)| *hwid == current) {
+        map.push(Cpu { hwid, node });
+    }
+
+    for (hwid, node) in nodes().filter(|(hwid, _)| *hwid != current) {
+        map.push(Cpu { hwid, node });
+    }
+}
+
+/// Obtain hardware identifier of the current core.
+///
+/// On AArch64 this is the value of Aff3 to Aff0 fields of MPIDR_EL1, while on
+/// RISC-V it is the hart identifier.
+pub fn current_id() -> u64 {
+    arch::cpu_hwid()
+}
+
+/// Obtain logical index of the current core.
+pub fn current() -> Option<usize> {
+    index_of(current_id())
+}
+
+/// Map a hardware identifier of a core to its logical index.
+pub fn index_of(hwid: u64) -> Option<usize> {
+    iter().position(|cpu| cpu.hwid == hwid)
+}
+
+/// Obtain a core with a given logical index.
+pub fn get(idx: usize) -> Option<&'static Cpu> {
+    let map = unsafe { &*CPUS.0.get() };
+
+    map.cpus.get(idx)?.as_ref()
+}
+
+/// Iterate over all known cores, in order of their logical indices.
+pub fn iter() -> impl Iterator<Item = &'static Cpu> {
+    let map = unsafe { &*CPUS.0.get() };
+
+    map.cpus[..map.count].iter().flatten()
+}
+
+/// See: [`CPUS`].
+struct CpuMapCell(UnsafeCell<CpuMap>);
+unsafe impl Sync for CpuMapCell {}
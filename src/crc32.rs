@@ -0,0 +1,91 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+/// Reversed IEEE 802.3 polynomial, as used by GPT, zlib and Ethernet.
+const POLY: u32 = 0xEDB8_8320;
+
+/// Remainders of all byte values, for processing data a byte at a time.
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[byte] = crc;
+        byte += 1;
+    }
+
+    table
+}
+
+/// A CRC-32 computed over data fed in pieces.
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32(u32);
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Crc32(!0)
+    }
+
+    /// Feed more data into the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 = TABLE[((self.0 ^ *byte as u32) & 0xFF) as usize]
+                ^ (self.0 >> 8);
+        }
+    }
+
+    /// Obtain the checksum of all data fed so far.
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compute the CRC-32 of a piece of data.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+
+    crc.update(data);
+    crc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test_case]
+    fn pieces_match_whole() {
+        let mut crc = Crc32::new();
+
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+
+        assert_eq!(crc.finish(), crc32(b"123456789"));
+    }
+}
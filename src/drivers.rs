@@ -0,0 +1,265 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+#[cfg(target_arch = "riscv64")]
+pub mod aclint;
+pub mod clk;
+pub mod fw_cfg;
+#[cfg(target_arch = "aarch64")]
+pub mod gic;
+pub mod ns16550;
+pub mod pl011;
+#[cfg(target_arch = "riscv64")]
+pub mod plic;
+pub mod serial;
+#[cfg(any(feature = "el3-monitor", feature = "m-mode"))]
+pub mod syscon;
+pub mod virtio;
+
+use core::cell::UnsafeCell;
+
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::irq;
+use crate::log::{debug, error, warn};
+
+unsafe extern "C" {
+    // See: arch/generic/sections.lds.h
+    static __drivers: u8;
+    static __edrivers: u8;
+}
+
+/// Maximum number of devices that can be bound to drivers.
+const MAX_BOUND: usize = 64;
+
+/// Maximum number of probes that can be deferred at the same time.
+const MAX_DEFERRED: usize = 16;
+
+/// Devicetree nodes bound to drivers.
+///
+/// Only written by [`probe_all`], which runs once on the primary core before
+/// secondary cores are started.
+static BOUND: BoundCell = BoundCell(UnsafeCell::new(Bound {
+    nodes: [0; MAX_BOUND],
+    count: 0,
+}));
+
+/// Errors reported by driver probe functions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The node does not describe a device the driver can handle.
+    NoDevice,
+    /// A dependency of the device is not ready yet, the probe is to be retried
+    /// once other devices are bound.
+    Defer,
+    /// The node lacks required properties or has malformed ones.
+    Invalid,
+    /// The device is already in use.
+    Busy,
+    /// The device reported a failure or behaves unexpectedly.
+    Io,
+}
+
+impl From<irq::Error> for Error {
+    /// Interrupts cannot be requested before the interrupt controller is
+    /// bound, so a missing one defers the probe.
+    fn from(err: irq::Error) -> Self {
+        match err {
+            irq::Error::NoController => Error::Defer,
+            irq::Error::Busy => Error::Busy,
+            irq::Error::OutOfRange | irq::Error::BadSpecifier => Error::Invalid,
+            irq::Error::NotRoot => Error::NoDevice,
+        }
+    }
+}
+
+/// Probing stages, in the order in which they are run.
+///
+/// Drivers of a stage are only probed once all devices of preceding stages
+/// are bound, or have failed to bind.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Stage {
+    /// Interrupt controllers, which other devices route interrupts through.
+    Irqchip,
+    /// Clock providers.
+    Clock,
+    /// Everything else.
+    Device,
+}
+
+impl Stage {
+    const ALL: [Stage; 3] = [Stage::Irqchip, Stage::Clock, Stage::Device];
+}
+
+/// Descriptor of a devicetree driver.
+///
+/// Drivers are registered with [`register_driver`].
+pub struct Driver {
+    /// Name of the driver, for diagnostics.
+    pub name: &'static str,
+    /// Compatible strings of devices handled by the driver.
+    pub compatible: &'static [&'static str],
+    /// Stage at which devices of the driver are probed.
+    pub stage: Stage,
+    /// Bind the driver to a device described by a devicetree node.
+    pub probe: fn(&FdtNode<'static>) -> Result<(), Error>,
+}
+
+/// See: [`BOUND`].
+struct Bound {
+    nodes: [usize; MAX_BOUND],
+    count: usize,
+}
+
+/// Register a devicetree driver.
+///
+/// The descriptor is placed in a dedicated section, from which [`probe_all`]
+/// picks it up.
+///
+/// # Arguments
+///
+/// - `$name`: Name of the static holding the descriptor.
+/// - `$driver`: Constant expression evaluating to a [`Driver`].
+macro_rules! register_driver {
+    ($name:ident, $driver:expr) => {
+        #[used]
+        #[unsafe(link_section = $crate::sections::drivers!())]
+        static $name: $crate::drivers::Driver = $driver;
+    };
+}
+pub(crate) use register_driver;
+
+/// Obtain all registered drivers.
+fn drivers() -> &'static [Driver] {
+    unsafe {
+        let start = &raw const __drivers as *const Driver;
+        let end = &raw const __edrivers as *const Driver;
+
+        core::slice::from_raw_parts(start, end.offset_from(start) as usize)
+    }
+}
+
+/// Find the driver best matching a node.
+///
+/// Compatible strings of a node go from the most to the least specific one, so
+/// the first of them handled by any driver decides.
+pub fn driver_of(node: &FdtNode) -> Option<&'static Driver> {
+    node.prop_strs("compatible")?.find_map(|compat| {
+        drivers()
+            .iter()
+            .find(|driver| driver.compatible.contains(&compat))
+    })
+}
+
+/// Check whether a node describes a device which is in use.
+///
+/// Nodes without the `status` property are considered enabled.
+pub fn is_enabled(node: &FdtNode) -> bool {
+    node.prop_str("status")
+        .map(|status| status.trim_end_matches('\0'))
+        .is_none_or(|status| status == "okay" || status == "ok")
+}
+
+/// Check whether a driver has been bound to a node.
+pub fn is_bound(node: &FdtNode) -> bool {
+    let bound = unsafe { &*BOUND.0.get() };
+    let id = node.id();
+
+    bound.nodes[..bound.count].contains(&id)
+}
+
+fn mark_bound(node: &FdtNode) {
+    let bound = unsafe { &mut *BOUND.0.get() };
+
+    if bound.count < MAX_BOUND {
+        bound.nodes[bound.count] = node.id();
+        bound.count += 1;
+    }
+}
+
+/// Call a given function for every node of a subtree, parents first.
+fn walk(node: &FdtNode<'static>, f: &mut impl FnMut(&FdtNode<'static>)) {
+    for child in node.stream() {
+        f(&child);
+        walk(&child, f);
+    }
+}
+
+/// Probe a single device, returning whether the probe was deferred.
+fn probe(node: &FdtNode<'static>, driver: &'static Driver) -> bool {
+    match (driver.probe)(node) {
+        Ok(()) => {
+            debug!("{}: bound to {}", driver.name, node.name());
+            mark_bound(node);
+        }
+        Err(Error::Defer) => return true,
+        Err(Error::NoDevice) => {}
+        Err(err) => {
+            warn!(
+                "{}: probe of {} failed: {:?}",
+                driver.name,
+                node.name(),
+                err
+            )
+        }
+    }
+
+    false
+}
+
+/// Bind drivers to all enabled devices described by the devicetree.
+///
+/// Devices are probed stage by stage, in the order of the devicetree within a
+/// stage. Deferred probes are retried at the end of each stage, for as long as
+/// any of them succeeds. Devices still deferred after the last stage are left
+/// unbound.
+pub fn probe_all() {
+    let mut deferred: [Option<(FdtNode<'static>, &'static Driver)>;
+        MAX_DEFERRED] = [None; MAX_DEFERRED];
+
+    for stage in Stage::ALL {
+        walk(&fdt::get().root(), &mut |node| {
+            if is_bound(node) || !is_enabled(node) {
+                return;
+            }
+
+            let Some(driver) = driver_of(node) else {
+                return;
+            };
+
+            if driver.stage != stage || !probe(node, driver) {
+                return;
+            }
+
+            match deferred.iter_mut().find(|slot| slot.is_none()) {
+                Some(slot) => *slot = Some((*node, driver)),
+                None => error!(
+                    "{}: too many deferred probes, dropping {}",
+                    driver.name,
+                    node.name()
+                ),
+            }
+        });
+
+        let mut progress = true;
+        while progress {
+            progress = false;
+
+            for slot in deferred.iter_mut() {
+                if let Some((node, driver)) = slot
+                    && !probe(node, driver)
+                {
+                    *slot = None;
+                    progress = true;
+                }
+            }
+        }
+    }
+
+    for (node, driver) in deferred.iter().flatten() {
+        warn!("{}: probe of {} never completed", driver.name, node.name());
+    }
+}
+
+/// See: [`BOUND`].
+struct BoundCell(UnsafeCell<Bound>);
+unsafe impl Sync for BoundCell {}
@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+
+use crate::arch::sbi;
+use crate::arch::trap::{self, IRQ_S_SOFT};
+use crate::cpu::MAX_CPUS;
+use crate::drivers::{Driver, Error, Stage, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::irq;
+use crate::mmio::Mmio;
+
+/// Devicetree compatible strings of the SiFive CLINT.
+const CLINT_COMPATIBLE: [&str; 2] = ["sifive,clint0", "riscv,clint0"];
+
+/// Devicetree compatible strings of ACLINT devices.
+const MSWI_COMPATIBLE: &str = "riscv,aclint-mswi";
+const MTIMER_COMPATIBLE: &str = "riscv,aclint-mtimer";
+const SSWI_COMPATIBLE: &str = "riscv,aclint-sswi";
+
+/// Hart-local interrupts referenced by `interrupts-extended` of the devices.
+const INTC_S_SOFT: u32 = 1;
+const INTC_M_SOFT: u32 = 3;
+const INTC_M_TIMER: u32 = 7;
+
+/// Offset of the MTIMECMP registers within a SiFive CLINT.
+const CLINT_MTIMECMP: usize = 0x4000;
+
+/// The S-mode software interrupt device, if the platform has one.
+///
+/// Written once by [`probe`], before secondary harts are started.
+static SSWI: SswiCell = SswiCell(UnsafeCell::new(None));
+
+/// Software interrupt device, that is an ACLINT MSWI or SSWI, or the MSIP
+/// registers of a CLINT.
+///
+/// Each hart context has a single 32-bit register, with the lowest bit
+/// reflecting the pending software interrupt of the hart.
+#[derive(Clone, Copy)]
+pub struct Swi {
+    regs: Mmio,
+    /// Contexts of harts, indexed by hart identifier.
+    ctx: [Option<usize>; MAX_CPUS],
+}
+
+/// Timer device, that is an ACLINT MTIMER or the MTIMECMP registers of a
+/// CLINT.
+///
+/// Each hart context has a single 64-bit compare register.
+#[derive(Clone, Copy)]
+pub struct Mtimer {
+    regs: Mmio,
+    /// Contexts of harts, indexed by hart identifier.
+    ctx: [Option<usize>; MAX_CPUS],
+}
+
+/// Machine-level software and timer interrupts, SiFive CLINT or ACLINT.
+#[derive(Clone, Copy)]
+pub struct Clint {
+    pub mswi: Swi,
+    pub mtimer: Mtimer,
+}
+
+/// Map harts to contexts of a device, for a given hart-local interrupt.
+///
+/// Contexts are numbered by order of `interrupts-extended` entries naming the
+/// interrupt.
+fn contexts(node: &FdtNode, irq: u32) -> [Option<usize>; MAX_CPUS] {
+    let mut ctx = [None; MAX_CPUS];
+
+    crate::arch::hart_irqs(node)
+        .filter(|entry| entry.is_some_and(|entry| entry.irq == irq))
+        .enumerate()
+        .filter_map(|(idx, entry)| Some((idx, entry?.hartid as usize)))
+        .filter(|(_, hartid)| *hartid < MAX_CPUS)
+        .for_each(|(idx, hartid)| ctx[hartid] = Some(idx));
+
+    ctx
+}
+
+impl Swi {
+    fn new(base: usize, node: &FdtNode, irq: u32) -> Self {
+        Swi {
+            regs: Mmio::new(base),
+            ctx: contexts(node, irq),
+        }
+    }
+
+    fn context(&self, hartid: u64) -> Option<usize> {
+        *self.ctx.get(hartid as usize)?
+    }
+
+    /// Check whether the device can interrupt a given hart.
+    pub fn has_hart(&self, hartid: u64) -> bool {
+        self.context(hartid).is_some()
+    }
+
+    /// Raise or clear the software interrupt of a given hart.
+    ///
+    /// Returns [`None`] if the device is not connected to the hart. SSWI
+    /// devices only ever raise the interrupt, it is cleared through `sip`.
+    pub fn set_pending(&self, hartid: u64, pending: bool) -> Option<()> {
+        let ctx = self.context(hartid)?;
+
+        self.regs.write32(ctx * 4, pending as u32);
+        Some(())
+    }
+}
+
+impl Mtimer {
+    /// Make the timer of a given hart fire once `mtime` reaches `ticks`.
+    ///
+    /// Returns [`None`] if the device is not connected to the hart.
+    pub fn set_compare(&self, hartid: u64, ticks: u64) -> Option<()> {
+        let ctx = (*self.ctx.get(hartid as usize)?)?;
+
+        self.regs.write64(ctx * 8, ticks);
+        Some(())
+    }
+}
+
+impl Clint {
+    /// Locate a CLINT, or a pair of ACLINT MSWI and MTIMER devices.
+    ///
+    /// These are only accessible from M-mode.
+    pub fn probe() -> Option<Self> {
+        let fdt = fdt::get();
+        let root = fdt.root();
+
+        if let Some(node) = CLINT_COMPATIBLE
+            .iter()
+            .find_map(|compat| root.node_by_compatible(compat))
+        {
+            let base = fdt.reg(&node, 0)?.start as usize;
+
+            return Some(Clint {
+                mswi: Swi::new(base, &node, INTC_M_SOFT),
+                mtimer: Mtimer {
+                    regs: Mmio::new(base + CLINT_MTIMECMP),
+                    ctx: contexts(&node, INTC_M_TIMER),
+                },
+            });
+        }
+
+        let mswi = root.node_by_compatible(MSWI_COMPATIBLE)?;
+        let mtimer = root.node_by_compatible(MTIMER_COMPATIBLE)?;
+
+        Some(Clint {
+            mswi: Swi::new(
+                fdt.reg(&mswi, 0)?.start as usize,
+                &mswi,
+                INTC_M_SOFT,
+            ),
+            mtimer: Mtimer {
+                regs: Mmio::new(fdt.reg(&mtimer, 0)?.start as usize),
+                ctx: contexts(&mtimer, INTC_M_TIMER),
+            },
+        })
+    }
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "aclint-sswi",
+        compatible: &[SSWI_COMPATIBLE],
+        stage: Stage::Irqchip,
+        probe,
+    }
+);
+
+/// Set up the ACLINT SSWI device for sending software interrupts.
+///
+/// Platforms without one fall back to SBI instead.
+fn probe(node: &FdtNode<'static>) -> Result<(), Error> {
+    if sswi().is_some() {
+        return Err(Error::Busy);
+    }
+
+    let reg = fdt::get().reg(node, 0).ok_or(Error::Invalid)?;
+
+    unsafe {
+        *SSWI.0.get() = Some(Swi::new(reg.start as usize, node, INTC_S_SOFT));
+    }
+
+    Ok(())
+}
+
+fn sswi() -> Option<&'static Swi> {
+    unsafe { (*SSWI.0.get()).as_ref() }
+}
+
+/// Raise the S-mode software interrupt of a given hart.
+///
+/// This goes through the SSWI device if it is connected to the hart, and
+/// through SBI otherwise.
+pub fn send_ipi(hartid: u64) -> Result<(), sbi::Error> {
+    if sswi()
+        .and_then(|sswi| sswi.set_pending(hartid, true))
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    sbi::send_ipi(1, hartid as usize)
+}
+
+/// Register a handler of the S-mode software interrupt and unmask it.
+///
+/// The interrupt is local to each hart, so it is only unmasked on the calling
+/// one. The pending bit is cleared before the handler is called.
+pub fn request_ipi(handler: irq::Handler) -> Result<u32, irq::Error> {
+    irq::register(IRQ_S_SOFT, handler)?;
+    trap::enable_local(IRQ_S_SOFT);
+
+    Ok(IRQ_S_SOFT)
+}
+
+/// See: [`SSWI`].
+struct SswiCell(UnsafeCell<Option<Swi>>);
+unsafe impl Sync for SswiCell {}
@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::drivers::{self, Error};
+use crate::fdt::{self, FdtNode, FdtStreamable, Phandle};
+
+/// Devicetree compatible string of fixed-rate clocks.
+const FIXED_CLOCK: &str = "fixed-clock";
+
+/// Devicetree compatible string of clocks derived from a parent by a constant
+/// factor.
+const FIXED_FACTOR_CLOCK: &str = "fixed-factor-clock";
+
+/// Resolve the `idx`-th entry of the `clocks` property of a node.
+///
+/// Returns the provider node along with the specifier cells, whose number is
+/// given by `#clock-cells` of the provider.
+fn provider(
+    node: &FdtNode,
+    idx: usize,
+) -> Option<(FdtNode<'static>, impl Iterator<Item = u32>)> {
+    let root = fdt::get().root();
+    let mut cells = node.prop_cells("clocks")?;
+
+    for nth in 0.. {
+        let phandle = cells.next()?;
+        let provider =
+            root.node_by_phandle(Phandle::new(phandle.to_be_bytes()))?;
+        let count = provider.prop_u32("#clock-cells").unwrap_or(0) as usize;
+
+        if nth == idx {
+            return Some((provider, cells.take(count)));
+        }
+
+        for _ in 0..count {
+            cells.next()?;
+        }
+    }
+
+    None
+}
+
+/// Obtain rate of the `idx`-th clock of a node, in Hz.
+///
+/// Rates of fixed-rate clocks and fixed factors of those are known from the
+/// devicetree alone. Other clocks need their provider to be bound to a driver,
+/// the probe of the consumer is deferred until then, unless no driver handles
+/// the provider at all.
+pub fn rate(node: &FdtNode, idx: usize) -> Result<u64, Error> {
+    let (provider, _) = provider(node, idx).ok_or(Error::NoDevice)?;
+
+    if provider.is_compatible(FIXED_CLOCK) {
+        return provider
+            .prop_u32("clock-frequency")
+            .map(u64::from)
+            .ok_or(Error::Invalid);
+    }
+
+    if provider.is_compatible(FIXED_FACTOR_CLOCK) {
+        let mult = provider.prop_u32("clock-mult").ok_or(Error::Invalid)?;
+        let div = provider.prop_u32("clock-div").ok_or(Error::Invalid)?;
+
+        return rate(&provider, 0)?
+            .checked_mul(mult as u64)
+            .and_then(|rate| rate.checked_div(div as u64))
+            .ok_or(Error::Invalid);
+    }
+
+    if drivers::is_enabled(&provider)
+        && !drivers::is_bound(&provider)
+        && drivers::driver_of(&provider).is_some()
+    {
+        return Err(Error::Defer);
+    }
+
+    Err(Error::NoDevice)
+}
+
+/// Obtain rate of the `idx`-th clock of a node, if it has one.
+///
+/// Unlike [`rate`], a missing or unsupported clock is not an error.
+pub fn optional_rate(node: &FdtNode, idx: usize) -> Result<Option<u64>, Error> {
+    match rate(node, idx) {
+        Ok(rate) => Ok(Some(rate)),
+        Err(Error::NoDevice) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
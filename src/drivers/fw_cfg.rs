@@ -0,0 +1,484 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ptr;
+
+use crate::arch;
+use crate::drivers::{self, Driver, Stage, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible string of the memory-mapped fw_cfg interface.
+const COMPATIBLE: &str = "qemu,fw-cfg-mmio";
+
+/// fw_cfg registers.
+const FW_CFG_DATA: usize = 0x00;
+const FW_CFG_SELECTOR: usize = 0x08;
+const FW_CFG_DMA_HI: usize = 0x10;
+const FW_CFG_DMA_LO: usize = 0x14;
+
+/// Items with fixed selector keys.
+const KEY_SIGNATURE: u16 = 0x00;
+const KEY_ID: u16 = 0x01;
+const KEY_KERNEL_SIZE: u16 = 0x08;
+const KEY_INITRD_SIZE: u16 = 0x0B;
+const KEY_KERNEL_DATA: u16 = 0x11;
+const KEY_INITRD_DATA: u16 = 0x12;
+const KEY_CMDLINE_SIZE: u16 = 0x14;
+const KEY_CMDLINE_DATA: u16 = 0x15;
+const KEY_FILE_DIR: u16 = 0x19;
+
+/// Contents of the signature item.
+const SIGNATURE: &[u8; 4] = b"QEMU";
+
+/// Feature bits of the ID item.
+const ID_DMA: u32 = 1 << 1;
+
+/// Control bits of a DMA access.
+const DMA_CTL_ERROR: u32 = 1 << 0;
+const DMA_CTL_READ: u32 = 1 << 1;
+const DMA_CTL_SKIP: u32 = 1 << 2;
+const DMA_CTL_SELECT: u32 = 1 << 3;
+const DMA_CTL_SELECT_SHIFT: u32 = 16;
+
+/// Size of the count opening the file directory.
+const DIR_HEADER_SIZE: usize = 4;
+
+/// Size of a file directory entry: size, selector key, reserved and name.
+const DIR_ENTRY_SIZE: usize = 64;
+
+/// Maximum length of a file name, including the terminating NUL.
+const MAX_NAME: usize = 56;
+
+/// The fw_cfg device discovered from the devicetree.
+///
+/// Written once by [`probe`], before secondary cores are started. Items are
+/// selected by writing a shared register, so the device is only ever to be
+/// used by a single core at a time.
+static FW_CFG: FwCfgCell = FwCfgCell(UnsafeCell::new(None));
+
+/// Errors reported by fw_cfg reads.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// No such item or file exists, or it is empty.
+    NotFound,
+    /// The item does not fit in the memory it is to be loaded into.
+    TooLarge,
+    /// The device failed a DMA transfer.
+    Dma,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "not found"),
+            Error::TooLarge => write!(f, "item too large"),
+            Error::Dma => write!(f, "DMA transfer failed"),
+        }
+    }
+}
+
+/// Items passed with `-kernel`, `-initrd` and `-append` on the QEMU command
+/// line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Blob {
+    Kernel,
+    Initrd,
+    Cmdline,
+}
+
+impl Blob {
+    /// Obtain selector keys of the size and the data of the item.
+    fn keys(self) -> (u16, u16) {
+        match self {
+            Blob::Kernel => (KEY_KERNEL_SIZE, KEY_KERNEL_DATA),
+            Blob::Initrd => (KEY_INITRD_SIZE, KEY_INITRD_DATA),
+            Blob::Cmdline => (KEY_CMDLINE_SIZE, KEY_CMDLINE_DATA),
+        }
+    }
+}
+
+/// A DMA access descriptor, read by the device.
+///
+/// All fields are big endian.
+#[repr(C, align(8))]
+struct DmaAccess {
+    control: u32,
+    length: u32,
+    address: u64,
+}
+
+/// A named file of the fw_cfg directory.
+#[derive(Clone, Copy)]
+pub struct File {
+    /// Size of the file in bytes.
+    pub size: u32,
+    /// Selector key of the file.
+    pub key: u16,
+    name: [u8; MAX_NAME],
+}
+
+impl File {
+    /// Obtain name of the file, such as `etc/ramfb` or `opt/lunar/cfg`.
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|b| *b == 0).unwrap_or(MAX_NAME);
+
+        core::str::from_utf8(&self.name[..len]).unwrap_or("")
+    }
+}
+
+/// QEMU firmware configuration device.
+pub struct FwCfg {
+    regs: Mmio,
+    /// Whether the DMA interface is available.
+    dma: bool,
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "fw_cfg",
+        compatible: &[COMPATIBLE],
+        stage: Stage::Device,
+        probe,
+    }
+);
+
+fn probe(node: &FdtNode<'static>) -> Result<(), drivers::Error> {
+    if get().is_some() {
+        return Err(drivers::Error::Busy);
+    }
+
+    let fw_cfg = FwCfg::probe(node)?;
+
+    unsafe {
+        *FW_CFG.0.get() = Some(fw_cfg);
+    }
+
+    Ok(())
+}
+
+/// Obtain the fw_cfg device, if one is bound.
+pub fn get() -> Option<&'static FwCfg> {
+    unsafe { (*FW_CFG.0.get()).as_ref() }
+}
+
+impl FwCfg {
+    /// Construct a driver of the fw_cfg device described by a devicetree node.
+    ///
+    /// Fails with [`drivers::Error::Io`] if the device does not identify
+    /// itself as fw_cfg.
+    pub fn probe(node: &FdtNode) -> Result<Self, drivers::Error> {
+        if !node.is_compatible(COMPATIBLE) {
+            return Err(drivers::Error::NoDevice);
+        }
+
+        let reg = fdt::get().reg(node, 0).ok_or(drivers::Error::Invalid)?;
+        let mut fw_cfg = FwCfg {
+            regs: Mmio::new(reg.start as usize),
+            dma: false,
+        };
+
+        let mut signature = [0u8; 4];
+        fw_cfg.select(KEY_SIGNATURE);
+        fw_cfg.read_data(&mut signature);
+
+        if &signature != SIGNATURE {
+            return Err(drivers::Error::Io);
+        }
+
+        fw_cfg.dma = fw_cfg.read_u32(KEY_ID) & ID_DMA != 0;
+
+        Ok(fw_cfg)
+    }
+
+    fn select(&self, key: u16) {
+        self.regs.write16(FW_CFG_SELECTOR, key.to_be());
+    }
+
+    /// Read the selected item from the current position, through the data
+    /// register.
+    fn read_data(&self, buf: &mut [u8]) {
+        let mut chunks = buf.chunks_exact_mut(8);
+
+        // Wide reads return bytes in the order of the item.
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.regs.read64(FW_CFG_DATA).to_ne_bytes());
+        }
+
+        for byte in chunks.into_remainder() {
+            *byte = self.regs.read8(FW_CFG_DATA);
+        }
+    }
+
+    /// Perform a DMA access, waiting for its completion.
+    ///
+    /// # Arguments
+    ///
+    /// - `control`: Control bits of the access, including the selector key.
+    /// - `address`: Address of memory to transfer to, if any.
+    /// - `length`: Number of bytes to transfer or skip.
+    fn dma(
+        &self,
+        control: u32,
+        address: usize,
+        length: usize,
+    ) -> Result<(), Error> {
+        let length = u32::try_from(length).map_err(|_| Error::TooLarge)?;
+        let mut access = DmaAccess {
+            control: control.to_be(),
+            length: length.to_be(),
+            address: (address as u64).to_be(),
+        };
+        let access_ptr = &raw mut access;
+        let addr = access_ptr as u64;
+
+        arch::io_barrier();
+
+        // Writing the low half starts the transfer.
+        self.regs
+            .write32(FW_CFG_DMA_HI, ((addr >> 32) as u32).to_be());
+        self.regs.write32(FW_CFG_DMA_LO, (addr as u32).to_be());
+
+        // The device clears the control field once done, except for the error
+        // bit.
+        let control = loop {
+            let control = u32::from_be(unsafe {
+                ptr::read_volatile(&raw const (*access_ptr).control)
+            });
+
+            if control & !DMA_CTL_ERROR == 0 {
+                break control;
+            }
+
+            core::hint::spin_loop();
+        };
+
+        arch::io_barrier();
+
+        if control & DMA_CTL_ERROR != 0 {
+            return Err(Error::Dma);
+        }
+
+        Ok(())
+    }
+
+    /// Read part of an item.
+    ///
+    /// # Arguments
+    ///
+    /// - `key`: Selector key of the item.
+    /// - `offset`: Offset within the item to start reading at.
+    /// - `buf`: Memory to read into. Reads past the end of the item fill it
+    ///   with zeroes.
+    pub fn read_at(
+        &self,
+        key: u16,
+        offset: usize,
+        buf: &mut [u8],
+    ) -> Result<(), Error> {
+        if !self.dma {
+            let mut skip = [0u8; 64];
+
+            self.select(key);
+
+            for len in (0..offset).step_by(skip.len()) {
+                let len = (offset - len).min(skip.len());
+                self.read_data(&mut skip[..len]);
+            }
+
+            self.read_data(buf);
+
+            return Ok(());
+        }
+
+        let select = DMA_CTL_SELECT | (key as u32) << DMA_CTL_SELECT_SHIFT;
+
+        if offset != 0 {
+            self.dma(select | DMA_CTL_SKIP, 0, offset)?;
+            self.dma(DMA_CTL_READ, buf.as_mut_ptr() as usize, buf.len())
+        } else {
+            self.dma(
+                select | DMA_CTL_READ,
+                buf.as_mut_ptr() as usize,
+                buf.len(),
+            )
+        }
+    }
+
+    /// Read an item holding a little endian 32-bit value.
+    fn read_u32(&self, key: u16) -> u32 {
+        let mut bytes = [0u8; 4];
+
+        self.select(key);
+        self.read_data(&mut bytes);
+
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Obtain an iterator over the file directory.
+    pub fn files(&self) -> Files<'_> {
+        let mut count = [0u8; DIR_HEADER_SIZE];
+
+        self.select(KEY_FILE_DIR);
+        self.read_data(&mut count);
+
+        Files {
+            fw_cfg: self,
+            idx: 0,
+            count: u32::from_be_bytes(count) as usize,
+        }
+    }
+
+    /// Find a file of the directory by name.
+    pub fn file(&self, name: &str) -> Option<File> {
+        self.files().find(|file| file.name() == name)
+    }
+
+    /// Load a whole file into memory.
+    ///
+    /// Returns the part of the destination the file was loaded into.
+    ///
+    /// # Arguments
+    ///
+    /// - `name`: Name of the file, see [`File::name`].
+    /// - `dst`: Memory to load the file into, which must be large enough.
+    pub fn load<'a>(
+        &self,
+        name: &str,
+        dst: &'a mut [u8],
+    ) -> Result<&'a mut [u8], Error> {
+        let file = self.file(name).ok_or(Error::NotFound)?;
+
+        self.load_item(file.key, file.size as usize, dst)
+    }
+
+    /// Obtain size of an item passed on the QEMU command line.
+    ///
+    /// Zero if the item was not passed.
+    pub fn blob_size(&self, blob: Blob) -> usize {
+        self.read_u32(blob.keys().0) as usize
+    }
+
+    /// Load an item passed on the QEMU command line into memory.
+    ///
+    /// Returns the part of the destination the item was loaded into. The
+    /// command line includes its terminating NUL.
+    ///
+    /// # Arguments
+    ///
+    /// - `blob`: Item to load.
+    /// - `dst`: Memory to load the item into, which must be large enough.
+    pub fn load_blob<'a>(
+        &self,
+        blob: Blob,
+        dst: &'a mut [u8],
+    ) -> Result<&'a mut [u8], Error> {
+        match self.blob_size(blob) {
+            0 => Err(Error::NotFound),
+            size => self.load_item(blob.keys().1, size, dst),
+        }
+    }
+
+    fn load_item<'a>(
+        &self,
+        key: u16,
+        size: usize,
+        dst: &'a mut [u8],
+    ) -> Result<&'a mut [u8], Error> {
+        let dst = dst.get_mut(..size).ok_or(Error::TooLarge)?;
+
+        self.read_at(key, 0, dst)?;
+
+        Ok(dst)
+    }
+}
+
+/// An iterator over files of the fw_cfg directory.
+///
+/// Entries are read one by one as the iteration goes, so other items can be
+/// read in between.
+pub struct Files<'a> {
+    fw_cfg: &'a FwCfg,
+    idx: usize,
+    count: usize,
+}
+
+impl Iterator for Files<'_> {
+    type Item = File;
+
+    fn next(&mut self) -> Option<File> {
+        if self.idx >= self.count {
+            return None;
+        }
+
+        let mut entry = [0u8; DIR_ENTRY_SIZE];
+        let offset = DIR_HEADER_SIZE + self.idx * DIR_ENTRY_SIZE;
+
+        self.fw_cfg.read_at(KEY_FILE_DIR, offset, &mut entry).ok()?;
+        self.idx += 1;
+
+        let mut name = [0u8; MAX_NAME];
+        name.copy_from_slice(&entry[DIR_ENTRY_SIZE - MAX_NAME..]);
+
+        Some(File {
+            size: u32::from_be_bytes(entry[0..4].try_into().unwrap()),
+            key: u16::from_be_bytes(entry[4..6].try_into().unwrap()),
+            name,
+        })
+    }
+}
+
+/// See: [`FW_CFG`].
+struct FwCfgCell(UnsafeCell<Option<FwCfg>>);
+unsafe impl Sync for FwCfgCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file QEMU always puts in the directory.
+    const KNOWN_FILE: &str = "etc/boot-fail-wait";
+
+    #[test_case]
+    fn probe_finds_dma() {
+        let fw_cfg = get().expect("no fw_cfg device");
+        let mut signature = [0u8; 4];
+
+        fw_cfg.read_at(KEY_SIGNATURE, 0, &mut signature).unwrap();
+
+        assert_eq!(&signature, SIGNATURE);
+        assert!(fw_cfg.dma);
+    }
+
+    #[test_case]
+    fn files_finds_known_file() {
+        let file = get().unwrap().file(KNOWN_FILE).expect("no known file");
+
+        assert_eq!(file.name(), KNOWN_FILE);
+        assert_eq!(file.size, 4);
+        assert!(get().unwrap().file("etc/no-such-file").is_none());
+    }
+
+    #[test_case]
+    fn read_at_dma_matches_data_register() {
+        let fw_cfg = get().unwrap();
+        let pio = FwCfg {
+            regs: fw_cfg.regs,
+            dma: false,
+        };
+        let mut dma = [0u8; DIR_ENTRY_SIZE];
+        let mut data = [0u8; DIR_ENTRY_SIZE];
+
+        fw_cfg
+            .read_at(KEY_FILE_DIR, DIR_HEADER_SIZE, &mut dma)
+            .unwrap();
+        pio.read_at(KEY_FILE_DIR, DIR_HEADER_SIZE, &mut data)
+            .unwrap();
+
+        assert_eq!(dma, data);
+        assert!(dma.iter().any(|byte| *byte != 0));
+    }
+}
@@ -0,0 +1,641 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::arch::asm;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::cpu::{self, MAX_CPUS};
+use crate::drivers::{Driver, Error, Stage, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable, FdtView};
+use crate::irq::{self, Controller, Trigger};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible string of GICv3 implementations.
+const GICV3_COMPATIBLE: &str = "arm,gic-v3";
+
+/// Devicetree compatible strings of supported GIC implementations.
+const COMPATIBLE: [&str; 4] = [
+    "arm,cortex-a15-gic",
+    "arm,gic-400",
+    "arm,cortex-a9-gic",
+    GICV3_COMPATIBLE,
+];
+
+/// First interrupt number of Private Peripheral Interrupts.
+const PPI_BASE: u32 = 16;
+
+/// First interrupt number of Shared Peripheral Interrupts.
+const SPI_BASE: u32 = 32;
+
+/// Interrupt numbers starting from this one are special or reserved.
+const SPECIAL_BASE: u32 = 1020;
+
+/// Priority assigned to all interrupts upon initialization.
+const DEFAULT_PRIORITY: u8 = 0xA0;
+
+/// Distributor registers, common to GICv2 and GICv3.
+const GICD_CTLR: usize = 0x0000;
+const GICD_TYPER: usize = 0x0004;
+const GICD_IGROUPR: usize = 0x0080;
+const GICD_ISENABLER: usize = 0x0100;
+const GICD_ICENABLER: usize = 0x0180;
+const GICD_ICPENDR: usize = 0x0280;
+const GICD_ICACTIVER: usize = 0x0380;
+const GICD_IPRIORITYR: usize = 0x0400;
+const GICD_ITARGETSR: usize = 0x0800;
+const GICD_ICFGR: usize = 0x0C00;
+#[cfg(feature = "el3-monitor")]
+const GICD_IGRPMODR: usize = 0x0D00;
+const GICD_IROUTER: usize = 0x6000;
+
+/// GICD_CTLR bits enabling both interrupt groups in GICv2.
+const GICD_CTLR_V2_ENABLE: u32 = 0x3;
+
+/// GICD_CTLR bits enabling affinity routing and Group 1 interrupts in GICv3.
+const GICD_CTLR_V3_ENABLE: u32 = 0x12;
+
+/// GICD_CTLR bit enabling Group 1 interrupts in the Secure view of GICv2.
+#[cfg(feature = "el3-monitor")]
+const GICD_CTLR_V2_SECURE_GRP1: u32 = 0x2;
+
+/// GICD_CTLR bits enabling affinity routing for both security states in the
+/// Secure view of GICv3.
+#[cfg(feature = "el3-monitor")]
+const GICD_CTLR_V3_SECURE_ARE: u32 = 0x30;
+
+/// GICD_CTLR bit enabling non-secure Group 1 interrupts in the Secure view of
+/// GICv3.
+#[cfg(feature = "el3-monitor")]
+const GICD_CTLR_V3_SECURE_GRP1NS: u32 = 0x2;
+
+/// GICD_CTLR bit set while a register write is in progress in GICv3.
+const GICD_CTLR_RWP: u32 = 1 << 31;
+
+/// CPU interface registers of GICv2.
+const GICC_CTLR: usize = 0x0000;
+const GICC_PMR: usize = 0x0004;
+const GICC_BPR: usize = 0x0008;
+const GICC_IAR: usize = 0x000C;
+const GICC_EOIR: usize = 0x0010;
+
+/// Redistributor registers of GICv3, relative to the RD_base frame.
+const GICR_CTLR: usize = 0x0000;
+const GICR_TYPER: usize = 0x0008;
+const GICR_WAKER: usize = 0x0014;
+
+/// Offset of the SGI_base frame from the RD_base frame of a redistributor.
+const GICR_SGI_BASE: usize = 0x1_0000;
+
+/// Default stride between redistributors, with and without virtual LPIs.
+const GICR_STRIDE: usize = 0x2_0000;
+const GICR_STRIDE_VLPIS: usize = 0x4_0000;
+
+/// GICR_CTLR bit set while a register write is in progress.
+const GICR_CTLR_RWP: u32 = 1 << 3;
+
+/// GICR_TYPER bits.
+const GICR_TYPER_VLPIS: u64 = 1 << 1;
+const GICR_TYPER_LAST: u64 = 1 << 4;
+
+/// GICR_WAKER bits.
+const GICR_WAKER_PROCESSOR_SLEEP: u32 = 1 << 1;
+const GICR_WAKER_CHILDREN_ASLEEP: u32 = 1 << 2;
+
+/// The interrupt controller discovered from the devicetree.
+///
+/// Written once by [`probe`], before interrupts are unmasked.
+static GIC: GicCell = GicCell(UnsafeCell::new(None));
+
+/// GICv2 CPU interface masks of cores, indexed by logical core index.
+static TARGETS: [AtomicU8; MAX_CPUS] = [const { AtomicU8::new(0) }; MAX_CPUS];
+
+/// Architecture version specific parts of the controller.
+enum Version {
+    V2 {
+        gicc: Mmio,
+    },
+    V3 {
+        redists: Mmio,
+        size: usize,
+        stride: Option<usize>,
+    },
+}
+
+/// ARM Generic Interrupt Controller.
+pub struct Gic {
+    gicd: Mmio,
+    lines: u32,
+    cells: usize,
+    version: Version,
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "gic",
+        compatible: &COMPATIBLE,
+        stage: Stage::Irqchip,
+        probe,
+    }
+);
+
+/// Set up the interrupt controller and make it the root.
+///
+/// Both the distributor and the interface of the calling core are set up, with
+/// all interrupts masked and assigned to Group 1.
+fn probe(node: &FdtNode<'static>) -> Result<(), Error> {
+    if irq::controller().is_some() {
+        return Err(Error::Busy);
+    }
+
+    let v3 = node.is_compatible(GICV3_COMPATIBLE);
+    let gic = Gic::probe(fdt::get(), node, v3).ok_or(Error::Invalid)?;
+
+    let gic = unsafe {
+        *GIC.0.get() = Some(gic);
+        (*GIC.0.get()).as_ref().unwrap()
+    };
+
+    gic.dist_init();
+    gic.cpu_init();
+    irq::set_controller(gic, node);
+
+    Ok(())
+}
+
+/// Assign all interrupts to the non-secure Group 1.
+///
+/// Group assignment is only writable from the Secure state, so this is done at
+/// EL3 by the monitor, before lunar and the payload take the controller over
+/// from the non-secure side. The interface of the calling core is always set
+/// up, the distributor only if asked to.
+///
+/// # Arguments
+///
+/// - `fdt`: Devicetree describing the controller.
+/// - `dist`: Whether to set up the distributor as well.
+#[cfg(feature = "el3-monitor")]
+pub fn secure_init(fdt: &FdtView<'static>, dist: bool) {
+    let Some((node, v3)) = COMPATIBLE.iter().find_map(|compat| {
+        fdt.node_by_compatible(compat)
+            .map(|node| (node, *compat == GICV3_COMPATIBLE))
+    }) else {
+        return;
+    };
+
+    let Some(gic) = Gic::probe(fdt, &node, v3) else {
+        return;
+    };
+
+    if dist {
+        gic.secure_dist_init();
+    }
+
+    gic.secure_cpu_init();
+}
+
+impl Gic {
+    fn probe(
+        fdt: &FdtView<'static>,
+        node: &FdtNode<'static>,
+        v3: bool,
+    ) -> Option<Self> {
+        let gicd = Mmio::new(fdt.reg(node, 0)?.start as usize);
+        let typer = gicd.read32(GICD_TYPER);
+        let lines = (((typer & 0x1F) + 1) * 32).min(SPECIAL_BASE);
+
+        // GICv3 with PPI partitions adds a fourth cell, the first three keep
+        // their meaning.
+        let cells = node
+            .prop_u32("#interrupt-cells")
+            .map(|cells| cells as usize)
+            .filter(|&cells| cells >= 3)?;
+
+        let version = if v3 {
+            let redists = fdt.reg(node, 1)?;
+
+            Version::V3 {
+                redists: Mmio::new(redists.start as usize),
+                size: (redists.end - redists.start) as usize,
+                stride: node
+                    .prop_u64("redistributor-stride")
+                    .map(|stride| stride as usize),
+            }
+        } else {
+            Version::V2 {
+                gicc: Mmio::new(fdt.reg(node, 1)?.start as usize),
+            }
+        };
+
+        Some(Gic {
+            gicd,
+            lines,
+            cells,
+            version,
+        })
+    }
+
+    /// Wait for a register write to the distributor to take effect.
+    fn dist_wait(&self) {
+        if let Version::V3 { .. } = self.version {
+            while self.gicd.read32(GICD_CTLR) & GICD_CTLR_RWP != 0 {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Wait for a write to registers of a given interrupt to take effect.
+    ///
+    /// On GICv3, private interrupts are tracked by the redistributor of the
+    /// calling core rather than by the distributor.
+    fn config_wait(&self, irq: u32) {
+        if irq >= SPI_BASE {
+            return self.dist_wait();
+        }
+
+        match self.redist() {
+            Some(rd) => {
+                while rd.read32(GICR_CTLR) & GICR_CTLR_RWP != 0 {
+                    core::hint::spin_loop();
+                }
+            }
+            None => self.dist_wait(),
+        }
+    }
+
+    /// Set up the distributor, with all shared interrupts masked.
+    fn dist_init(&self) {
+        self.gicd.write32(GICD_CTLR, 0);
+        self.dist_wait();
+
+        for irq in (SPI_BASE..self.lines).step_by(32) {
+            let off = (irq / 32) as usize * 4;
+
+            self.gicd.write32(GICD_ICENABLER + off, u32::MAX);
+            self.gicd.write32(GICD_ICPENDR + off, u32::MAX);
+            self.gicd.write32(GICD_ICACTIVER + off, u32::MAX);
+            self.gicd.write32(GICD_IGROUPR + off, u32::MAX);
+        }
+
+        for irq in SPI_BASE..self.lines {
+            self.gicd
+                .write8(GICD_IPRIORITYR + irq as usize, DEFAULT_PRIORITY);
+        }
+
+        self.dist_wait();
+
+        match self.version {
+            Version::V2 { .. } => {
+                self.gicd.write32(GICD_CTLR, GICD_CTLR_V2_ENABLE);
+            }
+            Version::V3 { .. } => {
+                self.gicd.write32(GICD_CTLR, GICD_CTLR_V3_ENABLE);
+                self.dist_wait();
+            }
+        }
+    }
+
+    /// Assign shared interrupts to the non-secure Group 1 and enable it, from
+    /// the Secure state.
+    ///
+    /// On GICv3, affinity routing is enabled for both security states first.
+    #[cfg(feature = "el3-monitor")]
+    fn secure_dist_init(&self) {
+        self.gicd.write32(GICD_CTLR, 0);
+        self.dist_wait();
+
+        if let Version::V3 { .. } = self.version {
+            self.gicd.write32(GICD_CTLR, GICD_CTLR_V3_SECURE_ARE);
+            self.dist_wait();
+        }
+
+        for irq in (SPI_BASE..self.lines).step_by(32) {
+            let off = (irq / 32) as usize * 4;
+
+            self.gicd.write32(GICD_IGROUPR + off, u32::MAX);
+            if let Version::V3 { .. } = self.version {
+                self.gicd.write32(GICD_IGRPMODR + off, 0);
+            }
+        }
+
+        match self.version {
+            Version::V2 { .. } => {
+                self.gicd.write32(GICD_CTLR, GICD_CTLR_V2_SECURE_GRP1);
+            }
+            Version::V3 { .. } => {
+                self.gicd.write32(
+                    GICD_CTLR,
+                    GICD_CTLR_V3_SECURE_ARE | GICD_CTLR_V3_SECURE_GRP1NS,
+                );
+                self.dist_wait();
+            }
+        }
+    }
+
+    /// Assign interrupts private to the calling core to the non-secure Group
+    /// 1, from the Secure state.
+    #[cfg(feature = "el3-monitor")]
+    fn secure_cpu_init(&self) {
+        match self.version {
+            // Banked IGROUPR0 holds private interrupts of the calling core.
+            Version::V2 { .. } => self.gicd.write32(GICD_IGROUPR, u32::MAX),
+            Version::V3 { .. } => {
+                if let Some(rd) = self.redist() {
+                    Self::wake(rd);
+
+                    let sgi = rd.offset(GICR_SGI_BASE);
+                    sgi.write32(GICD_IGROUPR, u32::MAX);
+                    sgi.write32(GICD_IGRPMODR, 0);
+                }
+            }
+        }
+    }
+
+    /// Wake up a redistributor, so that it forwards interrupts to its core.
+    fn wake(rd: Mmio) {
+        let waker = rd.read32(GICR_WAKER);
+        rd.write32(GICR_WAKER, waker & !GICR_WAKER_PROCESSOR_SLEEP);
+
+        while rd.read32(GICR_WAKER) & GICR_WAKER_CHILDREN_ASLEEP != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Locate the redistributor of the calling core.
+    fn redist(&self) -> Option<Mmio> {
+        let Version::V3 {
+            redists,
+            size,
+            stride,
+        } = self.version
+        else {
+            return None;
+        };
+
+        let hwid = cpu::current_id();
+        let aff = ((hwid >> 8) & 0xFF00_0000) | (hwid & 0xFF_FFFF);
+
+        let mut off = 0;
+        while off < size {
+            let rd = redists.offset(off);
+            let typer = rd.read64(GICR_TYPER);
+
+            if typer >> 32 == aff {
+                return Some(rd);
+            }
+
+            if typer & GICR_TYPER_LAST != 0 {
+                break;
+            }
+
+            off += stride.unwrap_or(if typer & GICR_TYPER_VLPIS != 0 {
+                GICR_STRIDE_VLPIS
+            } else {
+                GICR_STRIDE
+            });
+        }
+
+        None
+    }
+
+    /// Obtain registers holding configuration of a given interrupt.
+    ///
+    /// On GICv3, configuration of private interrupts lives in redistributors.
+    fn config_regs(&self, irq: u32) -> Mmio {
+        if irq >= SPI_BASE {
+            return self.gicd;
+        }
+
+        self.redist()
+            .map(|rd| rd.offset(GICR_SGI_BASE))
+            .unwrap_or(self.gicd)
+    }
+
+    fn set_bit(&self, base: usize, irq: u32) {
+        let off = base + (irq / 32) as usize * 4;
+
+        self.config_regs(irq).write32(off, 1 << (irq % 32));
+    }
+
+    fn ack(&self) -> u32 {
+        match self.version {
+            Version::V2 { gicc } => gicc.read32(GICC_IAR),
+            Version::V3 { .. } => {
+                let iar: u64;
+                unsafe {
+                    asm!("mrs {}, icc_iar1_el1", out(reg) iar, options(nostack));
+                }
+                iar as u32
+            }
+        }
+    }
+
+    fn eoi(&self, iar: u32) {
+        match self.version {
+            Version::V2 { gicc } => gicc.write32(GICC_EOIR, iar),
+            Version::V3 { .. } => unsafe {
+                asm!(
+                    "msr icc_eoir1_el1, {}",
+                    in(reg) iar as u64,
+                    options(nostack),
+                );
+            },
+        }
+    }
+
+    /// Mask, deactivate and clear all interrupts private to the calling core.
+    fn private_reset(&self) {
+        let regs = self.config_regs(0);
+
+        regs.write32(GICD_ICENABLER, u32::MAX);
+        regs.write32(GICD_ICPENDR, u32::MAX);
+        regs.write32(GICD_ICACTIVER, u32::MAX);
+        regs.write32(GICD_IGROUPR, u32::MAX);
+        self.config_wait(0);
+    }
+}
+
+impl Controller for Gic {
+    fn interrupt_cells(&self) -> usize {
+        self.cells
+    }
+
+    /// Decode a specifier in the format of the GIC devicetree bindings.
+    ///
+    /// The first cell selects between SPIs (0) and PPIs (1), the second is the
+    /// interrupt number within its type and the low bits of the third are the
+    /// trigger type.
+    fn xlate(&self, spec: &[u32]) -> Option<(u32, Trigger)> {
+        let irq = match spec.first()? {
+            0 => SPI_BASE + spec.get(1)?,
+            1 => PPI_BASE + spec.get(1)?,
+            _ => return None,
+        };
+
+        let trigger = match spec.get(2)? & 0xF {
+            1 => Trigger::EdgeRising,
+            2 => Trigger::EdgeFalling,
+            4 => Trigger::LevelHigh,
+            8 => Trigger::LevelLow,
+            _ => return None,
+        };
+
+        Some((irq, trigger))
+    }
+
+    fn dispatch(&self) {
+        loop {
+            let iar = self.ack();
+            let irq = iar & 0x00FF_FFFF;
+
+            if irq >= SPECIAL_BASE {
+                break;
+            }
+
+            if !irq::handle(irq) {
+                self.disable(irq);
+            }
+
+            self.eoi(iar);
+        }
+    }
+
+    fn enable(&self, irq: u32) {
+        self.set_bit(GICD_ISENABLER, irq);
+    }
+
+    fn disable(&self, irq: u32) {
+        self.set_bit(GICD_ICENABLER, irq);
+        self.config_wait(irq);
+    }
+
+    fn set_priority(&self, irq: u32, priority: u8) {
+        self.config_regs(irq)
+            .write8(GICD_IPRIORITYR + irq as usize, priority);
+    }
+
+    /// Configure trigger type of an interrupt.
+    ///
+    /// The GIC only distinguishes edge and level triggered interrupts, so the
+    /// polarity is ignored.
+    fn set_trigger(&self, irq: u32, trigger: Trigger) {
+        // SGIs are always edge-triggered.
+        if irq < PPI_BASE {
+            return;
+        }
+
+        let regs = self.config_regs(irq);
+        let off = GICD_ICFGR + (irq / 16) as usize * 4;
+        let shift = (irq % 16) * 2 + 1;
+
+        let cfg = match trigger {
+            Trigger::EdgeRising | Trigger::EdgeFalling => {
+                regs.read32(off) | (1 << shift)
+            }
+            Trigger::LevelHigh | Trigger::LevelLow => {
+                regs.read32(off) & !(1 << shift)
+            }
+        };
+
+        regs.write32(off, cfg);
+    }
+
+    fn route(&self, irq: u32, cpu: usize) {
+        if irq < SPI_BASE {
+            return;
+        }
+
+        match self.version {
+            Version::V2 { .. } => {
+                let Some(target) = TARGETS.get(cpu) else {
+                    return;
+                };
+
+                self.gicd.write8(
+                    GICD_ITARGETSR + irq as usize,
+                    target.load(Ordering::Relaxed),
+                );
+            }
+            Version::V3 { .. } => {
+                let Some(cpu) = cpu::get(cpu) else {
+                    return;
+                };
+
+                self.gicd.write64(GICD_IROUTER + irq as usize * 8, cpu.hwid);
+            }
+        }
+    }
+
+    fn cpu_init(&self) {
+        match self.version {
+            Version::V2 { gicc } => {
+                // Banked ITARGETSR0 reads as the mask of the calling core.
+                let mask = self.gicd.read8(GICD_ITARGETSR);
+                if let Some(target) =
+                    cpu::current().and_then(|i| TARGETS.get(i))
+                {
+                    target.store(mask, Ordering::Relaxed);
+                }
+
+                self.private_reset();
+                for irq in 0..SPI_BASE {
+                    self.set_priority(irq, DEFAULT_PRIORITY);
+                }
+
+                gicc.write32(GICC_PMR, 0xFF);
+                gicc.write32(GICC_BPR, 0);
+                gicc.write32(GICC_CTLR, 0x3);
+            }
+            Version::V3 { .. } => {
+                if let Some(rd) = self.redist() {
+                    Self::wake(rd);
+                }
+
+                self.private_reset();
+                for irq in 0..SPI_BASE {
+                    self.set_priority(irq, DEFAULT_PRIORITY);
+                }
+
+                unsafe {
+                    asm!(
+                        "mrs {tmp}, icc_sre_el1",
+                        "orr {tmp}, {tmp}, #1",
+                        "msr icc_sre_el1, {tmp}",
+                        "isb",
+                        "msr icc_pmr_el1, {pmr}",
+                        "msr icc_bpr1_el1, xzr",
+                        "msr icc_igrpen1_el1, {one}",
+                        "isb",
+                        tmp = out(reg) _,
+                        pmr = in(reg) 0xFFu64,
+                        one = in(reg) 1u64,
+                        options(nostack),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Leave the controller in a state suitable for Linux.
+    ///
+    /// All interrupts are masked, not pending and inactive, and belong to the
+    /// non-secure Group 1. Distributor and CPU interfaces stay enabled, the
+    /// payload is expected to reconfigure them as it sees fit.
+    fn handoff(&self) {
+        for irq in (SPI_BASE..self.lines).step_by(32) {
+            let off = (irq / 32) as usize * 4;
+
+            self.gicd.write32(GICD_ICENABLER + off, u32::MAX);
+            self.gicd.write32(GICD_ICPENDR + off, u32::MAX);
+            self.gicd.write32(GICD_ICACTIVER + off, u32::MAX);
+        }
+
+        self.dist_wait();
+        self.private_reset();
+    }
+}
+
+/// See: [`GIC`].
+struct GicCell(UnsafeCell<Option<Gic>>);
+unsafe impl Sync for GicCell {}
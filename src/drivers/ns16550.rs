@@ -0,0 +1,259 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::console;
+use crate::drivers::serial::{self, LineConfig, Parity, Serial};
+use crate::drivers::{Driver, Error, Stage, clk, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible strings of supported 8250-family UARTs.
+const COMPATIBLE: [&str; 5] = [
+    "ns16550a",
+    "ns16550",
+    "ns16450",
+    "ns8250",
+    "snps,dw-apb-uart",
+];
+
+/// NS16550 registers, in units of the register stride.
+const UART_RBR: usize = 0;
+const UART_THR: usize = 0;
+const UART_DLL: usize = 0;
+const UART_IER: usize = 1;
+const UART_DLM: usize = 1;
+const UART_FCR: usize = 2;
+const UART_LCR: usize = 3;
+const UART_MCR: usize = 4;
+const UART_LSR: usize = 5;
+
+/// FCR bits enabling and resetting both FIFOs.
+const FCR_ENABLE: u32 = 0x7;
+
+/// LCR bits.
+const LCR_PEN: u32 = 1 << 3;
+const LCR_EPS: u32 = 1 << 4;
+const LCR_DLAB: u32 = 1 << 7;
+
+/// MCR bits.
+const MCR_DTR: u32 = 1 << 0;
+const MCR_RTS: u32 = 1 << 1;
+const MCR_AFE: u32 = 1 << 5;
+
+/// LSR bits.
+const LSR_DR: u32 = 1 << 0;
+const LSR_THRE: u32 = 1 << 5;
+const LSR_TEMT: u32 = 1 << 6;
+
+/// The UART backing the console.
+///
+/// Written by [`bind`], before secondary cores are started.
+static STDOUT: Ns16550Cell = Ns16550Cell(UnsafeCell::new(None));
+
+/// NS16550-compatible UART.
+#[derive(Clone, Copy)]
+pub struct Ns16550 {
+    regs: Mmio,
+    /// Registers are spaced `1 << shift` bytes apart.
+    shift: u32,
+    /// Width of register accesses in bytes.
+    width: u32,
+    /// Frequency of the reference clock in Hz, if known.
+    clock: Option<u64>,
+    /// Baud rate set up by firmware, if any.
+    speed: Option<u32>,
+}
+
+/// Bind a UART to the console.
+///
+/// Line settings default to the `current-speed` of the UART, if it has one,
+/// otherwise the UART is used as left by the firmware.
+///
+/// # Arguments
+///
+/// - `node`: Devicetree node of the UART.
+/// - `config`: Line settings to program, overriding the defaults.
+pub fn bind(node: &FdtNode, config: Option<LineConfig>) -> Result<(), Error> {
+    let uart = Ns16550::probe(node)?;
+
+    let config = config.or(uart.speed.map(|baud| LineConfig {
+        baud,
+        ..LineConfig::default()
+    }));
+
+    if let Some(config) = config {
+        uart.configure(&config);
+    }
+
+    let uart = unsafe {
+        *STDOUT.0.get() = Some(uart);
+        (*STDOUT.0.get()).as_ref().unwrap()
+    };
+
+    console::register("ns16550", uart).map_err(|_| Error::Busy)
+}
+
+impl Ns16550 {
+    /// Construct a driver of the UART described by a devicetree node.
+    ///
+    /// Fails with [`Error::NoDevice`] if the node is not compatible with the
+    /// driver.
+    pub fn probe(node: &FdtNode) -> Result<Self, Error> {
+        let uart = Self::early(node)?;
+
+        let clock = match node.prop_u32("clock-frequency") {
+            Some(freq) => Some(freq as u64),
+            None => clk::optional_rate(node, 0)?,
+        };
+
+        Ok(Ns16550 {
+            clock,
+            speed: node.prop_u32("current-speed"),
+            ..uart
+        })
+    }
+
+    /// Construct a driver of the UART from its register layout alone.
+    ///
+    /// The rate of the reference clock is left unknown, so the UART can only
+    /// be used as configured by firmware. Meant for the early console.
+    pub fn early(node: &FdtNode) -> Result<Self, Error> {
+        if !COMPATIBLE.iter().any(|compat| node.is_compatible(compat)) {
+            return Err(Error::NoDevice);
+        }
+
+        let reg = fdt::get().reg(node, 0).ok_or(Error::Invalid)?;
+        let offset = node.prop_u32("reg-offset").unwrap_or(0) as u64;
+
+        Ok(Ns16550 {
+            regs: Mmio::new((reg.start + offset) as usize),
+            shift: node.prop_u32("reg-shift").unwrap_or(0),
+            width: node.prop_u32("reg-io-width").unwrap_or(1),
+            clock: None,
+            speed: None,
+        })
+    }
+
+    fn read(&self, reg: usize) -> u32 {
+        let off = reg << self.shift;
+
+        match self.width {
+            4 => self.regs.read32(off),
+            2 => self.regs.read16(off) as u32,
+            _ => self.regs.read8(off) as u32,
+        }
+    }
+
+    fn write(&self, reg: usize, val: u32) {
+        let off = reg << self.shift;
+
+        match self.width {
+            4 => self.regs.write32(off, val),
+            2 => self.regs.write16(off, val as u16),
+            _ => self.regs.write8(off, val as u8),
+        }
+    }
+
+    /// Program line settings of the UART.
+    ///
+    /// The baud rate divisor is only touched if the reference clock rate is
+    /// known, so that firmware settings are kept otherwise. Interrupts of the
+    /// UART are disabled, as it is driven by polling.
+    pub fn configure(&self, config: &LineConfig) {
+        self.flush();
+        self.write(UART_IER, 0);
+
+        let mut lcr = (config.data_bits.clamp(5, 8) - 5) as u32;
+
+        lcr |= match config.parity {
+            Parity::None => 0,
+            Parity::Odd => LCR_PEN,
+            Parity::Even => LCR_PEN | LCR_EPS,
+        };
+
+        if let Some(clock) = self.clock {
+            let baud = config.baud as u64;
+            let div = ((clock + baud * 8) / (baud * 16)).clamp(1, 0xFFFF);
+
+            self.write(UART_LCR, LCR_DLAB);
+            self.write(UART_DLL, (div & 0xFF) as u32);
+            self.write(UART_DLM, (div >> 8) as u32);
+        }
+
+        self.write(UART_LCR, lcr);
+        self.write(UART_FCR, FCR_ENABLE);
+
+        let mut mcr = MCR_DTR | MCR_RTS;
+        if config.flow {
+            mcr |= MCR_AFE;
+        }
+
+        self.write(UART_MCR, mcr);
+    }
+}
+
+impl Serial for Ns16550 {
+    fn putc(&self, byte: u8) {
+        while self.read(UART_LSR) & LSR_THRE == 0 {
+            core::hint::spin_loop();
+        }
+
+        self.write(UART_THR, byte as u32);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        if self.read(UART_LSR) & LSR_DR == 0 {
+            return None;
+        }
+
+        Some(self.read(UART_RBR) as u8)
+    }
+
+    fn flush(&self) {
+        while self.read(UART_LSR) & LSR_TEMT == 0 {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl console::Sink for Ns16550 {
+    fn write_str(&self, s: &str) {
+        self.puts(s);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        Serial::getc(self)
+    }
+
+    fn flush(&self) {
+        Serial::flush(self);
+    }
+}
+
+impl fmt::Write for Ns16550 {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.puts(s);
+        Ok(())
+    }
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "ns16550",
+        compatible: &COMPATIBLE,
+        stage: Stage::Device,
+        probe,
+    }
+);
+
+fn probe(node: &FdtNode<'static>) -> Result<(), Error> {
+    serial::probe(node, bind)
+}
+
+/// See: [`STDOUT`].
+struct Ns16550Cell(UnsafeCell<Option<Ns16550>>);
+unsafe impl Sync for Ns16550Cell {}
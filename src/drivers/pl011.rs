@@ -0,0 +1,212 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::console;
+use crate::drivers::serial::{self, LineConfig, Parity, Serial};
+use crate::drivers::{Driver, Error, Stage, clk, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible string of the PL011.
+const COMPATIBLE: &str = "arm,pl011";
+
+/// PL011 registers.
+const UARTDR: usize = 0x00;
+const UARTFR: usize = 0x18;
+const UARTIBRD: usize = 0x24;
+const UARTFBRD: usize = 0x28;
+const UARTLCR_H: usize = 0x2C;
+const UARTCR: usize = 0x30;
+const UARTIMSC: usize = 0x38;
+const UARTICR: usize = 0x44;
+
+/// UARTFR bits.
+const FR_BUSY: u32 = 1 << 3;
+const FR_RXFE: u32 = 1 << 4;
+const FR_TXFF: u32 = 1 << 5;
+
+/// UARTLCR_H bits.
+const LCR_H_PEN: u32 = 1 << 1;
+const LCR_H_EPS: u32 = 1 << 2;
+const LCR_H_FEN: u32 = 1 << 4;
+const LCR_H_WLEN_SHIFT: u32 = 5;
+
+/// UARTCR bits.
+const CR_UARTEN: u32 = 1 << 0;
+const CR_TXE: u32 = 1 << 8;
+const CR_RXE: u32 = 1 << 9;
+const CR_RTSEN: u32 = 1 << 14;
+const CR_CTSEN: u32 = 1 << 15;
+
+/// Value of UARTICR clearing all interrupts.
+const ICR_ALL: u32 = 0x7FF;
+
+/// The PL011 backing the console.
+///
+/// Written by [`bind`], before secondary cores are started.
+static STDOUT: Pl011Cell = Pl011Cell(UnsafeCell::new(None));
+
+/// ARM PrimeCell UART.
+#[derive(Clone, Copy)]
+pub struct Pl011 {
+    regs: Mmio,
+    /// Frequency of the reference clock in Hz, if known.
+    clock: Option<u64>,
+}
+
+/// Bind a PL011 to the console.
+///
+/// # Arguments
+///
+/// - `node`: Devicetree node of the UART.
+/// - `config`: Line settings to program, or [`None`] to keep the current ones.
+pub fn bind(node: &FdtNode, config: Option<LineConfig>) -> Result<(), Error> {
+    let uart = Pl011::probe(node)?;
+
+    if let Some(config) = config {
+        uart.configure(&config);
+    }
+
+    let uart = unsafe {
+        *STDOUT.0.get() = Some(uart);
+        (*STDOUT.0.get()).as_ref().unwrap()
+    };
+
+    console::register("pl011", uart).map_err(|_| Error::Busy)
+}
+
+impl Pl011 {
+    /// Construct a driver of the PL011 described by a devicetree node.
+    ///
+    /// Fails with [`Error::NoDevice`] if the node is not compatible with the
+    /// driver.
+    pub fn probe(node: &FdtNode) -> Result<Self, Error> {
+        Ok(Pl011 {
+            clock: clk::optional_rate(node, 0)?,
+            ..Self::early(node)?
+        })
+    }
+
+    /// Construct a driver of the PL011 from its register range alone.
+    ///
+    /// The rate of the reference clock is left unknown, so the UART can only
+    /// be used as configured by firmware. Meant for the early console.
+    pub fn early(node: &FdtNode) -> Result<Self, Error> {
+        if !node.is_compatible(COMPATIBLE) {
+            return Err(Error::NoDevice);
+        }
+
+        let reg = fdt::get().reg(node, 0).ok_or(Error::Invalid)?;
+
+        Ok(Pl011 {
+            regs: Mmio::new(reg.start as usize),
+            clock: None,
+        })
+    }
+
+    /// Program line settings of the UART.
+    ///
+    /// The baud rate divisors are only touched if the reference clock rate is
+    /// known, so that firmware settings are kept otherwise.
+    pub fn configure(&self, config: &LineConfig) {
+        self.flush();
+        self.regs.write32(UARTCR, 0);
+
+        if let Some(clock) = self.clock {
+            // Divisor in 1/64ths, that is clock / (16 * baud) with 6 fractional
+            // bits, rounded to nearest.
+            let baud = config.baud as u64;
+            let div = (clock * 4 + baud / 2) / baud;
+
+            self.regs.write32(UARTIBRD, (div >> 6) as u32);
+            self.regs.write32(UARTFBRD, (div & 0x3F) as u32);
+        }
+
+        let mut lcr = LCR_H_FEN
+            | ((config.data_bits.clamp(5, 8) - 5) as u32) << LCR_H_WLEN_SHIFT;
+
+        lcr |= match config.parity {
+            Parity::None => 0,
+            Parity::Odd => LCR_H_PEN,
+            Parity::Even => LCR_H_PEN | LCR_H_EPS,
+        };
+
+        // Writing UARTLCR_H latches the divisors as well.
+        self.regs.write32(UARTLCR_H, lcr);
+        self.regs.write32(UARTIMSC, 0);
+        self.regs.write32(UARTICR, ICR_ALL);
+
+        let mut cr = CR_UARTEN | CR_TXE | CR_RXE;
+        if config.flow {
+            cr |= CR_RTSEN | CR_CTSEN;
+        }
+
+        self.regs.write32(UARTCR, cr);
+    }
+}
+
+impl Serial for Pl011 {
+    fn putc(&self, byte: u8) {
+        while self.regs.read32(UARTFR) & FR_TXFF != 0 {
+            core::hint::spin_loop();
+        }
+
+        self.regs.write32(UARTDR, byte as u32);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        if self.regs.read32(UARTFR) & FR_RXFE != 0 {
+            return None;
+        }
+
+        Some(self.regs.read32(UARTDR) as u8)
+    }
+
+    fn flush(&self) {
+        while self.regs.read32(UARTFR) & FR_BUSY != 0 {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl console::Sink for Pl011 {
+    fn write_str(&self, s: &str) {
+        self.puts(s);
+    }
+
+    fn getc(&self) -> Option<u8> {
+        Serial::getc(self)
+    }
+
+    fn flush(&self) {
+        Serial::flush(self);
+    }
+}
+
+impl fmt::Write for Pl011 {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.puts(s);
+        Ok(())
+    }
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "pl011",
+        compatible: &[COMPATIBLE],
+        stage: Stage::Device,
+        probe,
+    }
+);
+
+fn probe(node: &FdtNode<'static>) -> Result<(), Error> {
+    serial::probe(node, bind)
+}
+
+/// See: [`STDOUT`].
+struct Pl011Cell(UnsafeCell<Option<Pl011>>);
+unsafe impl Sync for Pl011Cell {}
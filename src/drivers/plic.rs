@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::arch::trap::{self, IRQ_S_EXT, LOCAL_IRQS};
+use crate::cpu::{self, MAX_CPUS};
+use crate::drivers::{Driver, Error, Stage, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::irq::{self, Controller, MAX_IRQS, Trigger};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible strings of supported PLIC implementations.
+const COMPATIBLE: [&str; 2] = ["sifive,plic-1.0.0", "riscv,plic0"];
+
+/// Maximum number of interrupt sources, source 0 being reserved.
+const MAX_SOURCES: u32 = 1024;
+
+/// Interrupt number of the first PLIC source.
+///
+/// Numbers below are taken by hart-local interrupts, see [`LOCAL_IRQS`].
+const SOURCE_BASE: u32 = LOCAL_IRQS;
+
+/// PLIC registers.
+const PLIC_PRIORITY: usize = 0x00_0000;
+const PLIC_ENABLE: usize = 0x00_2000;
+const PLIC_CONTEXT: usize = 0x20_0000;
+
+/// Strides between per-context register blocks.
+const PLIC_ENABLE_STRIDE: usize = 0x80;
+const PLIC_CONTEXT_STRIDE: usize = 0x1000;
+
+/// Per-context registers, relative to the context block.
+const PLIC_THRESHOLD: usize = 0x0;
+const PLIC_CLAIM: usize = 0x4;
+
+/// Entry of [`ROUTES`] for sources which have not been routed explicitly.
+const CPU_UNROUTED: u8 = u8::MAX;
+
+/// The interrupt controller discovered from the devicetree.
+///
+/// Written once by [`probe`], before interrupts are unmasked.
+static PLIC: PlicCell = PlicCell(UnsafeCell::new(None));
+
+/// Logical indices of cores interrupt sources are routed to.
+///
+/// The PLIC has no routing registers of its own, an interrupt is routed to a
+/// hart by being enabled in its context. Sources which have not been routed
+/// explicitly are delivered to the core enabling them.
+static ROUTES: [AtomicU8; MAX_SOURCES as usize] =
+    [const { AtomicU8::new(CPU_UNROUTED) }; MAX_SOURCES as usize];
+
+/// RISC-V Platform-Level Interrupt Controller.
+pub struct Plic {
+    regs: Mmio,
+    /// Number of interrupt sources, including the reserved source 0.
+    sources: u32,
+    /// Highest priority supported by the implementation.
+    max_priority: u32,
+    /// Number of cells in interrupt specifiers.
+    cells: usize,
+    /// S-mode external interrupt contexts of cores, by logical core index.
+    contexts: [Option<usize>; MAX_CPUS],
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "plic",
+        compatible: &COMPATIBLE,
+        stage: Stage::Irqchip,
+        probe,
+    }
+);
+
+/// Set up the interrupt controller and make it the root.
+///
+/// All sources are masked in all S-mode contexts and get the lowest priority.
+/// The context of the calling core is set up to accept any of them.
+fn probe(node: &FdtNode<'static>) -> Result<(), Error> {
+    if irq::controller().is_some() {
+        return Err(Error::Busy);
+    }
+
+    let plic = Plic::probe(node).ok_or(Error::Invalid)?;
+
+    let plic = unsafe {
+        *PLIC.0.get() = Some(plic);
+        (*PLIC.0.get()).as_ref().unwrap()
+    };
+
+    plic.global_init();
+    plic.cpu_init();
+    irq::set_controller(plic, node);
+
+    Ok(())
+}
+
+impl Plic {
+    fn probe(node: &FdtNode<'static>) -> Option<Self> {
+        let regs = Mmio::new(fdt::get().reg(node, 0)?.start as usize);
+        let ndev = node.prop_u32("riscv,ndev")?;
+        let sources = (ndev + 1)
+            .min(MAX_SOURCES)
+            .min(MAX_IRQS as u32 - SOURCE_BASE);
+
+        // Contexts are numbered by all `interrupts-extended` entries, including
+        // M-mode ones which lunar has no use for.
+        let mut contexts = [None; MAX_CPUS];
+        crate::arch::hart_irqs(node)
+            .enumerate()
+            .filter_map(|(ctx, entry)| Some((ctx, entry?)))
+            .filter(|(_, entry)| entry.irq == IRQ_S_EXT)
+            .filter_map(|(ctx, entry)| {
+                Some((ctx, cpu::index_of(entry.hartid)?))
+            })
+            .for_each(|(ctx, idx)| contexts[idx] = Some(ctx));
+
+        // Priority registers only implement as many bits as needed.
+        regs.write32(PLIC_PRIORITY + 4, u32::MAX);
+        let max_priority = regs.read32(PLIC_PRIORITY + 4).max(1);
+
+        Some(Plic {
+            regs,
+            sources,
+            max_priority,
+            cells: node.prop_u32("#interrupt-cells").unwrap_or(1) as usize,
+            contexts,
+        })
+    }
+
+    /// Obtain the registers of a given context.
+    fn context(&self, ctx: usize) -> Mmio {
+        self.regs.offset(PLIC_CONTEXT + ctx * PLIC_CONTEXT_STRIDE)
+    }
+
+    /// Obtain the S-mode context of the calling core.
+    fn current_context(&self) -> Option<usize> {
+        *self.contexts.get(cpu::current()?)?
+    }
+
+    /// Obtain offset of the enable register of a source in a given context.
+    fn enable_reg(ctx: usize, src: u32) -> usize {
+        PLIC_ENABLE + ctx * PLIC_ENABLE_STRIDE + (src / 32) as usize * 4
+    }
+
+    fn is_enabled(&self, ctx: usize, src: u32) -> bool {
+        self.regs.read32(Self::enable_reg(ctx, src)) & (1 << (src % 32)) != 0
+    }
+
+    fn set_enable(&self, ctx: usize, src: u32, enable: bool) {
+        let off = Self::enable_reg(ctx, src);
+        let bit = 1 << (src % 32);
+
+        let val = if enable {
+            self.regs.read32(off) | bit
+        } else {
+            self.regs.read32(off) & !bit
+        };
+
+        self.regs.write32(off, val);
+    }
+
+    /// Mask all sources in a given context.
+    fn context_reset(&self, ctx: usize) {
+        for src in (0..self.sources).step_by(32) {
+            self.regs.write32(Self::enable_reg(ctx, src), 0);
+        }
+    }
+
+    /// Mask all sources in all S-mode contexts and reset their priorities.
+    fn global_init(&self) {
+        for ctx in self.contexts.iter().flatten() {
+            self.context_reset(*ctx);
+        }
+
+        for src in 1..self.sources {
+            self.regs.write32(PLIC_PRIORITY + src as usize * 4, 1);
+        }
+    }
+
+    /// Obtain the S-mode context a given source is to be enabled in.
+    fn route_context(&self, src: u32) -> Option<usize> {
+        match ROUTES[src as usize].load(Ordering::Relaxed) {
+            CPU_UNROUTED => self.current_context(),
+            idx => *self.contexts.get(idx as usize)?,
+        }
+    }
+
+    /// Translate an interrupt number to a source number.
+    fn source(&self, irq: u32) -> Option<u32> {
+        let src = irq.checked_sub(SOURCE_BASE)?;
+
+        (src != 0 && src < self.sources).then_some(src)
+    }
+}
+
+impl Controller for Plic {
+    fn interrupt_cells(&self) -> usize {
+        self.cells
+    }
+
+    /// Decode a specifier in the format of the PLIC devicetree bindings.
+    ///
+    /// The first cell is the source number. Some implementations add a second
+    /// cell with the trigger type, which otherwise defaults to level-high.
+    fn xlate(&self, spec: &[u32]) -> Option<(u32, Trigger)> {
+        let src = *spec.first()?;
+        if src == 0 || src >= self.sources {
+            return None;
+        }
+
+        let trigger = match spec.get(1).map(|flags| flags & 0xF) {
+            None | Some(4) => Trigger::LevelHigh,
+            Some(1) => Trigger::EdgeRising,
+            Some(2) => Trigger::EdgeFalling,
+            Some(8) => Trigger::LevelLow,
+            _ => return None,
+        };
+
+        Some((SOURCE_BASE + src, trigger))
+    }
+
+    fn dispatch(&self) {
+        let Some(ctx) = self.current_context() else {
+            return;
+        };
+
+        let regs = self.context(ctx);
+
+        loop {
+            let src = regs.read32(PLIC_CLAIM);
+            if src == 0 {
+                break;
+            }
+
+            let irq = SOURCE_BASE + src;
+            if !irq::handle(irq) {
+                self.disable(irq);
+            }
+
+            regs.write32(PLIC_CLAIM, src);
+        }
+    }
+
+    /// Unmask an interrupt.
+    ///
+    /// Hart-local interrupts are unmasked on the calling core only.
+    fn enable(&self, irq: u32) {
+        if irq < SOURCE_BASE {
+            return trap::enable_local(irq);
+        }
+
+        if let Some(src) = self.source(irq)
+            && let Some(ctx) = self.route_context(src)
+        {
+            self.set_enable(ctx, src, true);
+        }
+    }
+
+    fn disable(&self, irq: u32) {
+        if irq < SOURCE_BASE {
+            return trap::disable_local(irq);
+        }
+
+        if let Some(src) = self.source(irq) {
+            for ctx in self.contexts.iter().flatten() {
+                self.set_enable(*ctx, src, false);
+            }
+        }
+    }
+
+    /// Set priority of an interrupt.
+    ///
+    /// PLIC priorities grow with urgency, with 0 masking the source, so the
+    /// range of the generic priority is mapped onto `1..=max_priority`.
+    fn set_priority(&self, irq: u32, priority: u8) {
+        let Some(src) = self.source(irq) else {
+            return;
+        };
+
+        let level =
+            1 + (0xFF - priority as u32) * (self.max_priority - 1) / 0xFF;
+
+        self.regs.write32(PLIC_PRIORITY + src as usize * 4, level);
+    }
+
+    /// Configure trigger type of an interrupt.
+    ///
+    /// The PLIC has no trigger configuration, gateways of sources are fixed by
+    /// the implementation.
+    fn set_trigger(&self, _irq: u32, _trigger: Trigger) {}
+
+    fn route(&self, irq: u32, cpu: usize) {
+        let Some(src) = self.source(irq) else {
+            return;
+        };
+
+        let Some(Some(new)) = self.contexts.get(cpu) else {
+            return;
+        };
+
+        let enabled = self
+            .contexts
+            .iter()
+            .flatten()
+            .any(|ctx| self.is_enabled(*ctx, src));
+
+        ROUTES[src as usize].store(cpu as u8, Ordering::Relaxed);
+
+        if enabled {
+            self.disable(irq);
+            self.set_enable(*new, src, true);
+        }
+    }
+
+    fn cpu_init(&self) {
+        if let Some(ctx) = self.current_context() {
+            self.context_reset(ctx);
+            self.context(ctx).write32(PLIC_THRESHOLD, 0);
+        }
+
+        trap::enable_local(IRQ_S_EXT);
+    }
+
+    /// Leave the controller in a state suitable for Linux.
+    ///
+    /// All sources are masked in all S-mode contexts, and so are hart-local
+    /// interrupts of the calling core. Interrupts claimed but not completed do
+    /// not exist at this point, as handlers run with interrupts masked.
+    fn handoff(&self) {
+        for ctx in self.contexts.iter().flatten() {
+            self.context_reset(*ctx);
+        }
+
+        for irq in [trap::IRQ_S_SOFT, trap::IRQ_S_TIMER, IRQ_S_EXT] {
+            trap::disable_local(irq);
+        }
+    }
+}
+
+/// See: [`PLIC`].
+struct PlicCell(UnsafeCell<Option<Plic>>);
+unsafe impl Sync for PlicCell {}
@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::console::earlycon;
+use crate::drivers::Error;
+use crate::fdt::{self, FdtNode, FdtStreamable};
+
+/// Baud rate assumed when the stdout-path options name none.
+pub const DEFAULT_BAUD: u32 = 115200;
+
+/// A serial port driven by polling.
+pub trait Serial: Sync {
+    /// Transmit a byte, waiting for room in the transmitter.
+    fn putc(&self, byte: u8);
+
+    /// Receive a byte, if one is available.
+    fn getc(&self) -> Option<u8>;
+
+    /// Wait for all transmitted bytes to leave the port.
+    fn flush(&self) {}
+
+    /// Transmit a string, translating line feeds into CR-LF sequences.
+    fn puts(&self, s: &str) {
+        for byte in s.bytes() {
+            if byte == b'\n' {
+                self.putc(b'\r');
+            }
+
+            self.putc(byte);
+        }
+    }
+}
+
+/// Parity setting of a serial line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+}
+
+/// Line settings of a serial port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LineConfig {
+    pub baud: u32,
+    pub parity: Parity,
+    /// Number of data bits, between 5 and 8.
+    pub data_bits: u8,
+    /// Whether RTS/CTS flow control is used.
+    pub flow: bool,
+}
+
+impl Default for LineConfig {
+    fn default() -> Self {
+        LineConfig {
+            baud: DEFAULT_BAUD,
+            parity: Parity::None,
+            data_bits: 8,
+            flow: false,
+        }
+    }
+}
+
+impl LineConfig {
+    /// Parse options of a `stdout-path`, such as `115200n8r`.
+    ///
+    /// The format is `<baud>{<parity>{<bits>{<flow>}}}`, as used by the Linux
+    /// `console=` parameter. Omitted trailing settings take their defaults.
+    pub fn parse(opts: &str) -> Option<Self> {
+        let digits = opts
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(opts.len());
+        let (baud, rest) = opts.split_at(digits);
+        let mut rest = rest.bytes();
+
+        let mut config = LineConfig {
+            baud: baud.parse().ok().filter(|baud| *baud != 0)?,
+            ..LineConfig::default()
+        };
+
+        config.parity = match rest.next() {
+            None | Some(b'n') => Parity::None,
+            Some(b'o') => Parity::Odd,
+            Some(b'e') => Parity::Even,
+            Some(_) => return None,
+        };
+
+        config.data_bits = match rest.next() {
+            None => 8,
+            Some(bits @ b'5'..=b'8') => bits - b'0',
+            Some(_) => return None,
+        };
+
+        config.flow = match rest.next() {
+            None => false,
+            Some(b'r') => true,
+            Some(_) => return None,
+        };
+
+        Some(config)
+    }
+}
+
+/// Resolve `/chosen/stdout-path` to a node and its options, if any.
+///
+/// The path may also start with an alias defined in `/aliases`.
+pub fn stdout() -> Option<(FdtNode<'static>, Option<&'static str>)> {
+    let fdt = fdt::get();
+    let value = fdt.node_by_path("/chosen")?.prop_str("stdout-path")?;
+    let value = value.trim_end_matches('\0');
+
+    let (path, opts) = match value.split_once(':') {
+        Some((path, opts)) => (path, Some(opts)),
+        None => (value, None),
+    };
+
+    let node = if path.starts_with('/') {
+        fdt.node_by_path(path)?
+    } else {
+        let path = fdt.node_by_path("/aliases")?.prop_str(path)?;
+        fdt.node_by_path(path.trim_end_matches('\0'))?
+    };
+
+    Some((node, opts))
+}
+
+/// Probe a serial port on behalf of its driver.
+///
+/// Only the port named by `/chosen/stdout-path` is of use, others are left
+/// alone. Once the port is bound to the console, it takes over from the early
+/// console.
+///
+/// # Arguments
+///
+/// - `node`: Devicetree node of the port.
+/// - `bind`: Bind function of the driver, see [`crate::drivers::pl011::bind`].
+pub fn probe(
+    node: &FdtNode,
+    bind: fn(&FdtNode, Option<LineConfig>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let (stdout, opts) = stdout().ok_or(Error::NoDevice)?;
+
+    if !stdout.is_same(node) {
+        return Err(Error::NoDevice);
+    }
+
+    bind(node, opts.and_then(LineConfig::parse))?;
+    earlycon::exit();
+
+    Ok(())
+}
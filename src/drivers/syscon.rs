@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::mmio::Mmio;
+
+/// A register write triggering a system-wide power transition.
+///
+/// Follows the `syscon-reboot` and `syscon-poweroff` devicetree bindings.
+#[derive(Clone, Copy)]
+pub struct Syscon {
+    reg: Mmio,
+    value: u32,
+    mask: u32,
+}
+
+impl Syscon {
+    /// Read a syscon power transition from a node of a given compatible.
+    ///
+    /// The register map is given by the `regmap` phandle or, when absent, is
+    /// the parent of the node.
+    pub fn from_compatible(compat: &str) -> Option<Self> {
+        let fdt = fdt::get();
+        let node = fdt.root().node_by_compatible(compat)?;
+
+        let regmap: FdtNode = match node.prop_phandle("regmap") {
+            Some(phandle) => fdt.root().node_by_phandle(phandle)?,
+            None => fdt.parent_of(&node)?,
+        };
+
+        let base = fdt.reg(&regmap, 0)?.start;
+        let offset = node.prop_u32("offset")?;
+
+        // Legacy nodes without `value` carry the value in `mask`.
+        let (value, mask) =
+            match (node.prop_u32("value"), node.prop_u32("mask")) {
+                (Some(value), mask) => (value, mask.unwrap_or(u32::MAX)),
+                (None, Some(mask)) => (mask, u32::MAX),
+                (None, None) => return None,
+            };
+
+        Some(Syscon {
+            reg: Mmio::new((base + offset as u64) as usize),
+            value,
+            mask,
+        })
+    }
+
+    /// Trigger the power transition.
+    pub fn write(&self) {
+        let old = match self.mask {
+            u32::MAX => 0,
+            mask => self.reg.read32(0) & !mask,
+        };
+
+        self.reg.write32(0, old | (self.value & self.mask));
+    }
+}
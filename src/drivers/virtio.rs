@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+pub mod blk;
+pub mod mmio;
+pub mod queue;
+
+use core::fmt;
+
+use crate::drivers;
+
+pub use mmio::Transport;
+pub use queue::{Completion, Virtqueue};
+
+/// Device status bits.
+pub const STATUS_ACKNOWLEDGE: u32 = 1 << 0;
+pub const STATUS_DRIVER: u32 = 1 << 1;
+pub const STATUS_DRIVER_OK: u32 = 1 << 2;
+pub const STATUS_FEATURES_OK: u32 = 1 << 3;
+pub const STATUS_NEEDS_RESET: u32 = 1 << 6;
+pub const STATUS_FAILED: u32 = 1 << 7;
+
+/// Feature bits independent of the device type.
+pub const F_VERSION_1: u64 = 1 << 32;
+
+/// Errors reported by virtio transports and virtqueues.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The device rejected the negotiated features.
+    FeaturesRejected,
+    /// The virtqueue does not exist or is already in use.
+    NoQueue,
+    /// The pool of DMA memory is exhausted.
+    OutOfMemory,
+    /// Not enough free descriptors for a request.
+    QueueFull,
+    /// The device did not complete a request in time.
+    Timeout,
+    /// The device completed a request other than the one awaited, reported
+    /// that it needs a reset, or was reset after timing out.
+    Device,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FeaturesRejected => write!(f, "features rejected"),
+            Error::NoQueue => write!(f, "no such virtqueue"),
+            Error::OutOfMemory => write!(f, "out of DMA memory"),
+            Error::QueueFull => write!(f, "virtqueue full"),
+            Error::Timeout => write!(f, "request timed out"),
+            Error::Device => write!(f, "device error"),
+        }
+    }
+}
+
+impl From<Error> for drivers::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NoQueue => drivers::Error::Invalid,
+            Error::OutOfMemory => drivers::Error::Busy,
+            _ => drivers::Error::Io,
+        }
+    }
+}
+
+/// Type of a virtio device.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceId {
+    Net,
+    Block,
+    Console,
+    Entropy,
+    Balloon,
+    Scsi,
+    Gpu,
+    Input,
+    Vsock,
+    Other(u32),
+}
+
+impl DeviceId {
+    /// Construct a device type from its identifier, as read from a transport.
+    pub fn from_raw(id: u32) -> Self {
+        match id {
+            1 => DeviceId::Net,
+            2 => DeviceId::Block,
+            3 => DeviceId::Console,
+            4 => DeviceId::Entropy,
+            5 => DeviceId::Balloon,
+            8 => DeviceId::Scsi,
+            16 => DeviceId::Gpu,
+            18 => DeviceId::Input,
+            19 => DeviceId::Vsock,
+            id => DeviceId::Other(id),
+        }
+    }
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceId::Net => f.pad("net"),
+            DeviceId::Block => f.pad("block"),
+            DeviceId::Console => f.pad("console"),
+            DeviceId::Entropy => f.pad("entropy"),
+            DeviceId::Balloon => f.pad("balloon"),
+            DeviceId::Scsi => f.pad("scsi"),
+            DeviceId::Gpu => f.pad("gpu"),
+            DeviceId::Input => f.pad("input"),
+            DeviceId::Vsock => f.pad("vsock"),
+            DeviceId::Other(id) => write!(f, "type {id}"),
+        }
+    }
+}
+
+/// Descriptor of a driver of a virtio device type.
+pub struct DeviceDriver {
+    /// Name of the driver, for diagnostics.
+    pub name: &'static str,
+    /// Type of devices handled by the driver.
+    pub id: DeviceId,
+    /// Bind the driver to a device, once the transport is set up.
+    pub probe: fn(Transport) -> Result<(), drivers::Error>,
+}
+
+/// Drivers of virtio device types, tried by [`mmio`] for every device found.
+const DEVICE_DRIVERS: &[&DeviceDriver] = &[&blk::DRIVER];
+
+/// Find the driver of a device type.
+pub fn driver_of(id: DeviceId) -> Option<&'static DeviceDriver> {
+    DEVICE_DRIVERS
+        .iter()
+        .copied()
+        .find(|driver| driver.id == id)
+}
@@ -0,0 +1,255 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+
+use crate::block::{self, BlockDevice};
+use crate::drivers;
+use crate::drivers::virtio::{
+    self, Completion, DeviceDriver, DeviceId, Transport, Virtqueue,
+};
+use crate::log::info;
+
+/// Feature bits of block devices.
+const F_SIZE_MAX: u64 = 1 << 1;
+const F_RO: u64 = 1 << 5;
+const F_BLK_SIZE: u64 = 1 << 6;
+const F_FLUSH: u64 = 1 << 9;
+
+/// Features known to the driver.
+const SUPPORTED: u64 = F_SIZE_MAX | F_RO | F_BLK_SIZE | F_FLUSH;
+
+/// Offsets of configuration fields.
+const CONFIG_CAPACITY: usize = 0x00;
+const CONFIG_SIZE_MAX: usize = 0x08;
+const CONFIG_BLK_SIZE: usize = 0x14;
+
+/// Request types.
+const T_IN: u32 = 0;
+const T_OUT: u32 = 1;
+const T_FLUSH: u32 = 4;
+
+/// Request status values.
+const S_OK: u8 = 0;
+const S_UNSUPP: u8 = 2;
+
+/// Size of sectors that requests and capacity are expressed in, regardless of
+/// the block size.
+const SECTOR_SIZE: usize = 512;
+
+/// Maximum number of bytes transferred by a single request.
+///
+/// Lower if the device limits the size of segments.
+const MAX_TRANSFER: usize = 0x10_0000;
+
+/// Maximum number of block devices handled.
+const MAX_DISKS: usize = 8;
+
+/// Names the disks are registered under, in order of probing.
+const NAMES: [&str; MAX_DISKS] =
+    ["vda", "vdb", "vdc", "vdd", "vde", "vdf", "vdg", "vdh"];
+
+/// Block devices bound to the driver.
+///
+/// Only written by [`probe`], before secondary cores are started.
+static DISKS: DisksCell =
+    DisksCell(UnsafeCell::new([const { None }; MAX_DISKS]));
+
+/// Driver of virtio block devices.
+pub static DRIVER: DeviceDriver = DeviceDriver {
+    name: "virtio-blk",
+    id: DeviceId::Block,
+    probe,
+};
+
+/// A virtio block device.
+pub struct VirtioBlk {
+    transport: Transport,
+    /// The request queue, only ever used by one core at a time.
+    queue: UnsafeCell<Virtqueue>,
+    block_size: usize,
+    /// Capacity in sectors of [`SECTOR_SIZE`] bytes.
+    sectors: u64,
+    read_only: bool,
+    flush: bool,
+    /// Maximum number of bytes transferred by a single request, a multiple of
+    /// the block size.
+    max_transfer: usize,
+}
+
+// Requests are only issued from one core at a time.
+unsafe impl Sync for VirtioBlk {}
+
+fn probe(transport: Transport) -> Result<(), drivers::Error> {
+    let disks = unsafe { &mut *DISKS.0.get() };
+    let idx = disks
+        .iter()
+        .position(|slot| slot.is_none())
+        .ok_or(drivers::Error::Busy)?;
+
+    let disk = VirtioBlk::new(transport)?;
+
+    info!(
+        "{}: {} MiB, {} byte blocks{}",
+        NAMES[idx],
+        disk.capacity() >> 20,
+        disk.block_size,
+        if disk.read_only { ", read-only" } else { "" }
+    );
+
+    disks[idx] = Some(disk);
+
+    let disk = disks[idx].as_ref().unwrap();
+    block::register(NAMES[idx], disk).map_err(|_| drivers::Error::Busy)
+}
+
+impl VirtioBlk {
+    /// Set a block device up behind a transport.
+    pub fn new(transport: Transport) -> Result<Self, virtio::Error> {
+        let features = transport.negotiate(SUPPORTED)?;
+        let queue = Virtqueue::new(&transport, 0, Completion::Interrupt)?;
+
+        transport.driver_ok();
+
+        let block_size = match features & F_BLK_SIZE {
+            0 => SECTOR_SIZE,
+            _ => transport.config_u32(CONFIG_BLK_SIZE) as usize,
+        };
+
+        // Odd block sizes are not worth supporting.
+        let block_size =
+            if block_size.is_power_of_two() && block_size >= SECTOR_SIZE {
+                block_size
+            } else {
+                SECTOR_SIZE
+            };
+
+        let size_max = match features & F_SIZE_MAX {
+            0 => MAX_TRANSFER,
+            _ => transport.config_u32(CONFIG_SIZE_MAX) as usize,
+        };
+
+        Ok(VirtioBlk {
+            sectors: transport.config_u64(CONFIG_CAPACITY),
+            read_only: features & F_RO != 0,
+            flush: features & F_FLUSH != 0,
+            max_transfer: (size_max.min(MAX_TRANSFER) / block_size).max(1)
+                * block_size,
+            queue: UnsafeCell::new(queue),
+            block_size,
+            transport,
+        })
+    }
+
+    /// Issue a request and wait for its completion.
+    ///
+    /// # Arguments
+    ///
+    /// - `kind`: Type of the request.
+    /// - `sector`: First sector accessed, in units of [`SECTOR_SIZE`].
+    /// - `out`: Data written to the device.
+    /// - `inp`: Memory data read from the device goes to.
+    fn request(
+        &self,
+        kind: u32,
+        sector: u64,
+        out: Option<&[u8]>,
+        inp: Option<&mut [u8]>,
+    ) -> Result<(), block::Error> {
+        let mut header = [0u8; 16];
+        let mut status = [0xFFu8];
+
+        header[0..4].copy_from_slice(&kind.to_le_bytes());
+        header[8..16].copy_from_slice(&sector.to_le_bytes());
+
+        let queue = unsafe { &mut *self.queue.get() };
+        let result = match (out, inp) {
+            (Some(data), _) => queue.transfer(
+                &self.transport,
+                &[&header, data],
+                &mut [&mut status],
+            ),
+            (None, Some(data)) => queue.transfer(
+                &self.transport,
+                &[&header],
+                &mut [data, &mut status],
+            ),
+            (None, None) => {
+                queue.transfer(&self.transport, &[&header], &mut [&mut status])
+            }
+        };
+
+        result.map_err(|_| block::Error::Io)?;
+
+        match status[0] {
+            S_OK => Ok(()),
+            S_UNSUPP => Err(block::Error::Unsupported),
+            _ => Err(block::Error::Io),
+        }
+    }
+
+    /// Obtain the sector a block starts at.
+    fn sector_of(&self, lba: u64) -> u64 {
+        lba * (self.block_size / SECTOR_SIZE) as u64
+    }
+}
+
+impl BlockDevice for VirtioBlk {
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.sectors / (self.block_size / SECTOR_SIZE) as u64
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn read_blocks(
+        &self,
+        lba: u64,
+        buf: &mut [u8],
+    ) -> Result<(), block::Error> {
+        block::check_access(self, lba, buf.len())?;
+
+        let per_request = (self.max_transfer / self.block_size) as u64;
+
+        for (nth, chunk) in buf.chunks_mut(self.max_transfer).enumerate() {
+            let lba = lba + nth as u64 * per_request;
+            self.request(T_IN, self.sector_of(lba), None, Some(chunk))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_blocks(&self, lba: u64, buf: &[u8]) -> Result<(), block::Error> {
+        if self.read_only {
+            return Err(block::Error::ReadOnly);
+        }
+
+        block::check_access(self, lba, buf.len())?;
+
+        let per_request = (self.max_transfer / self.block_size) as u64;
+
+        for (nth, chunk) in buf.chunks(self.max_transfer).enumerate() {
+            let lba = lba + nth as u64 * per_request;
+            self.request(T_OUT, self.sector_of(lba), Some(chunk), None)?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), block::Error> {
+        if !self.flush {
+            return Ok(());
+        }
+
+        self.request(T_FLUSH, 0, None, None)
+    }
+}
+
+/// See: [`DISKS`].
+struct DisksCell(UnsafeCell<[Option<VirtioBlk>; MAX_DISKS]>);
+unsafe impl Sync for DisksCell {}
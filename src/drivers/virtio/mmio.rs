@@ -0,0 +1,410 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::drivers::virtio::{self, DeviceId, Error};
+use crate::drivers::{self, Driver, Stage, register_driver};
+use crate::fdt::{self, FdtNode, FdtStreamable};
+use crate::irq;
+use crate::log::{debug, info};
+use crate::mmio::Mmio;
+
+/// Devicetree compatible string of virtio-mmio transports.
+const COMPATIBLE: &str = "virtio,mmio";
+
+/// virtio-mmio registers.
+const MAGIC_VALUE: usize = 0x000;
+const VERSION: usize = 0x004;
+const DEVICE_ID: usize = 0x008;
+const DEVICE_FEATURES: usize = 0x010;
+const DEVICE_FEATURES_SEL: usize = 0x014;
+const DRIVER_FEATURES: usize = 0x020;
+const DRIVER_FEATURES_SEL: usize = 0x024;
+const GUEST_PAGE_SIZE: usize = 0x028;
+const QUEUE_SEL: usize = 0x030;
+const QUEUE_NUM_MAX: usize = 0x034;
+const QUEUE_NUM: usize = 0x038;
+const QUEUE_ALIGN: usize = 0x03C;
+const QUEUE_PFN: usize = 0x040;
+const QUEUE_READY: usize = 0x044;
+const QUEUE_NOTIFY: usize = 0x050;
+const INTERRUPT_STATUS: usize = 0x060;
+const INTERRUPT_ACK: usize = 0x064;
+const STATUS: usize = 0x070;
+const QUEUE_DESC_LOW: usize = 0x080;
+const QUEUE_DESC_HIGH: usize = 0x084;
+const QUEUE_DRIVER_LOW: usize = 0x090;
+const QUEUE_DRIVER_HIGH: usize = 0x094;
+const QUEUE_DEVICE_LOW: usize = 0x0A0;
+const QUEUE_DEVICE_HIGH: usize = 0x0A4;
+const CONFIG_GENERATION: usize = 0x0FC;
+const CONFIG: usize = 0x100;
+
+/// Value of the magic register, `virt` in little endian.
+const MAGIC: u32 = 0x7472_6976;
+
+/// Versions of the transport.
+const VERSION_LEGACY: u32 = 1;
+const VERSION_MODERN: u32 = 2;
+
+/// Page size assumed by legacy transports, which queues are aligned to.
+pub const LEGACY_PAGE_SIZE: usize = 0x1000;
+
+/// Maximum number of transports whose interrupts can be handled.
+const MAX_DEVICES: usize = 32;
+
+/// Register blocks of transports which interrupts were requested for.
+///
+/// Interrupts of all of them are acknowledged by [`on_irq`], which does not
+/// know which transport an interrupt line belongs to.
+static IRQ_DEVICES: [AtomicUsize; MAX_DEVICES] =
+    [const { AtomicUsize::new(0) }; MAX_DEVICES];
+
+/// A virtio-mmio transport of a single device.
+pub struct Transport {
+    regs: Mmio,
+    version: u32,
+    id: DeviceId,
+    /// Interrupt of the device, if completions can be signalled.
+    irq: Option<u32>,
+}
+
+register_driver!(
+    DRIVER,
+    Driver {
+        name: "virtio-mmio",
+        compatible: &[COMPATIBLE],
+        stage: Stage::Device,
+        probe,
+    }
+);
+
+/// Identify a device behind a transport and hand it to its driver.
+///
+/// Transports without a device behind them, of which QEMU creates plenty, are
+/// skipped quietly.
+fn probe(node: &FdtNode<'static>) -> Result<(), drivers::Error> {
+    let mut transport = Transport::probe(node)?;
+
+    debug!(
+        "{}: {} device, version {}",
+        node.name(),
+        transport.id,
+        transport.version
+    );
+
+    let Some(driver) = virtio::driver_of(transport.id) else {
+        info!("{}: no driver for {} device", node.name(), transport.id);
+        return Err(drivers::Error::NoDevice);
+    };
+
+    transport.irq = transport.request_irq(node);
+
+    debug!("{}: handing over to {}", node.name(), driver.name);
+
+    (driver.probe)(transport)
+}
+
+/// Acknowledge interrupts of all transports.
+///
+/// Completions are picked up from virtqueues by whoever waits for them, so the
+/// interrupt only has to be cleared.
+fn on_irq(_irq: u32) {
+    for base in &IRQ_DEVICES {
+        let base = base.load(Ordering::Acquire);
+
+        if base != 0 {
+            Mmio::new(base).write32(
+                INTERRUPT_ACK,
+                Mmio::new(base).read32(INTERRUPT_STATUS),
+            );
+        }
+    }
+}
+
+impl Transport {
+    /// Construct a transport described by a devicetree node.
+    ///
+    /// Fails with [`drivers::Error::NoDevice`] if no device is behind the
+    /// transport. The device is reset, with its interrupt left unused.
+    pub fn probe(node: &FdtNode) -> Result<Self, drivers::Error> {
+        if !node.is_compatible(COMPATIBLE) {
+            return Err(drivers::Error::NoDevice);
+        }
+
+        let reg = fdt::get().reg(node, 0).ok_or(drivers::Error::Invalid)?;
+        let regs = Mmio::new(reg.start as usize);
+
+        if regs.read32(MAGIC_VALUE) != MAGIC {
+            return Err(drivers::Error::Io);
+        }
+
+        let version = regs.read32(VERSION);
+        if version != VERSION_LEGACY && version != VERSION_MODERN {
+            return Err(drivers::Error::Io);
+        }
+
+        let id = match regs.read32(DEVICE_ID) {
+            0 => return Err(drivers::Error::NoDevice),
+            id => DeviceId::from_raw(id),
+        };
+
+        let transport = Transport {
+            regs,
+            version,
+            id,
+            irq: None,
+        };
+
+        transport.reset();
+
+        Ok(transport)
+    }
+
+    /// Register the transport for interrupt handling.
+    ///
+    /// Completions are polled for if this fails.
+    fn request_irq(&self, node: &FdtNode) -> Option<u32> {
+        let slot = IRQ_DEVICES.iter().find(|slot| {
+            slot.compare_exchange(
+                0,
+                self.regs.base(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+        })?;
+
+        // Lines may be shared, in which case the handler is already there.
+        match irq::request(node, 0, on_irq) {
+            Ok(irq) => Some(irq),
+            Err(irq::Error::Busy) => None,
+            Err(_) => {
+                slot.store(0, Ordering::Release);
+                None
+            }
+        }
+    }
+
+    /// Obtain type of the device.
+    pub fn device_id(&self) -> DeviceId {
+        self.id
+    }
+
+    /// Check whether the transport is a legacy one, predating virtio 1.0.
+    pub fn is_legacy(&self) -> bool {
+        self.version == VERSION_LEGACY
+    }
+
+    /// Obtain interrupt of the device, if it can signal completions.
+    pub fn irq(&self) -> Option<u32> {
+        self.irq
+    }
+
+    /// Reset the device, stopping all its activity.
+    pub fn reset(&self) {
+        self.regs.write32(STATUS, 0);
+
+        while self.regs.read32(STATUS) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Obtain status of the device.
+    pub fn status(&self) -> u32 {
+        self.regs.read32(STATUS)
+    }
+
+    fn set_status(&self, bits: u32) {
+        self.regs.write32(STATUS, self.status() | bits);
+    }
+
+    /// Mark the device as not usable by the driver.
+    pub fn fail(&self) {
+        self.set_status(virtio::STATUS_FAILED);
+    }
+
+    /// Reset the device and negotiate features with it.
+    ///
+    /// Returns the features accepted, which are those offered by the device
+    /// out of the supported ones. Virtqueues are to be set up next, followed
+    /// by [`Transport::driver_ok`].
+    ///
+    /// # Arguments
+    ///
+    /// - `supported`: Device type specific features known to the driver.
+    pub fn negotiate(&self, supported: u64) -> Result<u64, Error> {
+        self.reset();
+        self.set_status(virtio::STATUS_ACKNOWLEDGE | virtio::STATUS_DRIVER);
+
+        let mut offered = 0;
+        for sel in 0..2 {
+            self.regs.write32(DEVICE_FEATURES_SEL, sel);
+            offered |= (self.regs.read32(DEVICE_FEATURES) as u64) << (sel * 32);
+        }
+
+        // Modern transports cannot be driven in legacy mode.
+        let supported = if self.is_legacy() {
+            supported & !virtio::F_VERSION_1
+        } else {
+            supported | virtio::F_VERSION_1
+        };
+        let features = offered & supported;
+
+        for sel in 0..2 {
+            self.regs.write32(DRIVER_FEATURES_SEL, sel);
+            self.regs
+                .write32(DRIVER_FEATURES, (features >> (sel * 32)) as u32);
+        }
+
+        if self.is_legacy() {
+            self.regs.write32(GUEST_PAGE_SIZE, LEGACY_PAGE_SIZE as u32);
+            return Ok(features);
+        }
+
+        self.set_status(virtio::STATUS_FEATURES_OK);
+
+        if features & virtio::F_VERSION_1 == 0
+            || self.status() & virtio::STATUS_FEATURES_OK == 0
+        {
+            self.fail();
+            return Err(Error::FeaturesRejected);
+        }
+
+        Ok(features)
+    }
+
+    /// Let the device know that the driver is ready to use it.
+    pub fn driver_ok(&self) {
+        self.set_status(virtio::STATUS_DRIVER_OK);
+    }
+
+    /// Obtain maximum size of a virtqueue, zero if it does not exist.
+    pub fn queue_max(&self, queue: u16) -> u16 {
+        self.regs.write32(QUEUE_SEL, queue as u32);
+
+        let ready = if self.is_legacy() {
+            self.regs.read32(QUEUE_PFN)
+        } else {
+            self.regs.read32(QUEUE_READY)
+        };
+
+        if ready != 0 {
+            return 0;
+        }
+
+        self.regs.read32(QUEUE_NUM_MAX).min(u16::MAX as u32) as u16
+    }
+
+    /// Hand a virtqueue over to the device.
+    ///
+    /// Legacy transports require the driver and device areas to follow the
+    /// descriptor table in this order, with the device area aligned to
+    /// [`LEGACY_PAGE_SIZE`].
+    ///
+    /// # Arguments
+    ///
+    /// - `queue`: Index of the virtqueue.
+    /// - `size`: Number of descriptors.
+    /// - `desc`: Address of the descriptor table, page aligned.
+    /// - `driver`: Address of the driver area, also known as the available
+    ///   ring.
+    /// - `device`: Address of the device area, also known as the used ring.
+    pub fn set_queue(
+        &self,
+        queue: u16,
+        size: u16,
+        desc: usize,
+        driver: usize,
+        device: usize,
+    ) {
+        self.regs.write32(QUEUE_SEL, queue as u32);
+        self.regs.write32(QUEUE_NUM, size as u32);
+
+        if self.is_legacy() {
+            self.regs.write32(QUEUE_ALIGN, LEGACY_PAGE_SIZE as u32);
+            self.regs
+                .write32(QUEUE_PFN, (desc / LEGACY_PAGE_SIZE) as u32);
+            return;
+        }
+
+        let write64 = |low: usize, high: usize, addr: usize| {
+            self.regs.write32(low, addr as u32);
+            self.regs.write32(high, ((addr as u64) >> 32) as u32);
+        };
+
+        write64(QUEUE_DESC_LOW, QUEUE_DESC_HIGH, desc);
+        write64(QUEUE_DRIVER_LOW, QUEUE_DRIVER_HIGH, driver);
+        write64(QUEUE_DEVICE_LOW, QUEUE_DEVICE_HIGH, device);
+
+        self.regs.write32(QUEUE_READY, 1);
+    }
+
+    /// Let the device know that a virtqueue has new buffers available.
+    pub fn notify(&self, queue: u16) {
+        self.regs.write32(QUEUE_NOTIFY, queue as u32);
+    }
+
+    /// Acknowledge pending interrupts of the device.
+    ///
+    /// Fails with [`Error::Device`] if the device reports that it needs a
+    /// reset.
+    pub fn ack_interrupt(&self) -> Result<(), Error> {
+        let pending = self.regs.read32(INTERRUPT_STATUS);
+        self.regs.write32(INTERRUPT_ACK, pending);
+
+        if self.status() & virtio::STATUS_NEEDS_RESET != 0 {
+            return Err(Error::Device);
+        }
+
+        Ok(())
+    }
+
+    /// Read device type specific configuration.
+    ///
+    /// Reads are retried until the configuration is seen unchanged throughout.
+    ///
+    /// # Arguments
+    ///
+    /// - `off`: Offset within the configuration space.
+    /// - `buf`: Memory to read into.
+    pub fn read_config(&self, off: usize, buf: &mut [u8]) {
+        loop {
+            let generation = self.config_generation();
+
+            for (idx, byte) in buf.iter_mut().enumerate() {
+                *byte = self.regs.read8(CONFIG + off + idx);
+            }
+
+            if generation == self.config_generation() {
+                break;
+            }
+        }
+    }
+
+    /// Read a device type specific configuration field of 32 bits.
+    pub fn config_u32(&self, off: usize) -> u32 {
+        let mut bytes = [0u8; 4];
+
+        self.read_config(off, &mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Read a device type specific configuration field of 64 bits.
+    pub fn config_u64(&self, off: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+
+        self.read_config(off, &mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Obtain generation of the configuration, which legacy transports lack.
+    fn config_generation(&self) -> u32 {
+        if self.is_legacy() {
+            0
+        } else {
+            self.regs.read32(CONFIG_GENERATION)
+        }
+    }
+}
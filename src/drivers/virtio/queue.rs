@@ -0,0 +1,378 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::ptr;
+use core::time::Duration;
+
+use crate::align;
+use crate::arch;
+use crate::drivers::virtio::mmio::LEGACY_PAGE_SIZE;
+use crate::drivers::virtio::{Error, Transport};
+use crate::mem::dma;
+use crate::time::{self, Deadline};
+
+/// Maximum number of descriptors of a virtqueue.
+///
+/// Requests of lunar are few and short, so larger queues would only waste DMA
+/// memory.
+const MAX_QUEUE_SIZE: u16 = 64;
+
+/// Time a device is given to complete a request.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Descriptor flags.
+const DESC_F_NEXT: u16 = 1 << 0;
+const DESC_F_WRITE: u16 = 1 << 1;
+
+/// Driver area flags.
+const AVAIL_F_NO_INTERRUPT: u16 = 1 << 0;
+
+/// Size of a descriptor: address, length, flags and next.
+const DESC_SIZE: usize = 16;
+
+/// Size of the driver area header and of its entries.
+const AVAIL_HEADER_SIZE: usize = 4;
+const AVAIL_ENTRY_SIZE: usize = 2;
+
+/// Size of the device area header and of its entries.
+const USED_HEADER_SIZE: usize = 4;
+const USED_ENTRY_SIZE: usize = 8;
+
+/// Size of the event index trailing either ring.
+const EVENT_SIZE: usize = 2;
+
+/// Way in which completion of requests is awaited.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Completion {
+    /// Spin until the device returns the buffers.
+    Poll,
+    /// Sleep until the device interrupts.
+    Interrupt,
+}
+
+/// A split virtqueue.
+///
+/// The descriptor table, driver area and device area live in DMA memory laid
+/// out contiguously, as legacy transports expect. Free descriptors are linked
+/// through their `next` fields.
+pub struct Virtqueue {
+    queue: u16,
+    size: u16,
+    desc: usize,
+    avail: usize,
+    used: usize,
+    free_head: u16,
+    num_free: u16,
+    /// Index of the next driver area entry.
+    avail_idx: u16,
+    /// Index of the next device area entry to be consumed.
+    last_used: u16,
+    completion: Completion,
+    /// Whether the device was reset after a timeout, taking the virtqueue
+    /// away from it.
+    dead: bool,
+}
+
+impl Virtqueue {
+    /// Set up a virtqueue and hand it over to the device.
+    ///
+    /// Must be done after features are negotiated and before the device is
+    /// told that the driver is ready.
+    ///
+    /// # Arguments
+    ///
+    /// - `transport`: Transport of the device.
+    /// - `queue`: Index of the virtqueue.
+    /// - `completion`: Way of awaiting completions. Falls back to polling if
+    ///   the device has no interrupt.
+    pub fn new(
+        transport: &Transport,
+        queue: u16,
+        completion: Completion,
+    ) -> Result<Self, Error> {
+        let size = transport.queue_max(queue).min(MAX_QUEUE_SIZE);
+
+        if size == 0 {
+            return Err(Error::NoQueue);
+        }
+
+        // Sizes of split virtqueues are powers of two.
+        let size = 1u16 << (u16::BITS - 1 - size.leading_zeros());
+        let count = size as usize;
+
+        let avail_off = count * DESC_SIZE;
+        let used_off = align::align_up!(
+            avail_off
+                + AVAIL_HEADER_SIZE
+                + count * AVAIL_ENTRY_SIZE
+                + EVENT_SIZE,
+            LEGACY_PAGE_SIZE
+        );
+        let total =
+            used_off + USED_HEADER_SIZE + count * USED_ENTRY_SIZE + EVENT_SIZE;
+
+        let base = dma::alloc(total, LEGACY_PAGE_SIZE)
+            .ok_or(Error::OutOfMemory)?
+            .as_ptr() as usize;
+
+        let completion = match transport.irq() {
+            Some(_) => completion,
+            None => Completion::Poll,
+        };
+
+        let vq = Virtqueue {
+            queue,
+            size,
+            desc: base,
+            avail: base + avail_off,
+            used: base + used_off,
+            free_head: 0,
+            num_free: size,
+            avail_idx: 0,
+            last_used: 0,
+            completion,
+            dead: false,
+        };
+
+        for idx in 0..size {
+            vq.set_desc_next(idx, idx.wrapping_add(1));
+        }
+
+        if completion == Completion::Poll {
+            vq.write16(vq.avail, AVAIL_F_NO_INTERRUPT);
+        }
+
+        transport.set_queue(queue, size, vq.desc, vq.avail, vq.used);
+
+        Ok(vq)
+    }
+
+    /// Obtain index of the virtqueue.
+    pub fn index(&self) -> u16 {
+        self.queue
+    }
+
+    /// Obtain number of descriptors of the virtqueue.
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    fn read16(&self, addr: usize) -> u16 {
+        unsafe { ptr::read_volatile(addr as *const u16) }
+    }
+
+    fn write16(&self, addr: usize, val: u16) {
+        unsafe { ptr::write_volatile(addr as *mut u16, val) }
+    }
+
+    fn read32(&self, addr: usize) -> u32 {
+        unsafe { ptr::read_volatile(addr as *const u32) }
+    }
+
+    fn desc_addr(&self, idx: u16) -> usize {
+        self.desc + idx as usize * DESC_SIZE
+    }
+
+    fn desc_flags(&self, idx: u16) -> u16 {
+        self.read16(self.desc_addr(idx) + 12)
+    }
+
+    fn desc_next(&self, idx: u16) -> u16 {
+        self.read16(self.desc_addr(idx) + 14)
+    }
+
+    fn set_desc_next(&self, idx: u16, next: u16) {
+        self.write16(self.desc_addr(idx) + 14, next);
+    }
+
+    /// Fill a descriptor in, leaving its link to the next one intact.
+    fn set_desc(&self, idx: u16, addr: usize, len: usize, flags: u16) {
+        let desc = self.desc_addr(idx);
+
+        unsafe {
+            ptr::write_volatile(desc as *mut u64, addr as u64);
+            ptr::write_volatile((desc + 8) as *mut u32, len as u32);
+        }
+
+        self.write16(desc + 12, flags);
+    }
+
+    /// Make a chain of buffers available to the device.
+    ///
+    /// Returns the head of the chain, by which the device reports it used.
+    /// The device is not notified.
+    ///
+    /// # Arguments
+    ///
+    /// - `out`: Buffers read by the device, placed first.
+    /// - `inp`: Buffers written by the device, placed after those read.
+    ///
+    /// # Safety
+    ///
+    /// The buffers must stay valid and must not be accessed until the chain is
+    /// returned by [`Virtqueue::pop_used`], or the device is reset.
+    pub unsafe fn add(
+        &mut self,
+        out: &[&[u8]],
+        inp: &mut [&mut [u8]],
+    ) -> Result<u16, Error> {
+        let count = out.len() + inp.len();
+
+        if count == 0 || count > self.num_free as usize {
+            return Err(Error::QueueFull);
+        }
+
+        let bufs = out
+            .iter()
+            .map(|buf| (buf.as_ptr() as usize, buf.len(), 0))
+            .chain(inp.iter_mut().map(|buf| {
+                (buf.as_mut_ptr() as usize, buf.len(), DESC_F_WRITE)
+            }));
+
+        let head = self.free_head;
+        let mut idx = head;
+
+        for (nth, (addr, len, mut flags)) in bufs.enumerate() {
+            if nth + 1 < count {
+                flags |= DESC_F_NEXT;
+            }
+
+            self.set_desc(idx, addr, len, flags);
+            idx = self.desc_next(idx);
+        }
+
+        self.free_head = idx;
+        self.num_free -= count as u16;
+
+        let slot = (self.avail_idx % self.size) as usize;
+        self.write16(
+            self.avail + AVAIL_HEADER_SIZE + slot * AVAIL_ENTRY_SIZE,
+            head,
+        );
+
+        // The entry has to be visible before the index covering it.
+        arch::io_barrier();
+
+        self.avail_idx = self.avail_idx.wrapping_add(1);
+        self.write16(self.avail + 2, self.avail_idx);
+
+        arch::io_barrier();
+
+        Ok(head)
+    }
+
+    /// Take a chain the device is done with, if any.
+    ///
+    /// Returns the head of the chain and the number of bytes the device wrote
+    /// to it. Descriptors of the chain are freed.
+    pub fn pop_used(&mut self) -> Option<(u16, u32)> {
+        arch::io_barrier();
+
+        if self.read16(self.used + 2) == self.last_used {
+            return None;
+        }
+
+        // The entry is only to be read once its index is seen.
+        arch::io_barrier();
+
+        let slot = (self.last_used % self.size) as usize;
+        let entry = self.used + USED_HEADER_SIZE + slot * USED_ENTRY_SIZE;
+        let head = self.read32(entry) as u16;
+        let len = self.read32(entry + 4);
+
+        self.last_used = self.last_used.wrapping_add(1);
+
+        let mut idx = head;
+        loop {
+            self.num_free += 1;
+
+            if self.desc_flags(idx) & DESC_F_NEXT == 0 {
+                break;
+            }
+
+            idx = self.desc_next(idx);
+        }
+
+        self.set_desc_next(idx, self.free_head);
+        self.free_head = head;
+
+        Some((head, len))
+    }
+
+    /// Pass a chain of buffers to the device and wait for it to be used.
+    ///
+    /// Returns the number of bytes the device wrote. If the device does not
+    /// complete the request in time, it is reset, since it could access the
+    /// buffers later on otherwise. Later transfers then fail right away.
+    ///
+    /// # Arguments
+    ///
+    /// - `transport`: Transport of the device owning the virtqueue.
+    /// - `out`: Buffers read by the device.
+    /// - `inp`: Buffers written by the device.
+    pub fn transfer(
+        &mut self,
+        transport: &Transport,
+        out: &[&[u8]],
+        inp: &mut [&mut [u8]],
+    ) -> Result<u32, Error> {
+        if self.dead {
+            return Err(Error::Device);
+        }
+
+        let head = unsafe { self.add(out, inp)? };
+
+        transport.notify(self.queue);
+
+        let Some((used, len)) = self.wait() else {
+            transport.reset();
+            self.dead = true;
+            return Err(Error::Timeout);
+        };
+
+        transport.ack_interrupt()?;
+
+        if used != head {
+            return Err(Error::Device);
+        }
+
+        Ok(len)
+    }
+
+    /// Wait for the device to use a chain, for up to [`TIMEOUT`].
+    ///
+    /// Sleeping relies on an alarm to wake the core up in case the device
+    /// never interrupts. The alarm is shared, so if someone else has it armed
+    /// the queue is polled instead.
+    fn wait(&mut self) -> Option<(u16, u32)> {
+        let deadline = Deadline::after(TIMEOUT);
+        let sleep = self.completion == Completion::Interrupt
+            && !time::alarm_armed()
+            && time::set_alarm(TIMEOUT, wake).is_ok();
+
+        let used = loop {
+            if let Some(used) = self.pop_used() {
+                break Some(used);
+            }
+
+            if deadline.expired() {
+                break None;
+            }
+
+            if sleep {
+                arch::wait_for_interrupt();
+            } else {
+                core::hint::spin_loop();
+            }
+        };
+
+        if sleep {
+            time::cancel_alarm();
+        }
+
+        used
+    }
+}
+
+/// Handler of the alarm set while sleeping, which only has to wake the core.
+fn wake() {}
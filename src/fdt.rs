@@ -1,6 +1,8 @@
 // SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
 // SPDX-License-Identifier: EUPL-1.2
 
+pub mod patch;
+
 use core::cell::UnsafeCell;
 use core::iter::Iterator;
 use core::ops::Range;
@@ -94,6 +96,16 @@ pub trait FdtStreamable<'a> {
         self.node_by_name(target)
     }
 
+    /// Search the subtree for a node compatible with a given string.
+    fn node_by_compatible(&self, target: &str) -> Option<FdtNode<'a>> {
+        self.stream()
+            .find(|node| node.is_compatible(target))
+            .or_else(|| {
+                self.stream()
+                    .find_map(|node| node.node_by_compatible(target))
+            })
+    }
+
     /// Search for a node with a given phandle.
     fn node_by_phandle(&self, id: Phandle) -> Option<FdtNode<'a>> {
         self.stream()
@@ -188,6 +200,28 @@ pub trait FdtStreamable<'a> {
             .flatten()
     }
 
+    /// Search for a given property and return its value as a [`u64`].
+    fn prop_u64(&self, target: &str) -> Option<u64> {
+        self.prop_raw(target)
+            .and_then(|bytes| bytes.get(0..8)?.try_into().ok())
+            .map(u64::from_be_bytes)
+    }
+
+    /// Search for a given property and return its value as a string list.
+    fn prop_strs(&self, target: &str) -> Option<impl Iterator<Item = &'a str>> {
+        self.prop_raw(target).map(|data| {
+            data.split(|byte| *byte == 0)
+                .filter(|bytes| !bytes.is_empty())
+                .flat_map(str::from_utf8)
+        })
+    }
+
+    /// Check whether `compatible` property contains a given string.
+    fn is_compatible(&self, target: &str) -> bool {
+        self.prop_strs("compatible")
+            .is_some_and(|mut compats| compats.any(|compat| compat == target))
+    }
+
     /// Search for a given property and return its value as a phandle.
     fn prop_phandle(&self, target: &str) -> Option<Phandle> {
         self.prop_raw(target)
@@ -278,16 +312,29 @@ impl FdtHeader {
 /// readonly section.
 #[unsafe(link_section = sections::start_text!())]
 pub fn init() {
-    let header: FdtHeader;
+    let Some(view) = embedded() else {
+        panic!("embedded FDT is malformed");
+    };
+
+    unsafe {
+        *SYSTEM_FDT.0.get() = Some(view);
+    }
+}
+
+/// Create a view into the embedded FDT blob, see: [`init`].
+///
+/// Unlike [`get`], this does not depend on any state of lunar, so it is usable
+/// before `.bss` is cleared.
+pub fn embedded() -> Option<FdtView<'static>> {
     let data: &[u8];
 
     unsafe {
         let start = fdt_blob.as_ptr();
-        header = core::ptr::read(start as *const FdtHeader);
+        let header = core::ptr::read(start as *const FdtHeader);
 
         // Validate magic number
         if header.magic.get() != FDT_MAGIC {
-            panic!("FDT magic number mismatch");
+            return None;
         }
 
         // Obtain a slice with the entire FDT
@@ -295,16 +342,7 @@ pub fn init() {
         data = core::slice::from_raw_parts(start, size);
     }
 
-    let view = FdtView {
-        dt_struct: header.dt_struct(data),
-        dt_strings: header.dt_strings(data),
-        mem_rsvmap: header.mem_rsvmap(data),
-        data,
-    };
-
-    unsafe {
-        *SYSTEM_FDT.0.get() = Some(view);
-    }
+    FdtView::new(data)
 }
 
 /// Obtain a reference to a view into embedded FDT blob.
@@ -416,7 +454,7 @@ impl<'a> FdtStream<'a> {
     }
 }
 
-/// Iterator over nodes in a devicetree stream.
+/// Iterator over direct children of a node in a devicetree stream.
 impl<'a> Iterator for FdtStream<'a> {
     type Item = FdtNode<'a>;
 
@@ -439,6 +477,9 @@ impl<'a> Iterator for FdtStream<'a> {
                     let start = self.off;
                     let end = end_lookup.node_end_off()?;
 
+                    // Continue with the next sibling, not with the children.
+                    self.off = end;
+
                     return Some(FdtNode {
                         name,
                         paddr_cells: self.paddr_cells,
@@ -468,6 +509,7 @@ enum FdtToken {
 }
 
 /// A zero-copy handle into a devicetree node.
+#[derive(Clone, Copy)]
 pub struct FdtNode<'a> {
     paddr_cells: u32,
     psize_cells: u32,
@@ -505,9 +547,12 @@ fn ccmb64(cells: &mut impl Iterator<Item = u32>, count: u32) -> Option<u64> {
         return None;
     }
 
+    // Cells exceeding 64 bits are consumed, but discarded.
     for i in 0..count {
+        let cell = cells.next()?;
+
         if count - i <= 2 {
-            ret = (ret << 32) | cells.next()? as u64;
+            ret = (ret << 32) | cell as u64;
         }
     }
 
@@ -515,14 +560,90 @@ fn ccmb64(cells: &mut impl Iterator<Item = u32>, count: u32) -> Option<u64> {
 }
 
 impl<'a> FdtNode<'a> {
+    /// Obtain name of the node, including its unit address.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Obtain a value identifying the node among all nodes of the tree.
+    pub fn id(&self) -> usize {
+        self.body.as_ptr() as usize
+    }
+
+    /// Check whether two handles refer to the same node.
+    pub fn is_same(&self, other: &FdtNode) -> bool {
+        self.id() == other.id()
+    }
+
+    /// Obtain the address from the `reg` property of a node with no size.
+    ///
+    /// Some nodes, such as the ones found under `/cpus`, have their
+    /// `#size-cells` set to zero and use `reg` only as an identifier.
+    pub fn reg_addr_u64(&self) -> Option<u64> {
+        let mut cells = self.prop_cells("reg")?;
+
+        ccmb64(&mut cells, self.parent_address_cells())
+    }
+
     pub fn reg_u64(&self) -> Option<Range<u64>> {
+        self.reg_nth_u64(0)
+    }
+
+    /// Obtain a given entry of the `reg` property, in the parent bus space.
+    pub fn reg_nth_u64(&self, idx: usize) -> Option<Range<u64>> {
         let mut cells = self.prop_cells("reg")?;
+        let addr = self.parent_address_cells();
+        let size = self.parent_size_cells();
 
-        let base = ccmb64(&mut cells, self.parent_address_cells())?;
-        let size = ccmb64(&mut cells, self.parent_size_cells())?;
+        for _ in 0..idx {
+            ccmb64(&mut cells, addr)?;
+            ccmb64(&mut cells, size)?;
+        }
+
+        let base = ccmb64(&mut cells, addr)?;
+        let size = ccmb64(&mut cells, size)?;
 
         Some(base..base + size)
     }
+
+    /// Check whether this node is the root node of the devicetree.
+    pub fn is_root(&self) -> bool {
+        self.name.is_empty()
+    }
+
+    /// Check whether a given node is located within the subtree of this node.
+    fn contains(&self, other: &FdtNode) -> bool {
+        let outer = self.body.as_ptr_range();
+        let inner = other.body.as_ptr_range();
+
+        outer.start <= inner.start && inner.end <= outer.end
+    }
+
+    /// Translate an address of a child bus to an address of the parent bus.
+    ///
+    /// Absence of the `ranges` property means that no translation is possible,
+    /// while an empty one denotes an identity mapping.
+    fn translate_child(&self, addr: u64) -> Option<u64> {
+        let ranges = self.prop_raw("ranges")?;
+        if ranges.is_empty() {
+            return Some(addr);
+        }
+
+        let mut cells = self.prop_cells("ranges")?;
+        let child_cells = self.address_cells();
+        let parent_cells = self.parent_address_cells();
+        let size_cells = self.size_cells();
+
+        loop {
+            let child = ccmb64(&mut cells, child_cells)?;
+            let parent = ccmb64(&mut cells, parent_cells)?;
+            let size = ccmb64(&mut cells, size_cells)?;
+
+            if (child..child + size).contains(&addr) {
+                return Some(addr - child + parent);
+            }
+        }
+    }
 }
 
 /// A view into devicetree contents.
@@ -533,14 +654,94 @@ impl<'a> FdtNode<'a> {
 /// orchestrates reading properties from it.
 pub struct FdtView<'a> {
     data: &'a [u8],
+    root: FdtNode<'a>,
     dt_struct: &'a [u8],
     dt_strings: &'a [u8],
     mem_rsvmap: &'a [FdtReserveEntry],
 }
 
+impl<'a> FdtView<'a> {
+    /// Create a view into an FDT blob.
+    ///
+    /// Returns `None` if the blob does not start with a valid header or has no
+    /// root node.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The FDT blob, 8 byte aligned.
+    pub fn new(data: &'a [u8]) -> Option<FdtView<'a>> {
+        if data.len() < size_of::<FdtHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(data.as_ptr() as *const FdtHeader) };
+
+        if header.magic.get() != FDT_MAGIC
+            || header.totalsize.get() as usize > data.len()
+        {
+            return None;
+        }
+
+        let dt_struct = header.dt_struct(data);
+        let dt_strings = header.dt_strings(data);
+        let root = FdtStream::new(dt_struct, dt_strings, 2, 1).next()?;
+
+        Some(FdtView {
+            root,
+            dt_struct,
+            dt_strings,
+            mem_rsvmap: header.mem_rsvmap(data),
+            data,
+        })
+    }
+
+    /// Obtain the root node of the devicetree.
+    pub fn root(&self) -> FdtNode<'a> {
+        self.root
+    }
+
+    /// Find the parent of a given node.
+    pub fn parent_of(&self, node: &FdtNode<'a>) -> Option<FdtNode<'a>> {
+        let mut parent = self.root;
+
+        loop {
+            let child = parent.stream().find(|child| child.contains(node))?;
+            if child.body.as_ptr() == node.body.as_ptr() {
+                return Some(parent);
+            }
+
+            parent = child;
+        }
+    }
+
+    /// Translate an address from the bus of a node to the CPU address space.
+    ///
+    /// Returns [`None`] if any of the buses on the way to the root node does
+    /// not have a mapping for the address.
+    pub fn translate(&self, node: &FdtNode<'a>, addr: u64) -> Option<u64> {
+        let mut addr = addr;
+        let mut bus = self.parent_of(node)?;
+
+        while !bus.is_root() {
+            addr = bus.translate_child(addr)?;
+            bus = self.parent_of(&bus)?;
+        }
+
+        Some(addr)
+    }
+
+    /// Obtain a given entry of node's `reg` property, in CPU address space.
+    pub fn reg(&self, node: &FdtNode<'a>, idx: usize) -> Option<Range<u64>> {
+        let reg = node.reg_nth_u64(idx)?;
+        let base = self.translate(node, reg.start)?;
+
+        Some(base..base + (reg.end - reg.start))
+    }
+}
+
 impl<'a> FdtStreamable<'a> for FdtView<'a> {
     fn data(&self) -> &'a [u8] {
-        self.dt_struct
+        self.root.body
     }
 
     fn strings(&self) -> &'a [u8] {
@@ -559,3 +760,49 @@ impl<'a> FdtStreamable<'a> for FdtView<'a> {
 /// See: [`SYSTEM_FDT`].
 struct FdtViewCell(UnsafeCell<Option<FdtView<'static>>>);
 unsafe impl Sync for FdtViewCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn root_is_root() {
+        let fdt = get();
+
+        assert!(fdt.root().is_root());
+        assert!(fdt.parent_of(&fdt.root()).is_none());
+    }
+
+    #[test_case]
+    fn node_by_path_finds_nodes() {
+        let fdt = get();
+        let cpus = fdt.node_by_path("/cpus").expect("no /cpus node");
+
+        assert_eq!(cpus.name(), "cpus");
+        assert!(fdt.parent_of(&cpus).unwrap().is_same(&fdt.root()));
+        assert!(fdt.node_by_path("/chosen").is_some());
+        assert!(fdt.node_by_path("/no-such-node").is_none());
+    }
+
+    #[test_case]
+    fn node_by_name_skips_nested_nodes() {
+        let fdt = get();
+
+        assert!(fdt.node_by_name("cpu@0").is_none());
+
+        let cpu = fdt.node_by_path("/cpus/cpu@0").expect("no cpu@0 node");
+        let cpus = fdt.parent_of(&cpu).expect("cpu@0 has no parent");
+
+        assert_eq!(cpu.name(), "cpu@0");
+        assert_eq!(cpus.name(), "cpus");
+    }
+
+    #[test_case]
+    fn root_cells() {
+        let root = get().root();
+
+        assert!(root.prop_u32("#address-cells").is_some());
+        assert!(root.prop_u32("#size-cells").is_some());
+        assert!(root.prop_u32("no-such-prop").is_none());
+    }
+}
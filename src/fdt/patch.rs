@@ -0,0 +1,416 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::fmt;
+use core::ops::Range;
+
+use super::{FDT_MAGIC, FdtToken, FdtView, read_from_tape_u32};
+use crate::align;
+
+/// Size of the FDT header, as of version 17 of the format.
+const HEADER_SIZE: usize = 40;
+
+/// Version of the FDT format emitted by [`Patch::apply`].
+const FDT_VERSION: u32 = 17;
+
+/// Oldest version of the FDT format the emitted blob is compatible with.
+const FDT_LAST_COMP_VERSION: u32 = 16;
+
+/// Errors reported when patching a devicetree.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// Destination buffer is too small to hold the patched devicetree.
+    NoSpace,
+    /// Source devicetree is malformed.
+    Malformed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSpace => write!(f, "devicetree too large"),
+            Error::Malformed => write!(f, "malformed devicetree"),
+        }
+    }
+}
+
+/// Value of a property of a [`Node`].
+pub enum Value<'a> {
+    /// A property with no value, used for boolean flags.
+    Empty,
+    /// A list of big endian 32-bit cells.
+    Cells(&'a [u32]),
+    /// A single NUL-terminated string.
+    Str(&'a str),
+    /// A list of NUL-terminated strings.
+    Strs(&'a [&'a str]),
+}
+
+/// A property of a [`Node`].
+pub struct Prop<'a> {
+    pub name: &'a str,
+    pub value: Value<'a>,
+}
+
+/// A node added to the root of a devicetree.
+pub struct Node<'a> {
+    pub name: &'a str,
+    pub props: &'a [Prop<'a>],
+}
+
+/// A set of changes applied to a devicetree when handing it off to a payload.
+///
+/// Nodes are added as children of the root node, replacing existing nodes of
+/// the same name. Memory reservations are appended to the existing ones.
+pub struct Patch<'a> {
+    pub reserve: &'a [Range<u64>],
+    pub nodes: &'a [Node<'a>],
+}
+
+/// A cursor writing big endian values into a byte buffer.
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    off: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.buf
+            .get_mut(self.off..self.off + bytes.len())
+            .ok_or(Error::NoSpace)?
+            .copy_from_slice(bytes);
+
+        self.off += bytes.len();
+
+        Ok(())
+    }
+
+    fn u32(&mut self, val: u32) -> Result<(), Error> {
+        self.bytes(&val.to_be_bytes())
+    }
+
+    fn u64(&mut self, val: u64) -> Result<(), Error> {
+        self.bytes(&val.to_be_bytes())
+    }
+
+    /// Pad with zeros up to a given alignment.
+    fn align(&mut self, how: usize) -> Result<(), Error> {
+        while self.off != align::align_up!(self.off, how) {
+            self.bytes(&[0])?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a NUL-terminated string, padded to a 4 byte boundary.
+    fn name(&mut self, name: &str) -> Result<(), Error> {
+        self.bytes(name.as_bytes())?;
+        self.bytes(&[0])?;
+        self.align(4)
+    }
+}
+
+impl Value<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Value::Empty => 0,
+            Value::Cells(cells) => cells.len() * 4,
+            Value::Str(s) => s.len() + 1,
+            Value::Strs(strs) => strs.iter().map(|s| s.len() + 1).sum(),
+        }
+    }
+
+    fn write(&self, out: &mut Writer) -> Result<(), Error> {
+        match self {
+            Value::Empty => Ok(()),
+            Value::Cells(cells) => {
+                cells.iter().try_for_each(|cell| out.u32(*cell))
+            }
+            Value::Str(s) => {
+                out.bytes(s.as_bytes())?;
+                out.bytes(&[0])
+            }
+            Value::Strs(strs) => strs.iter().try_for_each(|s| {
+                out.bytes(s.as_bytes())?;
+                out.bytes(&[0])
+            }),
+        }
+    }
+}
+
+/// Find offset of a string in a DT strings block, including string suffixes.
+fn find_string(strings: &[u8], name: &str) -> Option<usize> {
+    let len = name.len();
+
+    (0..strings.len().saturating_sub(len)).find(|&off| {
+        &strings[off..off + len] == name.as_bytes() && strings[off + len] == 0
+    })
+}
+
+impl Patch<'_> {
+    /// Iterate over names of added properties missing from a strings block.
+    fn new_strings<'s>(
+        &'s self,
+        strings: &'s [u8],
+    ) -> impl Iterator<Item = &'s str> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.props.iter())
+            .map(|prop| prop.name)
+            .filter(|name| find_string(strings, name).is_none())
+    }
+
+    /// Emit added nodes into the structure block.
+    ///
+    /// Names of properties not present in the original strings block are
+    /// assigned offsets past its end, in the order of [`Patch::new_strings`].
+    fn write_nodes(
+        &self,
+        out: &mut Writer,
+        strings: &[u8],
+    ) -> Result<(), Error> {
+        let mut extra = strings.len();
+
+        for node in self.nodes {
+            out.u32(FdtToken::BeginNode as u32)?;
+            out.name(node.name)?;
+
+            for prop in node.props {
+                let nameoff =
+                    find_string(strings, prop.name).unwrap_or_else(|| {
+                        extra += prop.name.len() + 1;
+                        extra - prop.name.len() - 1
+                    });
+
+                out.u32(FdtToken::Prop as u32)?;
+                out.u32(prop.value.len() as u32)?;
+                out.u32(nameoff as u32)?;
+                prop.value.write(out)?;
+                out.align(4)?;
+            }
+
+            out.u32(FdtToken::EndNode as u32)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the structure block, skipping replaced nodes and adding new ones.
+    fn write_struct(
+        &self,
+        fdt: &FdtView,
+        out: &mut Writer,
+    ) -> Result<(), Error> {
+        use FdtToken::*;
+
+        let tape = fdt.dt_struct;
+        let mut off = 0;
+        let mut depth = 0usize;
+        let mut skip: Option<usize> = None;
+
+        while let Some(token) = read_from_tape_u32(tape, off) {
+            let start = off;
+            off += 4;
+
+            match token {
+                _ if token == BeginNode as u32 => {
+                    let len = tape[off..]
+                        .iter()
+                        .position(|byte| *byte == 0)
+                        .ok_or(Error::Malformed)?;
+                    let name = &tape[off..off + len];
+                    off = align::align_up!(off + len + 1, 4);
+                    depth += 1;
+
+                    let replaced = self
+                        .nodes
+                        .iter()
+                        .any(|node| node.name.as_bytes() == name);
+
+                    if skip.is_none() && depth == 2 && replaced {
+                        skip = Some(depth);
+                    }
+                }
+                _ if token == EndNode as u32 => {
+                    if skip.is_none() && depth == 1 {
+                        self.write_nodes(out, fdt.dt_strings)?;
+                    }
+
+                    if skip == Some(depth) {
+                        skip = None;
+                        depth -= 1;
+                        continue;
+                    }
+
+                    depth = depth.checked_sub(1).ok_or(Error::Malformed)?;
+                }
+                _ if token == Prop as u32 => {
+                    let len = read_from_tape_u32(tape, off)
+                        .ok_or(Error::Malformed)?;
+                    off = align::align_up!(off + 8 + len as usize, 4);
+                }
+                _ if token == End as u32 => {
+                    out.u32(End as u32)?;
+                    return Ok(());
+                }
+                _ => continue,
+            }
+
+            if skip.is_none() {
+                out.bytes(tape.get(start..off).ok_or(Error::Malformed)?)?;
+            }
+        }
+
+        Err(Error::Malformed)
+    }
+
+    /// Write a patched copy of a devicetree into a buffer.
+    ///
+    /// Returns size of the resulting blob.
+    ///
+    /// # Arguments
+    ///
+    /// - `fdt`: Devicetree to be patched.
+    /// - `dst`: Buffer receiving the patched devicetree, 8 byte aligned.
+    pub fn apply(&self, fdt: &FdtView, dst: &mut [u8]) -> Result<usize, Error> {
+        let boot_cpuid = read_from_tape_u32(fdt.data, 28).unwrap_or(0);
+        let mut out = Writer { buf: dst, off: 0 };
+
+        out.bytes(&[0; HEADER_SIZE])?;
+        out.align(8)?;
+
+        let off_mem_rsvmap = out.off;
+        for entry in fdt.mem_rsvmap {
+            out.u64(entry.address.get())?;
+            out.u64(entry.size.get())?;
+        }
+        for range in self.reserve {
+            out.u64(range.start)?;
+            out.u64(range.end - range.start)?;
+        }
+        out.u64(0)?;
+        out.u64(0)?;
+
+        let off_dt_struct = out.off;
+        self.write_struct(fdt, &mut out)?;
+        let size_dt_struct = out.off - off_dt_struct;
+
+        let off_dt_strings = out.off;
+        out.bytes(fdt.dt_strings)?;
+        for name in self.new_strings(fdt.dt_strings) {
+            out.bytes(name.as_bytes())?;
+            out.bytes(&[0])?;
+        }
+        let size_dt_strings = out.off - off_dt_strings;
+
+        let totalsize = out.off;
+        let header = [
+            FDT_MAGIC,
+            totalsize as u32,
+            off_dt_struct as u32,
+            off_dt_strings as u32,
+            off_mem_rsvmap as u32,
+            FDT_VERSION,
+            FDT_LAST_COMP_VERSION,
+            boot_cpuid,
+            size_dt_strings as u32,
+            size_dt_struct as u32,
+        ];
+
+        out.off = 0;
+        for field in header {
+            out.u32(field)?;
+        }
+
+        Ok(totalsize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::UnsafeCell;
+
+    use super::*;
+    use crate::fdt::{self, FdtStreamable};
+
+    /// Size of the buffer receiving patched devicetrees.
+    const BUF_SIZE: usize = 0x8000;
+
+    /// Buffer receiving patched devicetrees, too large for the stack.
+    static BUF: BufCell = BufCell(UnsafeCell::new(Buf([0; BUF_SIZE])));
+
+    /// See: [`BUF`].
+    #[repr(C, align(8))]
+    struct Buf([u8; BUF_SIZE]);
+
+    /// See: [`BUF`].
+    struct BufCell(UnsafeCell<Buf>);
+    unsafe impl Sync for BufCell {}
+
+    const PSCI: Node = Node {
+        name: "psci",
+        props: &[
+            Prop {
+                name: "compatible",
+                value: Value::Strs(&["arm,psci-1.0", "arm,psci-0.2"]),
+            },
+            Prop {
+                name: "method",
+                value: Value::Str("smc"),
+            },
+            Prop {
+                name: "lunar,test-cells",
+                value: Value::Cells(&[1, 2]),
+            },
+        ],
+    };
+
+    const RESERVE: Range<u64> = 0x4000_0000..0x4001_0000;
+
+    #[test_case]
+    fn apply_adds_nodes_and_reservations() {
+        let src = fdt::get();
+        let buf = unsafe { &mut (*BUF.0.get()).0 };
+        let patch = Patch {
+            reserve: &[RESERVE],
+            nodes: &[PSCI],
+        };
+
+        let size = patch.apply(src, buf).unwrap();
+        let fdt = FdtView::new(&buf[..size]).expect("patched FDT malformed");
+        let psci = fdt.node_by_path("/psci").expect("no /psci node");
+
+        assert_eq!(psci.prop_str("method"), Some("smc\0"));
+        assert!(
+            psci.prop_strs("compatible")
+                .unwrap()
+                .eq(["arm,psci-1.0", "arm,psci-0.2"])
+        );
+        assert!(psci.prop_cells("lunar,test-cells").unwrap().eq([1, 2]));
+
+        // An existing /psci node is replaced, not merged into.
+        assert!(psci.shallow_prop_raw("cpu_on").is_none());
+
+        let last = fdt.mem_rsvmap.last().expect("no memory reservations");
+        assert_eq!(last.address.get(), RESERVE.start);
+        assert_eq!(last.size.get(), RESERVE.end - RESERVE.start);
+        assert_eq!(fdt.mem_rsvmap.len(), src.mem_rsvmap.len() + 1);
+
+        assert!(fdt.node_by_path("/cpus").is_some());
+        assert!(fdt.node_by_path("/chosen").is_some());
+    }
+
+    #[test_case]
+    fn apply_reports_no_space() {
+        let buf = unsafe { &mut (*BUF.0.get()).0 };
+        let patch = Patch {
+            reserve: &[],
+            nodes: &[PSCI],
+        };
+
+        assert_eq!(
+            patch.apply(fdt::get(), &mut buf[..64]),
+            Err(Error::NoSpace)
+        );
+    }
+}
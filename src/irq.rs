@@ -0,0 +1,262 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
+
+use crate::fdt::{self, FdtNode, FdtStreamable, FdtView, Phandle};
+
+/// Number of interrupt lines that handlers can be registered for.
+pub const MAX_IRQS: usize = 1024;
+
+/// A handler of a single interrupt line.
+pub type Handler = fn(irq: u32);
+
+/// The root interrupt controller.
+///
+/// Set once during early initialization, before interrupts are unmasked, so
+/// the [`UnsafeCell`] suffices here.
+static CONTROLLER: ControllerCell = ControllerCell(UnsafeCell::new(None));
+
+/// Phandle of the devicetree node of the root interrupt controller, zero if
+/// the node has none.
+static PHANDLE: AtomicU32 = AtomicU32::new(0);
+
+/// Handlers of interrupt lines, indexed by interrupt number.
+static HANDLERS: [AtomicPtr<()>; MAX_IRQS] =
+    [const { AtomicPtr::new(core::ptr::null_mut()) }; MAX_IRQS];
+
+/// Errors reported by the interrupt handling API.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// Interrupt number exceeds [`MAX_IRQS`].
+    OutOfRange,
+    /// Another handler is already registered for the interrupt.
+    Busy,
+    /// No interrupt controller is available.
+    NoController,
+    /// Interrupt specifier in the devicetree is missing or malformed.
+    BadSpecifier,
+    /// Interrupt is wired to a controller other than the root one.
+    NotRoot,
+}
+
+/// Trigger type of an interrupt line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Trigger {
+    EdgeRising,
+    EdgeFalling,
+    LevelHigh,
+    LevelLow,
+}
+
+/// An interrupt controller which interrupt exceptions are routed through.
+pub trait Controller: Sync {
+    /// Obtain number of cells in interrupt specifiers of the controller.
+    fn interrupt_cells(&self) -> usize;
+
+    /// Translate an interrupt specifier to an interrupt number and a trigger.
+    fn xlate(&self, spec: &[u32]) -> Option<(u32, Trigger)>;
+
+    /// Acknowledge pending interrupts and call [`handle`] for each of them.
+    fn dispatch(&self);
+
+    /// Unmask a given interrupt.
+    fn enable(&self, irq: u32);
+
+    /// Mask a given interrupt.
+    fn disable(&self, irq: u32);
+
+    /// Set priority of a given interrupt, lower values being more urgent.
+    fn set_priority(&self, irq: u32, priority: u8);
+
+    /// Set trigger type of a given interrupt.
+    fn set_trigger(&self, irq: u32, trigger: Trigger);
+
+    /// Route a given interrupt to a core with a given logical index.
+    fn route(&self, irq: u32, cpu: usize);
+
+    /// Initialize state of the controller local to the calling core.
+    fn cpu_init(&self);
+
+    /// Leave the controller in a state expected by the payload.
+    fn handoff(&self);
+}
+
+/// Set the root interrupt controller.
+///
+/// # Arguments
+///
+/// - `controller`: The controller to route interrupts through.
+/// - `node`: Devicetree node describing the controller.
+pub fn set_controller(controller: &'static dyn Controller, node: &FdtNode) {
+    PHANDLE.store(node.prop_u32("phandle").unwrap_or(0), Ordering::Relaxed);
+
+    unsafe {
+        *CONTROLLER.0.get() = Some(controller);
+    }
+}
+
+/// Obtain the root interrupt controller.
+pub fn controller() -> Option<&'static dyn Controller> {
+    unsafe { *CONTROLLER.0.get() }
+}
+
+/// Register a handler for the `idx`-th interrupt of a devicetree node.
+///
+/// The interrupt is configured according to its specifier and unmasked. Both
+/// `interrupts` and `interrupts-extended` are understood, but only interrupts
+/// of the root controller can be requested.
+pub fn request(
+    node: &FdtNode,
+    idx: usize,
+    handler: Handler,
+) -> Result<u32, Error> {
+    let ctl = controller().ok_or(Error::NoController)?;
+    let cells = ctl.interrupt_cells();
+    let root = PHANDLE.load(Ordering::Relaxed);
+
+    let mut spec = [0u32; 4];
+    if cells > spec.len() {
+        return Err(Error::BadSpecifier);
+    }
+
+    let mut raw = match node.prop_cells("interrupts-extended") {
+        Some(mut raw) => {
+            for _ in 0..idx {
+                let parent = raw.next().ok_or(Error::BadSpecifier)?;
+                for _ in 0..parent_cells(parent, root, cells)? {
+                    raw.next().ok_or(Error::BadSpecifier)?;
+                }
+            }
+
+            if raw.next().ok_or(Error::BadSpecifier)? != root {
+                return Err(Error::NotRoot);
+            }
+
+            raw
+        }
+        None => {
+            if interrupt_parent(node).is_some_and(|parent| parent != root) {
+                return Err(Error::NotRoot);
+            }
+
+            let mut raw =
+                node.prop_cells("interrupts").ok_or(Error::BadSpecifier)?;
+            for _ in 0..idx * cells {
+                raw.next().ok_or(Error::BadSpecifier)?;
+            }
+
+            raw
+        }
+    };
+
+    for cell in spec.iter_mut().take(cells) {
+        *cell = raw.next().ok_or(Error::BadSpecifier)?;
+    }
+
+    let (irq, trigger) =
+        ctl.xlate(&spec[..cells]).ok_or(Error::BadSpecifier)?;
+
+    register(irq, handler)?;
+    ctl.set_trigger(irq, trigger);
+    ctl.enable(irq);
+
+    Ok(irq)
+}
+
+/// Find the phandle of the interrupt parent of a node.
+///
+/// The `interrupt-parent` property is inherited from the closest ancestor which
+/// has it.
+fn interrupt_parent(node: &FdtNode) -> Option<u32> {
+    let fdt: &FdtView = fdt::get();
+    let mut node = *node;
+
+    loop {
+        if let Some(parent) = node.prop_u32("interrupt-parent") {
+            return Some(parent);
+        }
+
+        node = fdt.parent_of(&node)?;
+    }
+}
+
+/// Obtain number of cells in interrupt specifiers of a given controller.
+///
+/// # Arguments
+///
+/// - `parent`: Phandle of the controller.
+/// - `root`: Phandle of the root controller.
+/// - `cells`: Number of cells in specifiers of the root controller.
+fn parent_cells(parent: u32, root: u32, cells: usize) -> Result<usize, Error> {
+    if parent == root {
+        return Ok(cells);
+    }
+
+    fdt::get()
+        .node_by_phandle(Phandle::new(parent.to_be_bytes()))
+        .and_then(|node| node.prop_u32("#interrupt-cells"))
+        .map(|cells| cells as usize)
+        .ok_or(Error::BadSpecifier)
+}
+
+/// Register a handler for a given interrupt number.
+pub fn register(irq: u32, handler: Handler) -> Result<(), Error> {
+    let slot = HANDLERS.get(irq as usize).ok_or(Error::OutOfRange)?;
+
+    slot.compare_exchange(
+        core::ptr::null_mut(),
+        handler as *mut (),
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    )
+    .map(|_| ())
+    .map_err(|_| Error::Busy)
+}
+
+/// Remove a handler of a given interrupt number.
+pub fn unregister(irq: u32) {
+    if let Some(slot) = HANDLERS.get(irq as usize) {
+        slot.store(core::ptr::null_mut(), Ordering::Release);
+    }
+}
+
+/// Call a handler registered for a given interrupt number.
+///
+/// Returns `false` if there was no handler to call.
+pub fn handle(irq: u32) -> bool {
+    let Some(slot) = HANDLERS.get(irq as usize) else {
+        return false;
+    };
+
+    let ptr = slot.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return false;
+    }
+
+    let handler: Handler = unsafe { core::mem::transmute(ptr) };
+    handler(irq);
+
+    true
+}
+
+/// Handle an interrupt exception.
+///
+/// Called by the architecture-specific exception handling code.
+pub fn dispatch() {
+    if let Some(ctl) = controller() {
+        ctl.dispatch();
+    }
+}
+
+/// Leave the root interrupt controller in a state expected by the payload.
+pub fn handoff() {
+    if let Some(ctl) = controller() {
+        ctl.handoff();
+    }
+}
+
+/// See: [`CONTROLLER`].
+struct ControllerCell(UnsafeCell<Option<&'static dyn Controller>>);
+unsafe impl Sync for ControllerCell {}
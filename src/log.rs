@@ -0,0 +1,373 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::console;
+use crate::fdt::{self, FdtStreamable};
+use crate::time;
+
+/// Most verbose level of messages compiled in.
+///
+/// Messages of less severe levels are stripped at compile time, as selected by
+/// the `max-level-*` cargo features. If several of them are enabled, the most
+/// restrictive one wins.
+pub const STATIC_MAX_LEVEL: u8 = if cfg!(feature = "max-level-off") {
+    0
+} else if cfg!(feature = "max-level-error") {
+    Level::Error as u8
+} else if cfg!(feature = "max-level-warn") {
+    Level::Warn as u8
+} else if cfg!(feature = "max-level-info") {
+    Level::Info as u8
+} else if cfg!(feature = "max-level-debug") {
+    Level::Debug as u8
+} else {
+    Level::Trace as u8
+};
+
+/// Level of messages printed when no filter says otherwise.
+const DEFAULT_LEVEL: Level = Level::Info;
+
+/// Name of the `/chosen` property holding the log filter.
+const PROP_LOGLEVEL: &str = "lunar,loglevel";
+
+/// Prefix of the command line parameter holding the log filter.
+const PARAM_LOGLEVEL: &str = "lunar.loglevel=";
+
+/// Most verbose level enabled by the log filter for any target.
+///
+/// Lets disabled messages be rejected without looking at the filter.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL as u8);
+
+/// The log filter, see [`set_filter`].
+///
+/// Written by [`set_filter`], before secondary cores are started.
+static FILTER: FilterCell = FilterCell(UnsafeCell::new(None));
+
+/// Severity of a log message, from the most to the least severe.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 5] = [
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Parse a level of a filter directive.
+///
+/// Levels are given by name, case-insensitively, or by number, with `off` or
+/// zero disabling messages altogether. Returns the numeric value of the level.
+fn parse_level(s: &str) -> Option<u8> {
+    if s.eq_ignore_ascii_case("off") {
+        return Some(0);
+    }
+
+    if let Some(level) = Level::ALL
+        .iter()
+        .find(|level| s.eq_ignore_ascii_case(level.name()))
+    {
+        return Some(*level as u8);
+    }
+
+    s.parse().ok().filter(|level| *level <= Level::Trace as u8)
+}
+
+/// Strip the crate name off a target, which `module_path!` starts with.
+fn short_target(target: &str) -> &str {
+    target
+        .strip_prefix(env!("CARGO_CRATE_NAME"))
+        .and_then(|rest| rest.strip_prefix("::"))
+        .unwrap_or(target)
+}
+
+/// Split a filter into its directives, each being an optional target and a
+/// level.
+fn directives(filter: &str) -> impl Iterator<Item = (Option<&str>, &str)> {
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| match directive.split_once('=') {
+            Some((target, level)) => (Some(target.trim()), level.trim()),
+            None => (None, directive),
+        })
+}
+
+/// Find the level enabled by the log filter for a given target.
+///
+/// The directive with the longest target which the given one is, or is nested
+/// in, applies. Directives without a target apply to all targets.
+fn level_for(target: &str) -> u8 {
+    let Some(filter) = (unsafe { *FILTER.0.get() }) else {
+        return DEFAULT_LEVEL as u8;
+    };
+
+    let target = short_target(target);
+    let mut best = (0, DEFAULT_LEVEL as u8);
+
+    for (path, level) in directives(filter) {
+        let Some(level) = parse_level(level) else {
+            continue;
+        };
+
+        let len = match path {
+            None => 0,
+            Some(path) if target == path => path.len(),
+            Some(path)
+                if target
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with("::")) =>
+            {
+                path.len()
+            }
+            Some(_) => continue,
+        };
+
+        if len >= best.0 {
+            best = (len, level);
+        }
+    }
+
+    best.1
+}
+
+/// Set the log filter.
+///
+/// The filter is a comma-separated list of directives, each either a level
+/// applying to all targets or `<target>=<level>`, such as
+/// `warn,drivers=debug,drivers::pl011=trace`. Targets are module paths
+/// without the crate name.
+///
+/// # Arguments
+///
+/// - `filter`: The filter, which must outlive the log.
+pub fn set_filter(filter: &'static str) {
+    let mut global = DEFAULT_LEVEL as u8;
+    let mut max = 0;
+
+    for (target, level) in directives(filter) {
+        let Some(level) = parse_level(level) else {
+            warn!("unknown log level '{level}', ignored");
+            continue;
+        };
+
+        match target {
+            None => global = level,
+            Some(_) => max = max.max(level),
+        }
+    }
+
+    unsafe {
+        *FILTER.0.get() = Some(filter);
+    }
+
+    MAX_LEVEL.store(max.max(global), Ordering::Relaxed);
+}
+
+/// Set up the log filter from the devicetree.
+///
+/// The `lunar.loglevel=` parameter of `/chosen/bootargs` takes precedence over
+/// the `lunar,loglevel` property of `/chosen`. Both take a filter in the
+/// format accepted by [`set_filter`]. Messages up to [`Level::Info`] are
+/// printed if neither is present.
+pub fn init() {
+    let Some(chosen) = fdt::get().node_by_path("/chosen") else {
+        return;
+    };
+
+    let param = chosen.prop_str("bootargs").and_then(|args| {
+        args.trim_end_matches('\0')
+            .split_ascii_whitespace()
+            .find_map(|arg| arg.strip_prefix(PARAM_LOGLEVEL))
+    });
+
+    let prop = chosen
+        .prop_str(PROP_LOGLEVEL)
+        .map(|prop| prop.trim_end_matches('\0'));
+
+    if let Some(filter) = param.or(prop) {
+        set_filter(filter);
+    }
+}
+
+/// Check whether messages of a given level and target are to be printed.
+pub fn enabled(level: Level, target: &str) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+        && level as u8 <= level_for(target)
+}
+
+/// Implementation detail of the log macros.
+///
+/// Messages are prefixed with time elapsed since reset. Errors are flushed out
+/// of the consoles right away.
+#[doc(hidden)]
+pub fn _log(level: Level, target: &str, args: fmt::Arguments) {
+    let now = time::uptime();
+    let print = match level {
+        Level::Error => console::_eprint,
+        _ => console::_print,
+    };
+
+    print(format_args!(
+        "[{:5}.{:06}] {:<5} {}: {}\n",
+        now.as_secs(),
+        now.subsec_micros(),
+        level,
+        short_target(target),
+        args
+    ));
+}
+
+/// Log a message of a given level.
+///
+/// The target defaults to the path of the calling module.
+#[allow(unused_macros)]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let level: $crate::log::Level = $level;
+
+        if level as u8 <= $crate::log::STATIC_MAX_LEVEL
+            && $crate::log::enabled(level, $target)
+        {
+            $crate::log::_log(level, $target, format_args!($($arg)+));
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: module_path!(), $level, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use log;
+
+/// Log a message of the [`Level::Error`] level.
+#[allow(unused_macros)]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: $target, $crate::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log::log!($crate::log::Level::Error, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use error;
+
+/// Log a message of the [`Level::Warn`] level.
+///
+/// Defined under another name, as `warn` alone clashes with the built-in
+/// attribute.
+#[allow(unused_macros)]
+macro_rules! log_warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: $target, $crate::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log::log!($crate::log::Level::Warn, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use log_warn as warn;
+
+/// Log a message of the [`Level::Info`] level.
+#[allow(unused_macros)]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: $target, $crate::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log::log!($crate::log::Level::Info, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use info;
+
+/// Log a message of the [`Level::Debug`] level.
+#[allow(unused_macros)]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: $target, $crate::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log::log!($crate::log::Level::Debug, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use debug;
+
+/// Log a message of the [`Level::Trace`] level.
+#[allow(unused_macros)]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log::log!(target: $target, $crate::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log::log!($crate::log::Level::Trace, $($arg)+)
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use trace;
+
+/// See: [`FILTER`].
+struct FilterCell(UnsafeCell<Option<&'static str>>);
+unsafe impl Sync for FilterCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn parse_level_names_and_numbers() {
+        assert_eq!(parse_level("off"), Some(0));
+        assert_eq!(parse_level("WARN"), Some(Level::Warn as u8));
+        assert_eq!(parse_level("trace"), Some(Level::Trace as u8));
+        assert_eq!(parse_level("3"), Some(Level::Info as u8));
+        assert_eq!(parse_level("6"), None);
+        assert_eq!(parse_level("verbose"), None);
+    }
+
+    #[test_case]
+    fn directives_split() {
+        let mut dirs = directives(" info, drivers::gic = trace,,");
+
+        assert_eq!(dirs.next(), Some((None, "info")));
+        assert_eq!(dirs.next(), Some((Some("drivers::gic"), "trace")));
+        assert_eq!(dirs.next(), None);
+    }
+
+    #[test_case]
+    fn short_target_strips_crate() {
+        assert_eq!(short_target(module_path!()), "log::tests");
+        assert_eq!(short_target("drivers"), "drivers");
+    }
+}
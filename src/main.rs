@@ -3,43 +3,73 @@
 
 #![no_std]
 #![no_main]
+#![cfg_attr(test, feature(custom_test_frameworks))]
+#![cfg_attr(test, test_runner(crate::test::run))]
+#![cfg_attr(test, reexport_test_harness_main = "test_main")]
 
 pub mod align;
+pub mod arch;
+pub mod backtrace;
+pub mod block;
+pub mod boot;
+pub mod console;
+pub mod cpu;
+pub mod crc32;
+pub mod drivers;
 pub mod fdt;
 pub mod inttypes;
+pub mod irq;
+pub mod log;
 pub mod mem;
+pub mod mmio;
+pub mod panic;
+pub mod platform;
+#[cfg(feature = "semihosting")]
+pub mod semihosting;
+pub mod shell;
+pub mod smp;
+pub mod symbols;
+#[cfg(test)]
+pub mod test;
+pub mod time;
 
 /// A module exporting build-generated section constants.
 pub mod sections {
     include!(env!("BUILD_SECTIONS"));
 }
 
-use core::panic::PanicInfo;
-
-use crate::fdt::FdtStreamable;
+use crate::log::info;
 
 #[unsafe(no_mangle)]
 #[unsafe(link_section = sections::start_text!())]
 pub extern "C" fn kentry() -> ! {
     fdt::init();
+    time::init();
+    console::earlycon::init();
+    #[cfg(feature = "semihosting")]
+    semihosting::init();
+    log::init();
+    panic::init();
+    info!("lunar {}", env!("CARGO_PKG_VERSION"));
+
+    // Reclaimed once the token is dropped, which it never is for now.
+    let _arena = mem::start::init();
 
-    // XXX temporary, for GDB testing
-    #[allow(unused_variables)]
-    let arena = mem::start::init();
+    arch::init();
+    cpu::init();
+    drivers::probe_all();
 
-    // XXX temporary, for GDB testing
-    let stdout_path = fdt::get()
-        .node_by_name("chosen")
-        .unwrap()
-        .prop_str("stdout-path")
-        .unwrap();
+    // Probing the root interrupt controller set it up for this core.
+    if irq::controller().is_some() {
+        arch::enable_interrupts();
+    }
 
-    // XXX temporary, for GDB testing
-    let stdout = fdt::get().node_by_path(stdout_path).unwrap();
+    block::part::scan_all();
+    smp::init();
+    smp::boot_secondaries();
 
-    // XXX temporary, for GDB testing
-    #[allow(unused_variables)]
-    let range = stdout.reg_u64();
+    #[cfg(test)]
+    test_main();
 
     kmain();
 }
@@ -48,8 +78,3 @@ pub extern "C" fn kentry() -> ! {
 fn kmain() -> ! {
     loop {}
 }
-
-#[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
-    loop {}
-}
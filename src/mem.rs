@@ -1,4 +1,5 @@
 // SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
 // SPDX-License-Identifier: EUPL-1.2
 
+pub mod dma;
 pub mod start;
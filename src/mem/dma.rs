@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::align;
+
+/// Size of the pool of memory shared with devices.
+const DMA_POOL_SIZE: usize = 0x40000;
+
+/// Alignment of the pool, which no allocation may exceed.
+pub const DMA_POOL_ALIGN: usize = 0x1000;
+
+/// Memory shared with devices, such as virtqueues.
+///
+/// Part of the image, so it is identity mapped and physically contiguous, and
+/// is never reclaimed.
+static POOL: Pool = Pool(UnsafeCell::new([0; DMA_POOL_SIZE]));
+
+/// Offset of the first free byte of [`POOL`].
+static CURSOR: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocate zeroed memory for sharing with devices.
+///
+/// Allocations are never freed, so this is meant for structures living as
+/// long as their devices do. Addresses of the memory are the ones devices are
+/// to be given. Returns [`None`] once the pool runs out.
+///
+/// # Arguments
+///
+/// - `size`: Size of the allocation in bytes.
+/// - `align`: Alignment of the allocation, a power of two no greater than
+///   [`DMA_POOL_ALIGN`].
+pub fn alloc(size: usize, align: usize) -> Option<NonNull<u8>> {
+    assert!(align <= DMA_POOL_ALIGN);
+
+    let mut start = 0;
+
+    CURSOR
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |cursor| {
+            start = align::align_up!(cursor, align);
+            start.checked_add(size).filter(|end| *end <= DMA_POOL_SIZE)
+        })
+        .ok()?;
+
+    unsafe {
+        let ptr = (POOL.0.get() as *mut u8).add(start);
+        ptr.write_bytes(0, size);
+
+        NonNull::new(ptr)
+    }
+}
+
+/// See: [`POOL`].
+#[repr(C, align(0x1000))]
+struct Pool(UnsafeCell<[u8; DMA_POOL_SIZE]>);
+unsafe impl Sync for Pool {}
@@ -6,6 +6,7 @@ use core::sync::atomic::AtomicBool;
 use core::sync::atomic::Ordering;
 
 use crate::align;
+use crate::log::debug;
 use crate::sections;
 
 unsafe extern "C" {
@@ -57,6 +58,8 @@ pub fn init() -> Token<'static> {
         panic!("Double initialization of start arena");
     }
 
+    debug!("start arena at {start:#x}..{end:#x}");
+
     unsafe {
         *ARENA.0.get() = Some(arena);
     }
@@ -112,3 +115,40 @@ impl<'a> Drop for Token<'a> {
 /// See: [`START_ARENA`].
 struct ArenaCell(UnsafeCell<Option<Arena>>);
 unsafe impl Sync for ArenaCell {}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::ManuallyDrop;
+
+    use super::*;
+
+    /// Obtain a token for the arena set up by [`kentry`](crate::kentry),
+    /// which must not reclaim it when dropped.
+    fn token() -> ManuallyDrop<Token<'static>> {
+        ManuallyDrop::new(Token {
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    #[test_case]
+    fn alloc_slice_aligns() {
+        let token = token();
+        let bytes = token.alloc_slice::<u8>(3);
+        let words = token.alloc_slice::<u64>(2);
+
+        assert_eq!(bytes.len(), 3);
+        assert_eq!(words.len(), 2);
+        assert!(words.as_ptr().is_aligned());
+        assert!(words.as_ptr() as usize >= bytes.as_ptr() as usize + 3);
+    }
+
+    #[test_case]
+    fn alloc_slice_within_arena() {
+        let token = token();
+        let slice = token.alloc_slice::<u32>(16);
+        let start = slice.as_ptr() as usize;
+
+        assert!(start >= &raw const __arena as usize);
+        assert!(start + size_of_val(slice) <= &raw const __earena as usize);
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::ptr;
+
+/// A block of memory-mapped device registers.
+///
+/// All accesses are volatile and offsets are expressed in bytes, relative to
+/// the base address of the block.
+#[derive(Clone, Copy, Debug)]
+pub struct Mmio {
+    base: usize,
+}
+
+impl Mmio {
+    /// Construct a handle to a register block at a given physical address.
+    pub const fn new(base: usize) -> Self {
+        Mmio { base }
+    }
+
+    /// Obtain base address of the register block.
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Obtain a handle to a register block located at a given offset.
+    pub const fn offset(&self, off: usize) -> Self {
+        Mmio::new(self.base + off)
+    }
+
+    pub fn read8(&self, off: usize) -> u8 {
+        unsafe { ptr::read_volatile((self.base + off) as *const u8) }
+    }
+
+    pub fn write8(&self, off: usize, val: u8) {
+        unsafe { ptr::write_volatile((self.base + off) as *mut u8, val) }
+    }
+
+    pub fn read16(&self, off: usize) -> u16 {
+        unsafe { ptr::read_volatile((self.base + off) as *const u16) }
+    }
+
+    pub fn write16(&self, off: usize, val: u16) {
+        unsafe { ptr::write_volatile((self.base + off) as *mut u16, val) }
+    }
+
+    pub fn read32(&self, off: usize) -> u32 {
+        unsafe { ptr::read_volatile((self.base + off) as *const u32) }
+    }
+
+    pub fn write32(&self, off: usize, val: u32) {
+        unsafe { ptr::write_volatile((self.base + off) as *mut u32, val) }
+    }
+
+    pub fn read64(&self, off: usize) -> u64 {
+        unsafe { ptr::read_volatile((self.base + off) as *const u64) }
+    }
+
+    pub fn write64(&self, off: usize, val: u64) {
+        unsafe { ptr::write_volatile((self.base + off) as *mut u64, val) }
+    }
+}
@@ -0,0 +1,203 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::time::Duration;
+
+use crate::arch;
+use crate::backtrace::Backtrace;
+use crate::console::{self, eprintln};
+use crate::cpu;
+use crate::fdt::{self, FdtStreamable};
+use crate::log::warn;
+use crate::platform;
+#[cfg(not(test))]
+use crate::shell;
+#[cfg(not(test))]
+use crate::time;
+
+/// Value of [`PANICKING`] while no core has panicked.
+const NONE: u64 = u64::MAX;
+
+/// Delay before a reset, when the panic action names none.
+const DEFAULT_RESET_DELAY: Duration = Duration::from_secs(5);
+
+/// Name of the `/chosen` property selecting the panic action.
+const PROP_PANIC: &str = "lunar,panic";
+
+/// Prefix of the command line parameter selecting the panic action.
+const PARAM_PANIC: &str = "lunar.panic=";
+
+/// Hardware identifier of the core handling a panic.
+///
+/// Only the first core to panic reports it, others just stop.
+static PANICKING: AtomicU64 = AtomicU64::new(NONE);
+
+/// Set once the panic handler itself has panicked.
+static RECURSED: AtomicBool = AtomicBool::new(false);
+
+/// Action taken once a panic is reported.
+///
+/// Written by [`init`], before secondary cores are started.
+static ACTION: ActionCell = ActionCell(UnsafeCell::new(Action::Halt));
+
+/// Action taken once a panic is reported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Stop the panicking core.
+    Halt,
+    /// Reset the system after a given delay.
+    Reset(Duration),
+    /// Run the debug shell, see [`shell::run`].
+    Shell,
+}
+
+impl Action {
+    /// Parse a panic action, such as `halt`, `shell`, `reset` or `reset:10`.
+    ///
+    /// The optional argument of `reset` is the delay in seconds.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+
+        match (name, arg) {
+            ("halt", None) => Some(Action::Halt),
+            ("shell", None) => Some(Action::Shell),
+            ("reset", None) => Some(Action::Reset(DEFAULT_RESET_DELAY)),
+            ("reset", Some(secs)) => secs
+                .parse()
+                .ok()
+                .map(|secs| Action::Reset(Duration::from_secs(secs))),
+            _ => None,
+        }
+    }
+}
+
+/// Select the panic action from the devicetree.
+///
+/// The `lunar.panic=` parameter of `/chosen/bootargs` takes precedence over
+/// the `lunar,panic` property of `/chosen`. Both take an action in the format
+/// accepted by [`Action::parse`]. The panicking core is halted if neither is
+/// present.
+pub fn init() {
+    let Some(chosen) = fdt::get().node_by_path("/chosen") else {
+        return;
+    };
+
+    let param = chosen.prop_str("bootargs").and_then(|args| {
+        args.trim_end_matches('\0')
+            .split_ascii_whitespace()
+            .find_map(|arg| arg.strip_prefix(PARAM_PANIC))
+    });
+
+    let prop = chosen
+        .prop_str(PROP_PANIC)
+        .map(|prop| prop.trim_end_matches('\0'));
+
+    let Some(action) = param.or(prop) else {
+        return;
+    };
+
+    match Action::parse(action) {
+        Some(action) => unsafe { *ACTION.0.get() = action },
+        None => warn!("unknown panic action '{action}', ignored"),
+    }
+}
+
+/// Report a panic over the console and take the configured action.
+///
+/// A panic of the panic handler itself is reported in short, once, after which
+/// the core is halted.
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    arch::disable_interrupts();
+
+    let hwid = cpu::current_id();
+
+    if let Err(owner) = PANICKING.compare_exchange(
+        NONE,
+        hwid,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        if owner == hwid && !RECURSED.swap(true, Ordering::Relaxed) {
+            eprintln!("panic while panicking, halting");
+        }
+
+        platform::halt();
+    }
+
+    console::enter_panic();
+
+    match cpu::current() {
+        Some(idx) => eprintln!(
+            "\npanic on CPU {idx} (hwid {hwid:#x}) at {}:",
+            arch::privilege_level()
+        ),
+        None => eprintln!(
+            "\npanic on CPU with hwid {hwid:#x} at {}:",
+            arch::privilege_level()
+        ),
+    }
+
+    eprintln!("  {}", info.message());
+
+    if let Some(loc) = info.location() {
+        eprintln!("  at {}:{}:{}", loc.file(), loc.line(), loc.column());
+    }
+
+    Backtrace::capture().print();
+
+    // A panic in a test build fails the running test instead.
+    #[cfg(test)]
+    crate::test::fail();
+
+    #[cfg(not(test))]
+    act(unsafe { *ACTION.0.get() })
+}
+
+/// Take a panic action, once the panic is reported.
+#[cfg(not(test))]
+fn act(action: Action) -> ! {
+    match action {
+        Action::Halt => {
+            eprintln!("halting");
+            platform::halt();
+        }
+        Action::Reset(delay) => {
+            eprintln!("resetting in {} s", delay.as_secs());
+            time::delay(delay);
+            platform::reset();
+        }
+        Action::Shell => shell::run(),
+    }
+}
+
+/// See: [`ACTION`].
+struct ActionCell(UnsafeCell<Action>);
+unsafe impl Sync for ActionCell {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn parse_actions() {
+        assert_eq!(Action::parse("halt"), Some(Action::Halt));
+        assert_eq!(Action::parse("shell"), Some(Action::Shell));
+        assert_eq!(
+            Action::parse("reset"),
+            Some(Action::Reset(DEFAULT_RESET_DELAY))
+        );
+        assert_eq!(
+            Action::parse("reset:10"),
+            Some(Action::Reset(Duration::from_secs(10)))
+        );
+        assert_eq!(Action::parse("reset:soon"), None);
+        assert_eq!(Action::parse("halt:1"), None);
+    }
+}
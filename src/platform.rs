@@ -0,0 +1,29 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::arch;
+
+/// Reset the whole system.
+///
+/// If the platform offers no way of doing so, the current core is halted.
+pub fn reset() -> ! {
+    arch::system_reset();
+    halt()
+}
+
+/// Power off the whole system.
+///
+/// If the platform offers no way of doing so, the current core is halted.
+pub fn poweroff() -> ! {
+    arch::system_off();
+    halt()
+}
+
+/// Stop the current core for good, with interrupts masked.
+pub fn halt() -> ! {
+    arch::disable_interrupts();
+
+    loop {
+        arch::wait_for_interrupt();
+    }
+}
@@ -0,0 +1,272 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::fmt;
+
+use crate::arch;
+use crate::console::{self, Sink};
+use crate::platform;
+
+/// Semihosting operation numbers.
+const SYS_OPEN: usize = 0x01;
+const SYS_CLOSE: usize = 0x02;
+const SYS_WRITEC: usize = 0x03;
+const SYS_WRITE0: usize = 0x04;
+const SYS_READ: usize = 0x06;
+const SYS_READC: usize = 0x07;
+const SYS_SEEK: usize = 0x0A;
+const SYS_FLEN: usize = 0x0C;
+const SYS_ERRNO: usize = 0x13;
+const SYS_EXIT_EXTENDED: usize = 0x20;
+
+/// Mode of [`SYS_OPEN`] opening a file for reading in binary mode, `rb`.
+const OPEN_READ_BINARY: usize = 1;
+
+/// Reason of [`SYS_EXIT_EXTENDED`] reporting a normal exit of the program.
+const ADP_STOPPED_APPLICATION_EXIT: usize = 0x20026;
+
+/// Maximum length of a file path, including the terminating NUL.
+const MAX_PATH: usize = 256;
+
+/// Size of chunks strings are split into by [`write_str`].
+const WRITE_CHUNK: usize = 128;
+
+/// Errors reported by semihosting file operations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The host failed the operation, with a given `errno` value.
+    Host(isize),
+    /// The path is too long to be passed to the host.
+    PathTooLong,
+    /// The file ended before the buffer was filled.
+    UnexpectedEof,
+    /// The file does not fit in the memory it is to be loaded into.
+    TooLarge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Host(errno) => write!(f, "host error {errno}"),
+            Error::PathTooLong => write!(f, "path too long"),
+            Error::UnexpectedEof => write!(f, "unexpected end of file"),
+            Error::TooLarge => write!(f, "file too large"),
+        }
+    }
+}
+
+/// Issue a call with a parameter block.
+fn call(op: usize, params: &[usize]) -> isize {
+    arch::semihosting_call(op, params.as_ptr() as usize)
+}
+
+/// Turn a return value of a call into a result, `-1` marking a failure.
+fn check(ret: isize) -> Result<usize, Error> {
+    if ret == -1 {
+        return Err(Error::Host(arch::semihosting_call(SYS_ERRNO, 0)));
+    }
+
+    Ok(ret as usize)
+}
+
+/// Write a character to the debug console of the host.
+pub fn write_char(ch: u8) {
+    arch::semihosting_call(SYS_WRITEC, &raw const ch as usize);
+}
+
+/// Write a string to the debug console of the host.
+///
+/// The string is passed in NUL-terminated chunks, so it is cut short at any
+/// NUL it contains.
+pub fn write_str(s: &str) {
+    let mut buf = [0u8; WRITE_CHUNK + 1];
+
+    for chunk in s.as_bytes().chunks(WRITE_CHUNK) {
+        buf[..chunk.len()].copy_from_slice(chunk);
+        buf[chunk.len()] = 0;
+
+        arch::semihosting_call(SYS_WRITE0, buf.as_ptr() as usize);
+    }
+}
+
+/// Read a character from the debug console of the host.
+///
+/// Blocks until the host provides one.
+pub fn read_char() -> u8 {
+    arch::semihosting_call(SYS_READC, 0) as u8
+}
+
+/// Stop the program, reporting an exit code to the host.
+///
+/// The current core is halted if the host carries on anyway.
+pub fn exit(code: u32) -> ! {
+    call(
+        SYS_EXIT_EXTENDED,
+        &[ADP_STOPPED_APPLICATION_EXIT, code as usize],
+    );
+
+    platform::halt()
+}
+
+/// Debug console of the host.
+///
+/// Only used for output. [`SYS_READC`] blocks until the host provides a
+/// character, which would stall everyone waiting for the console lock while
+/// input is polled for.
+pub struct Console;
+
+impl Sink for Console {
+    fn write_str(&self, s: &str) {
+        write_str(s);
+    }
+}
+
+/// Register the debug console of the host as a console.
+///
+/// Output written to it usually ends up where the UART output goes, so this is
+/// only done if no other console is registered.
+pub fn init() {
+    if !console::has_sink() {
+        let _ = console::register("semihosting", &Console);
+    }
+}
+
+/// A file of the host, open for reading.
+///
+/// Meant as a source of images to boot, such as the payload. Closed when
+/// dropped.
+pub struct File {
+    handle: usize,
+}
+
+impl File {
+    /// Open a file of the host for reading.
+    ///
+    /// # Arguments
+    ///
+    /// - `path`: Path of the file, relative to the working directory of the
+    ///   host if not absolute.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let mut name = [0u8; MAX_PATH];
+
+        if path.len() >= MAX_PATH {
+            return Err(Error::PathTooLong);
+        }
+
+        name[..path.len()].copy_from_slice(path.as_bytes());
+
+        let params = [name.as_ptr() as usize, OPEN_READ_BINARY, path.len()];
+        let handle = check(call(SYS_OPEN, &params))?;
+
+        Ok(File { handle })
+    }
+
+    /// Obtain length of the file in bytes.
+    pub fn len(&self) -> Result<usize, Error> {
+        check(call(SYS_FLEN, &[self.handle]))
+    }
+
+    /// Check whether the file is empty.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Move the read position to a given offset from the start of the file.
+    pub fn seek(&mut self, pos: usize) -> Result<(), Error> {
+        check(call(SYS_SEEK, &[self.handle, pos])).map(|_| ())
+    }
+
+    /// Read from the current position, returning number of bytes read.
+    ///
+    /// Fewer bytes than requested are read only at the end of the file.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let params = [self.handle, buf.as_mut_ptr() as usize, buf.len()];
+        let left = call(SYS_READ, &params);
+
+        read_len(buf.len(), left).map_or_else(|| check(-1), Ok)
+    }
+
+    /// Fill a buffer from the current position.
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        if self.read(buf)? != buf.len() {
+            return Err(Error::UnexpectedEof);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        let _ = call(SYS_CLOSE, &[self.handle]);
+    }
+}
+
+/// Turn the number of bytes [`SYS_READ`] did not read into the number of bytes
+/// it did, or [`None`] if the call failed.
+///
+/// # Arguments
+///
+/// - `len`: Number of bytes requested.
+/// - `left`: Value returned by the call.
+fn read_len(len: usize, left: isize) -> Option<usize> {
+    usize::try_from(left)
+        .ok()
+        .filter(|left| *left <= len)
+        .map(|left| len - left)
+}
+
+/// Load a whole file of the host into memory.
+///
+/// Returns the part of the destination the file was loaded into.
+///
+/// # Arguments
+///
+/// - `path`: Path of the file, see [`File::open`].
+/// - `dst`: Memory to load the file into, which must be large enough.
+pub fn load<'a>(path: &str, dst: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
+    let mut file = File::open(path)?;
+    let len = file.len()?;
+    let dst = dst.get_mut(..len).ok_or(Error::TooLarge)?;
+
+    file.read_exact(dst)?;
+
+    Ok(dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn read_len_counts_bytes_read() {
+        assert_eq!(read_len(16, 0), Some(16));
+        assert_eq!(read_len(16, 6), Some(10));
+        assert_eq!(read_len(16, 16), Some(0));
+        assert_eq!(read_len(16, 17), None);
+        assert_eq!(read_len(16, -1), None);
+    }
+
+    #[test_case]
+    fn open_rejects_long_paths() {
+        let name = [b'a'; MAX_PATH];
+        let path = core::str::from_utf8(&name).unwrap();
+
+        assert_eq!(File::open(path).err(), Some(Error::PathTooLong));
+        assert!(matches!(File::open(&path[1..]), Err(Error::Host(_))));
+    }
+
+    #[test_case]
+    fn load_reads_host_files() {
+        let mut buf = [0u8; 0x1000];
+        let len = File::open("Cargo.toml").unwrap().len().unwrap();
+        let data = load("Cargo.toml", &mut buf).unwrap();
+
+        assert_eq!(data.len(), len);
+        assert!(data.starts_with(b"# SPDX-FileCopyrightText"));
+        assert_eq!(
+            load("Cargo.toml", &mut buf[..8]).err(),
+            Some(Error::TooLarge)
+        );
+    }
+}
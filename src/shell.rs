@@ -0,0 +1,296 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use crate::boot;
+use crate::console::{self, print, println};
+use crate::drivers::fw_cfg::{self, Blob};
+use crate::platform;
+#[cfg(feature = "semihosting")]
+use crate::semihosting;
+use crate::time;
+
+/// Maximum length of a command line.
+const MAX_LINE: usize = 80;
+
+/// Number of bytes dumped by `md` when no length is given.
+const DEFAULT_DUMP: usize = 64;
+
+/// Maximum number of bytes dumped by `md` at once.
+const MAX_DUMP: usize = 0x1000;
+
+/// Bytes dumped per line by `md`.
+const DUMP_LINE: usize = 16;
+
+/// A shell command.
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    help: &'static str,
+    run: fn(&mut dyn Iterator<Item = &str>),
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        usage: "",
+        help: "list commands",
+        run: help,
+    },
+    Command {
+        name: "md",
+        usage: "<addr> [len]",
+        help: "dump memory",
+        run: md,
+    },
+    Command {
+        name: "uptime",
+        usage: "",
+        help: "show time since reset",
+        run: uptime,
+    },
+    Command {
+        name: "fwload",
+        usage: "<addr> <item>",
+        help: "load a QEMU fw_cfg item",
+        run: fwload,
+    },
+    #[cfg(feature = "semihosting")]
+    Command {
+        name: "hload",
+        usage: "<addr> <path>",
+        help: "load a file of the host",
+        run: hload,
+    },
+    Command {
+        name: "go",
+        usage: "<addr>",
+        help: "hand off to a payload",
+        run: go,
+    },
+    Command {
+        name: "reset",
+        usage: "",
+        help: "reset the system",
+        run: |_| platform::reset(),
+    },
+    Command {
+        name: "poweroff",
+        usage: "",
+        help: "power the system off",
+        run: |_| platform::poweroff(),
+    },
+    Command {
+        name: "halt",
+        usage: "",
+        help: "stop this core",
+        run: |_| platform::halt(),
+    },
+];
+
+/// Parse a number, hexadecimal if prefixed with `0x`, decimal otherwise.
+fn parse_num(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn help(_: &mut dyn Iterator<Item = &str>) {
+    for cmd in COMMANDS.iter() {
+        println!("  {:<8} {:<14} {}", cmd.name, cmd.usage, cmd.help);
+    }
+}
+
+/// Dump memory as hexadecimal bytes.
+///
+/// Addresses are not validated, reading one which is not mapped faults.
+fn md(args: &mut dyn Iterator<Item = &str>) {
+    let Some(addr) = args.next().and_then(parse_num) else {
+        println!("md: bad address");
+        return;
+    };
+
+    let len = match args.next() {
+        Some(len) => match parse_num(len) {
+            Some(len) => len.min(MAX_DUMP),
+            None => {
+                println!("md: bad length");
+                return;
+            }
+        },
+        None => DEFAULT_DUMP,
+    };
+
+    let end = addr.saturating_add(len);
+
+    for line in (addr..end).step_by(DUMP_LINE) {
+        print!("{line:016x}:");
+
+        for byte in line..line.saturating_add(DUMP_LINE).min(end) {
+            let val = unsafe { (byte as *const u8).read_volatile() };
+            print!(" {val:02x}");
+        }
+
+        println!();
+    }
+}
+
+/// Load an item of the QEMU firmware configuration into memory.
+///
+/// The item is `kernel`, `initrd` or `cmdline` for the ones passed on the QEMU
+/// command line, or a name of a fw_cfg file. Addresses are not validated,
+/// loading over lunar or over memory which is not mapped breaks the system.
+fn fwload(args: &mut dyn Iterator<Item = &str>) {
+    let Some(fw_cfg) = fw_cfg::get() else {
+        println!("fwload: no fw_cfg device");
+        return;
+    };
+
+    let Some(addr) = args.next().and_then(parse_num).filter(|addr| *addr != 0)
+    else {
+        println!("fwload: bad address");
+        return;
+    };
+
+    let Some(item) = args.next() else {
+        println!("fwload: missing item");
+        return;
+    };
+
+    let blob = match item {
+        "kernel" => Some(Blob::Kernel),
+        "initrd" => Some(Blob::Initrd),
+        "cmdline" => Some(Blob::Cmdline),
+        _ => None,
+    };
+
+    let size = match blob {
+        Some(blob) => fw_cfg.blob_size(blob),
+        None => fw_cfg.file(item).map_or(0, |file| file.size as usize),
+    };
+
+    let dst = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, size) };
+    let loaded = match blob {
+        Some(blob) => fw_cfg.load_blob(blob, dst),
+        None => fw_cfg.load(item, dst),
+    };
+
+    match loaded {
+        Ok(data) => println!("fwload: {} bytes at {addr:#x}", data.len()),
+        Err(err) => println!("fwload: {item}: {err}"),
+    }
+}
+
+/// Load a file of the host into memory, through semihosting.
+///
+/// Addresses are not validated, see [`fwload`].
+#[cfg(feature = "semihosting")]
+fn hload(args: &mut dyn Iterator<Item = &str>) {
+    let Some(addr) = args.next().and_then(parse_num).filter(|addr| *addr != 0)
+    else {
+        println!("hload: bad address");
+        return;
+    };
+
+    let Some(path) = args.next() else {
+        println!("hload: missing path");
+        return;
+    };
+
+    let size = match semihosting::File::open(path).and_then(|file| file.len()) {
+        Ok(size) => size,
+        Err(err) => {
+            println!("hload: {path}: {err}");
+            return;
+        }
+    };
+
+    let dst = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, size) };
+
+    match semihosting::load(path, dst) {
+        Ok(data) => println!("hload: {} bytes at {addr:#x}", data.len()),
+        Err(err) => println!("hload: {path}: {err}"),
+    }
+}
+
+/// Hand the system off to a payload already placed in memory.
+///
+/// See [`boot::handoff`].
+fn go(args: &mut dyn Iterator<Item = &str>) {
+    let Some(entry) = args.next().and_then(parse_num) else {
+        println!("go: bad address");
+        return;
+    };
+
+    let Err(err) = boot::handoff(entry);
+    println!("go: {err}");
+}
+
+fn uptime(_: &mut dyn Iterator<Item = &str>) {
+    let now = time::uptime();
+
+    println!("{}.{:06} s", now.as_secs(), now.subsec_micros());
+}
+
+/// Read a line of input from the console, echoing it back.
+///
+/// Returns the length of the line, which is cut short if it does not fit in
+/// a given buffer.
+fn read_line(buf: &mut [u8]) -> usize {
+    let mut len = 0;
+
+    loop {
+        let Some(byte) = console::getc() else {
+            core::hint::spin_loop();
+            continue;
+        };
+
+        match byte {
+            b'\r' | b'\n' => {
+                println!();
+                return len;
+            }
+            // Backspace and delete.
+            0x08 | 0x7F if len > 0 => {
+                len -= 1;
+                print!("\x08 \x08");
+            }
+            0x20..=0x7E if len < buf.len() => {
+                buf[len] = byte;
+                len += 1;
+                print!("{}", byte as char);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Run a debug shell on the active console.
+///
+/// The shell polls the console and does not rely on interrupts, so it works
+/// from the panic handler. The shell is only left through commands resetting
+/// or halting the system, or handing it off to a payload.
+pub fn run() -> ! {
+    let mut buf = [0; MAX_LINE];
+
+    println!("lunar debug shell, type 'help' for commands");
+
+    loop {
+        print!("> ");
+
+        let len = read_line(&mut buf);
+        let Ok(line) = core::str::from_utf8(&buf[..len]) else {
+            continue;
+        };
+
+        let mut args = line.split_ascii_whitespace();
+        let Some(name) = args.next() else {
+            continue;
+        };
+
+        match COMMANDS.iter().find(|cmd| cmd.name == name) {
+            Some(cmd) => (cmd.run)(&mut args),
+            None => println!("{name}: unknown command"),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use crate::arch::{self, smp::EnableMethod};
+use crate::cpu::{self, MAX_CPUS};
+use crate::irq;
+use crate::log::warn;
+use crate::time;
+
+/// Size of a stack of each secondary core.
+const STACK_SIZE: usize = 0x2000;
+
+/// Time given to a secondary core to report itself online.
+const START_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Hardware identifier of an unused [`BOOT_ARGS`] entry.
+const ARGS_UNUSED: u64 = u64::MAX;
+
+/// Enable methods of cores, indexed by logical core index.
+///
+/// Populated once by [`init`], before any secondary core is started, and only
+/// read afterwards. As such, the [`UnsafeCell`] suffices here.
+static METHODS: MethodsCell = MethodsCell(UnsafeCell::new([None; MAX_CPUS]));
+
+/// Online state of cores, indexed by logical core index.
+static ONLINE: [AtomicBool; MAX_CPUS] =
+    [const { AtomicBool::new(false) }; MAX_CPUS];
+
+/// Stacks of secondary cores, indexed by logical core index.
+static STACKS: StacksCell = StacksCell(UnsafeCell::new(
+    [const { Stack([0; STACK_SIZE]) }; MAX_CPUS],
+));
+
+/// Arguments of secondary cores, indexed by logical core index.
+///
+/// Filled in by [`init`] before any secondary core is started. Each core looks
+/// up the entry with its own hardware identifier in `secondary_start` in
+/// `asm/start.S`, before any stack is available, so a core that is late to
+/// start never picks up arguments of another one. Entries are filled densely
+/// and the last one always remains unused, ending the lookup. Layout of the
+/// entries must match `cpu.h`.
+#[unsafe(export_name = "smp_boot_args")]
+static BOOT_ARGS: [BootArgs; MAX_CPUS + 1] =
+    [const { BootArgs::new() }; MAX_CPUS + 1];
+
+/// Set once secondary cores are to be handed off to the payload.
+static HANDOFF: AtomicBool = AtomicBool::new(false);
+
+/// See: [`BOOT_ARGS`].
+#[repr(C)]
+struct BootArgs {
+    hwid: AtomicU64,
+    stack: AtomicUsize,
+    cpu: AtomicUsize,
+}
+
+impl BootArgs {
+    const fn new() -> Self {
+        BootArgs {
+            hwid: AtomicU64::new(ARGS_UNUSED),
+            stack: AtomicUsize::new(0),
+            cpu: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[repr(C, align(16))]
+struct Stack([u8; STACK_SIZE]);
+
+/// Read enable methods of cores enumerated by [`cpu::init`].
+///
+/// Each core is also given its [`BOOT_ARGS`] entry, with a stack of its own.
+pub fn init() {
+    let methods = unsafe { &mut *METHODS.0.get() };
+
+    for (idx, cpu) in cpu::iter().enumerate() {
+        let stack = unsafe { (*STACKS.0.get())[idx].0.as_ptr_range().end };
+        let args = &BOOT_ARGS[idx];
+
+        args.stack.store(stack as usize, Ordering::Relaxed);
+        args.cpu.store(idx, Ordering::Relaxed);
+        args.hwid.store(cpu.hwid, Ordering::Release);
+
+        methods[idx] = EnableMethod::from_node(&cpu.node);
+
+        if let Some(method) = methods[idx] {
+            method.prepare(cpu.hwid, idx);
+        }
+    }
+
+    if let Some(idx) = cpu::current() {
+        ONLINE[idx].store(true, Ordering::Relaxed);
+    }
+}
+
+fn method(idx: usize) -> Option<EnableMethod> {
+    unsafe { (*METHODS.0.get())[idx] }
+}
+
+/// Check whether a core with a given logical index is running lunar code.
+pub fn is_online(idx: usize) -> bool {
+    ONLINE
+        .get(idx)
+        .is_some_and(|online| online.load(Ordering::Acquire))
+}
+
+/// Start all secondary cores with a known enable method.
+///
+/// Cores are started one at a time and enter [`secondary_entry`]. Cores that
+/// fail to report themselves online within [`START_TIMEOUT`] are skipped.
+pub fn boot_secondaries() {
+    for (idx, cpu) in cpu::iter().enumerate() {
+        let Some(method) = method(idx) else {
+            continue;
+        };
+
+        if is_online(idx) {
+            continue;
+        }
+
+        if method.start(cpu.hwid, idx).is_err() {
+            continue;
+        }
+
+        if time::wait_until(START_TIMEOUT, || is_online(idx)) {
+            method.started();
+        } else {
+            warn!("cpu{idx}: did not come online, skipped");
+        }
+    }
+}
+
+/// Hand secondary cores off to the payload.
+///
+/// Each online secondary core is parked as mandated by its enable method. This
+/// function returns once all of them have left lunar.
+pub fn handoff() {
+    let current = cpu::current();
+
+    HANDOFF.store(true, Ordering::Release);
+    arch::send_event();
+
+    for idx in (0..MAX_CPUS).filter(|idx| Some(*idx) != current) {
+        while is_online(idx) {
+            core::hint::spin_loop();
+        }
+
+        if let (Some(method), Some(cpu)) = (method(idx), cpu::get(idx)) {
+            method.wait_parked(cpu.hwid);
+        }
+    }
+}
+
+/// Entry point of secondary cores in the high-level language.
+///
+/// Called from `secondary_start` in `asm/start.S`, once the core has dropped to
+/// EL1 and has its stack set up.
+#[unsafe(no_mangle)]
+extern "C" fn secondary_entry(idx: usize) -> ! {
+    if let Some(ctl) = irq::controller() {
+        ctl.cpu_init();
+        arch::enable_interrupts();
+    }
+
+    ONLINE[idx].store(true, Ordering::Release);
+
+    while !HANDOFF.load(Ordering::Acquire) {
+        arch::wait_for_event();
+    }
+
+    arch::disable_interrupts();
+    ONLINE[idx].store(false, Ordering::Release);
+
+    match method(idx) {
+        Some(method) => method.park(),
+        None => loop {
+            arch::wait_for_event();
+        },
+    }
+}
+
+/// See: [`METHODS`].
+struct MethodsCell(UnsafeCell<[Option<EnableMethod>; MAX_CPUS]>);
+unsafe impl Sync for MethodsCell {}
+
+/// See: [`STACKS`].
+struct StacksCell(UnsafeCell<[Stack; MAX_CPUS]>);
+unsafe impl Sync for StacksCell {}
@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sections;
+
+unsafe extern "C" {
+    // See: arch/generic/sections.lds.h
+    static __symbols: u8;
+    static __esymbols: u8;
+}
+
+/// Space reserved for the symbol table.
+///
+/// Must be the same as `SYMBOLS_SIZE` in `build/symtab.rs`, which fails the
+/// build if the table does not fit.
+const SYMBOLS_SIZE: usize = 0x60000;
+
+/// Magic number opening a filled-in symbol table, `LSYM` in little endian.
+const MAGIC: u32 = 0x4D59_534C;
+
+/// Size of the table header: magic, symbol count and base address.
+const HEADER_SIZE: usize = 16;
+
+/// Size of a table entry: offset from the base, size and name offset.
+const ENTRY_SIZE: usize = 12;
+
+/// The symbol table, generated by the build script from a first link of the
+/// image.
+///
+/// Only ever read through `__symbols`, so that its contents are not assumed to
+/// be the ones it had when this code was compiled.
+#[used]
+#[unsafe(link_section = sections::symbols!())]
+static SYMBOLS: [u8; SYMBOLS_SIZE] = *include_bytes!(env!("BUILD_SYMBOLS"));
+
+/// Set once the hint about the missing symbol table has been printed.
+static HINTED: AtomicBool = AtomicBool::new(false);
+
+/// A function of lunar.
+#[derive(Clone, Copy, Debug)]
+pub struct Symbol {
+    pub name: &'static str,
+    pub addr: usize,
+    pub size: usize,
+}
+
+/// An address along with the function it belongs to, if known.
+///
+/// Displays as `<address> <function>+<offset>`. If the symbol table does not
+/// describe this image, the first address displayed carries a hint about it.
+#[derive(Clone, Copy)]
+pub struct Symbolized(pub usize);
+
+impl fmt::Display for Symbolized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#018x}", self.0)?;
+
+        if let Some(sym) = lookup(self.0) {
+            write!(f, " {}+{:#x}", sym.name, self.0 - sym.addr)?;
+        } else if !is_filled() && !HINTED.swap(true, Ordering::Relaxed) {
+            write!(f, " (no symbol table for this image)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Obtain contents of the symbol table section.
+fn table() -> &'static [u8] {
+    unsafe {
+        let start = &raw const __symbols;
+        let end = &raw const __esymbols;
+
+        core::slice::from_raw_parts(start, end.offset_from(start) as usize)
+    }
+}
+
+/// Check whether the symbol table has been filled in and describes this image.
+///
+/// The table is generated from the image built by `cargo build`. Images linked
+/// differently, such as test harnesses, carry one describing another layout,
+/// so the table is only trusted if it places [`lookup`] where it actually is.
+pub fn is_filled() -> bool {
+    let addr = lookup as fn(usize) -> Option<Symbol> as usize;
+
+    read_u32(table(), 0) == Some(MAGIC)
+        && find(addr).is_some_and(|sym| {
+            sym.addr == addr && sym.name.ends_with("symbols::lookup")
+        })
+}
+
+fn read_u32(bytes: &[u8], off: usize) -> Option<u32> {
+    let bytes = bytes.get(off..off + 4)?;
+
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(bytes: &[u8], off: usize) -> Option<u64> {
+    let bytes = bytes.get(off..off + 8)?;
+
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Find the function containing a given address.
+///
+/// Returns [`None`] if the address lies outside of all functions, or if the
+/// symbol table has not been filled in.
+pub fn lookup(addr: usize) -> Option<Symbol> {
+    if !is_filled() {
+        return None;
+    }
+
+    find(addr)
+}
+
+/// Find the function containing a given address in the symbol table, whichever
+/// image it describes.
+fn find(addr: usize) -> Option<Symbol> {
+    let table = table();
+
+    let count = read_u32(table, 4)? as usize;
+    let base = read_u64(table, 8)? as usize;
+    let strings = HEADER_SIZE.checked_add(count.checked_mul(ENTRY_SIZE)?)?;
+    let entry = |idx: usize| {
+        let off = HEADER_SIZE + idx * ENTRY_SIZE;
+
+        Some((
+            base + read_u32(table, off)? as usize,
+            read_u32(table, off + 4)? as usize,
+            read_u32(table, off + 8)? as usize,
+        ))
+    };
+
+    // Entries are sorted by address. Find the last one starting at or below
+    // the address.
+    let (mut lo, mut hi) = (0, count);
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        if entry(mid)?.0 <= addr {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let (start, size, name) = entry(lo.checked_sub(1)?)?;
+
+    if addr - start >= size {
+        return None;
+    }
+
+    let name = table.get(strings.checked_add(name)?..)?;
+    let len = name.iter().position(|byte| *byte == 0)?;
+
+    Some(Symbol {
+        name: core::str::from_utf8(&name[..len]).ok()?,
+        addr: start,
+        size,
+    })
+}
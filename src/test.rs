@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+//! Runner of `#[test_case]` functions, for test builds of lunar.
+//!
+//! Tests run on the boot core once [`kentry`](crate::kentry) has initialized
+//! the system, each reported as it completes. A panic fails the running test
+//! and ends the run, see [`fail`].
+//!
+//! The outcome is reported to the host as an exit code through semihosting,
+//! if enabled. Otherwise the system is powered off and the host has to tell
+//! the outcome from the summary printed last, as `tools/qemu-run` does.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::console::{self, eprintln, print, println};
+#[cfg(not(feature = "semihosting"))]
+use crate::platform;
+#[cfg(feature = "semihosting")]
+use crate::semihosting;
+
+/// Number of tests which passed so far.
+static PASSED: AtomicUsize = AtomicUsize::new(0);
+
+/// A test which reports its own outcome.
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        let name = core::any::type_name::<T>();
+        let name = name
+            .strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::"))
+            .unwrap_or(name);
+
+        print!("test {name} ... ");
+        self();
+        println!("ok");
+
+        PASSED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Run all tests and report the outcome to the host.
+///
+/// Never returns, a test failing ends the run early.
+///
+/// # Arguments
+///
+/// - `tests`: Tests collected from `#[test_case]` functions.
+pub fn run(tests: &[&dyn Testable]) -> ! {
+    println!("\nrunning {} tests", tests.len());
+
+    for test in tests {
+        test.run();
+    }
+
+    println!("\ntest result: ok. {} passed; 0 failed", tests.len());
+
+    exit(true)
+}
+
+/// Fail the running test and end the run.
+///
+/// Called by the panic handler once the panic is reported.
+pub fn fail() -> ! {
+    eprintln!(
+        "\ntest result: FAILED. {} passed; 1 failed",
+        PASSED.load(Ordering::Relaxed)
+    );
+
+    exit(false)
+}
+
+/// Stop the system, reporting the outcome of the run to the host.
+///
+/// Without semihosting, the outcome is only known from the summary printed.
+#[cfg_attr(not(feature = "semihosting"), allow(unused_variables))]
+fn exit(success: bool) -> ! {
+    console::flush();
+
+    #[cfg(feature = "semihosting")]
+    semihosting::exit(u32::from(!success));
+
+    #[cfg(not(feature = "semihosting"))]
+    platform::poweroff()
+}
@@ -0,0 +1,275 @@
+// SPDX-FileCopyrightText: 2026 Duszku <duszku511@gmail.com>
+// SPDX-License-Identifier: EUPL-1.2
+
+use core::ops::{Add, Sub};
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use core::time::Duration;
+
+use crate::arch::timer;
+use crate::irq;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Frequency of the counter backing [`Instant`], in Hz.
+///
+/// Zero until [`init`] is called.
+static FREQUENCY: AtomicU64 = AtomicU64::new(0);
+
+/// Function called when the alarm set with [`set_alarm`] goes off.
+static ALARM: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Whether the alarm set with [`set_alarm`] is yet to go off.
+static ARMED: AtomicBool = AtomicBool::new(false);
+
+/// A handler of the alarm.
+pub type AlarmHandler = fn();
+
+/// A point in time, as measured by a monotonic counter running since reset.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Instant(u64);
+
+/// A point in time after which an operation is considered to have timed out.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Deadline(Instant);
+
+/// Set up time keeping.
+///
+/// Must be called after [`crate::fdt::init`], as frequency of the counter may
+/// be overridden by the devicetree.
+pub fn init() {
+    FREQUENCY.store(timer::frequency(), Ordering::Relaxed);
+}
+
+/// Obtain frequency of the counter backing [`Instant`], in Hz.
+pub fn frequency() -> u64 {
+    FREQUENCY.load(Ordering::Relaxed)
+}
+
+fn ticks_to_duration(ticks: u64) -> Duration {
+    let freq = frequency().max(1) as u128;
+    let nanos = ticks as u128 * NANOS_PER_SEC / freq;
+
+    Duration::new(
+        (nanos / NANOS_PER_SEC) as u64,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+}
+
+fn duration_to_ticks(duration: Duration) -> u64 {
+    let ticks = duration.as_nanos() * frequency() as u128;
+
+    ticks.div_ceil(NANOS_PER_SEC).try_into().unwrap_or(u64::MAX)
+}
+
+impl Instant {
+    /// Obtain the current point in time.
+    pub fn now() -> Self {
+        Instant(timer::counter())
+    }
+
+    /// Obtain the raw value of the counter at this point in time.
+    pub const fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// Obtain time elapsed since an earlier point, or zero if it is later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        ticks_to_duration(self.0.saturating_sub(earlier.0))
+    }
+
+    /// Obtain time elapsed since this point in time.
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().duration_since(*self)
+    }
+
+    /// Obtain a point in time a given duration later, if representable.
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration_to_ticks(duration)).map(Instant)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    /// Saturates at the end of the counter range, instead of overflowing.
+    fn add(self, rhs: Duration) -> Instant {
+        Instant(self.0.saturating_add(duration_to_ticks(rhs)))
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
+
+impl Deadline {
+    /// Construct a deadline a given duration from now.
+    pub fn after(timeout: Duration) -> Self {
+        Deadline(Instant::now() + timeout)
+    }
+
+    /// Construct a deadline at a given point in time.
+    pub const fn at(instant: Instant) -> Self {
+        Deadline(instant)
+    }
+
+    /// Check whether the deadline has passed.
+    pub fn expired(&self) -> bool {
+        Instant::now() >= self.0
+    }
+
+    /// Obtain time left until the deadline, or zero if it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.0.duration_since(Instant::now())
+    }
+}
+
+/// Obtain time elapsed since the counter was reset, usually at power-on.
+pub fn uptime() -> Duration {
+    ticks_to_duration(timer::counter())
+}
+
+/// Busy-wait for at least a given duration.
+pub fn delay(duration: Duration) {
+    let deadline = Deadline::after(duration);
+
+    while !deadline.expired() {
+        core::hint::spin_loop();
+    }
+}
+
+/// Repeatedly call a function until it yields a value or a timeout expires.
+///
+/// The function is always called at least once, even with a zero timeout, and
+/// once more after the timeout, so that a slow poll is never misreported.
+///
+/// # Arguments
+///
+/// - `timeout`: Time after which polling is abandoned.
+/// - `f`: Function returning `Some` once the awaited condition is met.
+pub fn poll<T>(
+    timeout: Duration,
+    mut f: impl FnMut() -> Option<T>,
+) -> Option<T> {
+    let deadline = Deadline::after(timeout);
+
+    loop {
+        let expired = deadline.expired();
+
+        if let Some(val) = f() {
+            return Some(val);
+        }
+
+        if expired {
+            return None;
+        }
+
+        core::hint::spin_loop();
+    }
+}
+
+/// Wait until a condition holds or a timeout expires.
+///
+/// Returns `false` on timeout. See [`poll`] for details.
+pub fn wait_until(timeout: Duration, mut cond: impl FnMut() -> bool) -> bool {
+    poll(timeout, || cond().then_some(())).is_some()
+}
+
+/// Call a function from the timer interrupt once a given duration elapses.
+///
+/// Setting an alarm replaces the previous one. The timer interrupt is only
+/// delivered to the calling core, and only if interrupts are unmasked.
+pub fn set_alarm(
+    after: Duration,
+    handler: AlarmHandler,
+) -> Result<(), irq::Error> {
+    timer::cancel_alarm();
+
+    let first = ALARM.swap(handler as *mut (), Ordering::AcqRel).is_null();
+
+    if first && let Err(err) = timer::request_irq(on_alarm) {
+        ALARM.store(core::ptr::null_mut(), Ordering::Release);
+        return Err(err);
+    }
+
+    ARMED.store(true, Ordering::Release);
+    timer::set_alarm((Instant::now() + after).ticks());
+
+    Ok(())
+}
+
+/// Cancel the alarm set with [`set_alarm`], if it has not gone off yet.
+pub fn cancel_alarm() {
+    timer::cancel_alarm();
+    ARMED.store(false, Ordering::Release);
+}
+
+/// Check whether an alarm set with [`set_alarm`] is yet to go off.
+pub fn alarm_armed() -> bool {
+    ARMED.load(Ordering::Acquire)
+}
+
+fn on_alarm(_irq: u32) {
+    timer::cancel_alarm();
+    ARMED.store(false, Ordering::Release);
+
+    let ptr = ALARM.load(Ordering::Acquire);
+    if !ptr.is_null() {
+        let handler: AlarmHandler = unsafe { core::mem::transmute(ptr) };
+        handler();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    /// Set by [`fire`].
+    static FIRED: AtomicBool = AtomicBool::new(false);
+
+    fn fire() {
+        FIRED.store(true, Ordering::Release);
+    }
+
+    #[test_case]
+    fn alarm_fires() {
+        FIRED.store(false, Ordering::Release);
+        set_alarm(Duration::from_millis(1), fire).unwrap();
+
+        let fired = wait_until(Duration::from_secs(1), || {
+            FIRED.load(Ordering::Acquire)
+        });
+
+        cancel_alarm();
+        assert!(fired);
+    }
+
+    #[test_case]
+    fn cancelled_alarm_does_not_fire() {
+        FIRED.store(false, Ordering::Release);
+        set_alarm(Duration::from_millis(1), fire).unwrap();
+        cancel_alarm();
+
+        delay(Duration::from_millis(10));
+        assert!(!FIRED.load(Ordering::Acquire));
+    }
+
+    #[test_case]
+    fn alarm_armed_until_fired() {
+        FIRED.store(false, Ordering::Release);
+        set_alarm(Duration::from_millis(1), fire).unwrap();
+        assert!(alarm_armed());
+
+        wait_until(Duration::from_secs(1), || FIRED.load(Ordering::Acquire));
+        assert!(!alarm_armed());
+
+        set_alarm(Duration::from_secs(1), fire).unwrap();
+        cancel_alarm();
+        assert!(!alarm_armed());
+    }
+}